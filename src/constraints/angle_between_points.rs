@@ -8,6 +8,7 @@ use tsify::Tsify;
 use crate::{
     constraints::ConstraintLike,
     primitives::{point2::Point2, PrimitiveCell},
+    units::Angle,
 };
 
 // This is a sketch constraint that makes the end point of an arc coincident with a point.
@@ -27,14 +28,13 @@ impl AngleBetweenPoints {
         point1: Rc<RefCell<Point2>>,
         point2: Rc<RefCell<Point2>>,
         middle_point: Rc<RefCell<Point2>>,
-        desired_angle: f64,
+        desired_angle: Angle,
     ) -> Self {
-        assert!(desired_angle.is_finite());
         Self {
             point1,
             point2,
             middle_point,
-            desired_angle,
+            desired_angle: desired_angle.to_radians(),
         }
     }
 
@@ -188,6 +188,7 @@ mod tests {
     use crate::constraints::ConstraintCell;
     use crate::primitives::PrimitiveCell;
     use crate::solvers::Solver;
+    use crate::units::Angle;
     use crate::{
         constraints::angle_between_points::AngleBetweenPoints, constraints::ConstraintLike,
         primitives::point2::Point2, sketch::Sketch,
@@ -215,7 +216,7 @@ mod tests {
             point_a.clone(),
             point_b.clone(),
             point_middle.clone(),
-            std::f64::consts::PI / 4.0,
+            Angle::degrees(45.0),
         )));
         sketch
             .add_constraint(ConstraintCell::AngleBetweenPoints(constr1.clone()))
@@ -253,7 +254,7 @@ mod tests {
             point_a.clone(),
             point_b.clone(),
             point_middle.clone(),
-            std::f64::consts::PI / 2.0,
+            Angle::degrees(90.0),
         )));
         sketch
             .add_constraint(ConstraintCell::AngleBetweenPoints(constr1.clone()))
@@ -0,0 +1,101 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{arc::Arc, PrimitiveCell},
+};
+
+// This is a sketch constraint that fixes the length of an arc, which is its radius times its sweep
+// angle.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct ArcLength {
+    arc: Rc<RefCell<Arc>>,
+
+    desired_length: f64,
+}
+
+impl ArcLength {
+    pub fn new(arc: Rc<RefCell<Arc>>, desired_length: f64) -> Self {
+        assert!(desired_length.is_finite());
+        Self {
+            arc,
+            desired_length,
+        }
+    }
+
+    pub fn arc(&self) -> Rc<RefCell<Arc>> {
+        self.arc.clone()
+    }
+
+    pub fn set_arc(&mut self, arc: Rc<RefCell<Arc>>) {
+        self.arc = arc;
+    }
+
+    pub fn desired_length(&self) -> f64 {
+        self.desired_length
+    }
+
+    pub fn set_desired_length(&mut self, desired_length: f64) {
+        self.desired_length = desired_length;
+    }
+}
+
+impl ConstraintLike for ArcLength {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![PrimitiveCell::Arc(self.arc.clone())]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.arc.borrow().arc_length() - self.desired_length;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = self.arc.borrow().arc_length() - self.desired_length;
+        let grad_arc = err * self.arc.borrow().arc_length_gradient();
+        self.arc.borrow_mut().add_to_gradient(grad_arc.as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::ArcLength(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_arc_length() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.5, 1.0)?;
+
+        let constr1 = sketch.constrain_arc_length(arc.clone(), 3.0)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((arc.borrow().arc_length() - 3.0).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{arc::Arc, PrimitiveCell},
+    units::Angle,
+};
+
+// This is a sketch constraint that fixes the angle an arc sweeps from its start to its end. The
+// sweep follows the direction of the arc and wraps around at 2 * PI, so it lies in [0, 2 * PI).
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct ArcSweepAngle {
+    arc: Rc<RefCell<Arc>>,
+
+    desired_angle: f64,
+}
+
+impl ArcSweepAngle {
+    pub fn new(arc: Rc<RefCell<Arc>>, desired_angle: Angle) -> Self {
+        Self {
+            arc,
+            desired_angle: desired_angle.to_radians(),
+        }
+    }
+
+    pub fn arc(&self) -> Rc<RefCell<Arc>> {
+        self.arc.clone()
+    }
+
+    pub fn set_arc(&mut self, arc: Rc<RefCell<Arc>>) {
+        self.arc = arc;
+    }
+
+    pub fn desired_angle(&self) -> f64 {
+        self.desired_angle
+    }
+
+    pub fn set_desired_angle(&mut self, desired_angle: f64) {
+        self.desired_angle = desired_angle;
+    }
+}
+
+impl ConstraintLike for ArcSweepAngle {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![PrimitiveCell::Arc(self.arc.clone())]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.arc.borrow().sweep_angle() - self.desired_angle;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = self.arc.borrow().sweep_angle() - self.desired_angle;
+        let grad_arc = err * self.arc.borrow().sweep_angle_gradient();
+        self.arc.borrow_mut().add_to_gradient(grad_arc.as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::ArcSweepAngle(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::units::Angle;
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_arc_sweep_angle() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.5, 1.0)?;
+
+        let constr1 = sketch.constrain_arc_sweep_angle(arc.clone(), Angle::degrees(90.0))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clockwise_arc_sweep_angle() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        // The clockwise arc from 3.0 to -3.0 sweeps 6 radians, the long way around
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, true, 3.0, -3.0)?;
+        assert!((arc.borrow().sweep_angle() - 6.0).abs() < 1e-12);
+
+        let constr1 = sketch.constrain_arc_sweep_angle(arc.clone(), Angle::degrees(270.0))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
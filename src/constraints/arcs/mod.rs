@@ -0,0 +1,2 @@
+pub mod arc_length;
+pub mod arc_sweep_angle;
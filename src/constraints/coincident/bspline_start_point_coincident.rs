@@ -0,0 +1,136 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::SMatrix;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{bspline::BSpline, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes the start point of a B-spline coincident with a point.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct BSplineStartPointCoincident {
+    spline: Rc<RefCell<BSpline>>,
+    point: Rc<RefCell<Point2>>,
+}
+
+impl BSplineStartPointCoincident {
+    pub fn new(spline: Rc<RefCell<BSpline>>, point: Rc<RefCell<Point2>>) -> Self {
+        Self { spline, point }
+    }
+
+    pub fn spline(&self) -> Rc<RefCell<BSpline>> {
+        self.spline.clone()
+    }
+
+    pub fn set_spline(&mut self, spline: Rc<RefCell<BSpline>>) {
+        self.spline = spline;
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+}
+
+impl ConstraintLike for BSplineStartPointCoincident {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::BSpline(self.spline.clone()),
+            PrimitiveCell::Point2(self.point.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let spline_start = self.spline.borrow().start_point();
+        let point = self.point.borrow().data();
+        let dx = spline_start.x - point.x;
+        let dy = spline_start.y - point.y;
+        0.5 * (dx * dx + dy * dy)
+    }
+
+    fn update_gradient(&mut self) {
+        let spline_start = self.spline.borrow().start_point();
+        let point = self.point.borrow().data();
+        let dx = spline_start.x - point.x;
+        let dy = spline_start.y - point.y;
+
+        let gradient_constraint = SMatrix::<f64, 1, 2>::from_row_slice(&[dx, dy]);
+
+        let grad_spline = self.spline.borrow().start_point_gradient();
+        let grad_point = self.point.borrow().point_gradient();
+
+        self.spline
+            .borrow_mut()
+            .add_to_gradient((gradient_constraint * grad_spline).as_view());
+        self.point
+            .borrow_mut()
+            .add_to_gradient((-gradient_constraint * grad_point).as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::BSplineStartPointCoincident(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            coincident::bspline_start_point_coincident::BSplineStartPointCoincident,
+            ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_bspline_start_point_coincident() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let control_point0 = sketch.add_point2(0.0, 0.0)?;
+        let control_point1 = sketch.add_point2(1.0, 2.0)?;
+        let control_point2 = sketch.add_point2(2.0, -1.0)?;
+        let control_point3 = sketch.add_point2(3.0, 0.5)?;
+        let spline = sketch.add_bspline(
+            vec![
+                control_point0,
+                control_point1,
+                control_point2,
+                control_point3,
+            ],
+            2,
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            Some(vec![1.0, 2.0, 0.5, 1.0]),
+        )?;
+        let point = sketch.add_point2(3.0, 4.0)?;
+
+        let constr1 = Rc::new(RefCell::new(BSplineStartPointCoincident::new(
+            spline.clone(),
+            point.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::BSplineStartPointCoincident(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("spline: {:?}", spline.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
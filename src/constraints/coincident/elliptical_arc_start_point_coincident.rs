@@ -0,0 +1,125 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::SMatrix;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{elliptical_arc::EllipticalArc, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes the start point of an elliptical arc coincident with a point.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EllipticalArcStartPointCoincident {
+    arc: Rc<RefCell<EllipticalArc>>,
+    point: Rc<RefCell<Point2>>,
+}
+
+impl EllipticalArcStartPointCoincident {
+    pub fn new(arc: Rc<RefCell<EllipticalArc>>, point: Rc<RefCell<Point2>>) -> Self {
+        Self { arc, point }
+    }
+
+    pub fn arc(&self) -> Rc<RefCell<EllipticalArc>> {
+        self.arc.clone()
+    }
+
+    pub fn set_arc(&mut self, arc: Rc<RefCell<EllipticalArc>>) {
+        self.arc = arc;
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+}
+
+impl ConstraintLike for EllipticalArcStartPointCoincident {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::EllipticalArc(self.arc.clone()),
+            PrimitiveCell::Point2(self.point.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let arc_start = self.arc.borrow().start_point();
+        let point = self.point.borrow().data();
+        let dx = arc_start.x - point.x;
+        let dy = arc_start.y - point.y;
+        0.5 * (dx * dx + dy * dy)
+    }
+
+    fn update_gradient(&mut self) {
+        let arc_start = self.arc.borrow().start_point();
+        let point = self.point.borrow().data();
+        let dx = arc_start.x - point.x;
+        let dy = arc_start.y - point.y;
+
+        let gradient_constraint = SMatrix::<f64, 1, 2>::from_row_slice(&[dx, dy]);
+
+        let grad_arc = self.arc.borrow().start_point_gradient();
+        let grad_point = self.point.borrow().point_gradient();
+
+        self.arc
+            .borrow_mut()
+            .add_to_gradient((gradient_constraint * grad_arc).as_view());
+        self.point
+            .borrow_mut()
+            .add_to_gradient((-gradient_constraint * grad_point).as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::EllipticalArcStartPointCoincident(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            coincident::elliptical_arc_start_point_coincident::EllipticalArcStartPointCoincident,
+            ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_elliptical_arc_start_point_coincident() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_elliptical_arc(center.clone(), 2.0, 1.0, 0.3, false, 0.2, 2.5)?;
+        let point = sketch.add_point2(3.0, 4.0)?;
+
+        let constr1 = Rc::new(RefCell::new(EllipticalArcStartPointCoincident::new(
+            arc.clone(),
+            point.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::EllipticalArcStartPointCoincident(
+            constr1.clone(),
+        ))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -1,2 +1,7 @@
 pub mod arc_end_point_coincident;
 pub mod arc_start_point_coincident;
+pub mod bspline_end_point_coincident;
+pub mod bspline_start_point_coincident;
+pub mod elliptical_arc_end_point_coincident;
+pub mod elliptical_arc_start_point_coincident;
+pub mod point_on_ellipse;
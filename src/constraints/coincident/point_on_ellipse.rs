@@ -0,0 +1,147 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::{Matrix2, SMatrix};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{ellipse::Ellipse, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes a point lie on the circumference of an ellipse. It uses
+// the implicit ellipse equation (u / radius_x)^2 + (v / radius_y)^2 = 1, where (u, v) are the
+// coordinates of the point in the frame of the ellipse.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct PointOnEllipse {
+    ellipse: Rc<RefCell<Ellipse>>,
+    point: Rc<RefCell<Point2>>,
+}
+
+impl PointOnEllipse {
+    pub fn new(ellipse: Rc<RefCell<Ellipse>>, point: Rc<RefCell<Point2>>) -> Self {
+        Self { ellipse, point }
+    }
+
+    pub fn ellipse(&self) -> Rc<RefCell<Ellipse>> {
+        self.ellipse.clone()
+    }
+
+    pub fn set_ellipse(&mut self, ellipse: Rc<RefCell<Ellipse>>) {
+        self.ellipse = ellipse;
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+
+    fn implicit_value(&self) -> f64 {
+        let ellipse = self.ellipse.borrow();
+        let d = self.point.borrow().data() - ellipse.center().borrow().data();
+        let (sin_r, cos_r) = ellipse.rotation().sin_cos();
+        let u = cos_r * d.x + sin_r * d.y;
+        let v = -sin_r * d.x + cos_r * d.y;
+        (u / ellipse.radius_x()).powi(2) + (v / ellipse.radius_y()).powi(2) - 1.0
+    }
+}
+
+impl ConstraintLike for PointOnEllipse {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Ellipse(self.ellipse.clone()),
+            PrimitiveCell::Point2(self.point.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let f = self.implicit_value();
+        0.5 * f * f
+    }
+
+    fn update_gradient(&mut self) {
+        let f = self.implicit_value();
+
+        let ellipse = self.ellipse.borrow().clone();
+        let d = self.point.borrow().data() - ellipse.center().borrow().data();
+        let (sin_r, cos_r) = ellipse.rotation().sin_cos();
+        let u = cos_r * d.x + sin_r * d.y;
+        let v = -sin_r * d.x + cos_r * d.y;
+        let rx = ellipse.radius_x();
+        let ry = ellipse.radius_y();
+
+        let grad_f_from_uv =
+            SMatrix::<f64, 1, 2>::from_row_slice(&[2.0 * u / (rx * rx), 2.0 * v / (ry * ry)]);
+        let grad_uv_from_d = Matrix2::new(cos_r, sin_r, -sin_r, cos_r);
+        let grad_uv_from_rotation = SMatrix::<f64, 2, 1>::new(v, -u);
+
+        let grad_f_from_d = grad_f_from_uv * grad_uv_from_d;
+        let grad_f_from_rotation = (grad_f_from_uv * grad_uv_from_rotation)[0];
+        let grad_f_from_rx = -2.0 * u * u / (rx * rx * rx);
+        let grad_f_from_ry = -2.0 * v * v / (ry * ry * ry);
+
+        let grad_ellipse = f
+            * (-grad_f_from_d * ellipse.center_gradient()
+                + grad_f_from_rx * ellipse.radius_x_gradient()
+                + grad_f_from_ry * ellipse.radius_y_gradient()
+                + grad_f_from_rotation * ellipse.rotation_gradient());
+        let grad_point = f * grad_f_from_d * self.point.borrow().point_gradient();
+
+        self.ellipse
+            .borrow_mut()
+            .add_to_gradient(grad_ellipse.as_view());
+        self.point
+            .borrow_mut()
+            .add_to_gradient(grad_point.as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::PointOnEllipse(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            coincident::point_on_ellipse::PointOnEllipse, ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_point_on_ellipse() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.5, -0.2)?;
+        let ellipse = sketch.add_ellipse(center.clone(), 2.0, 1.0, 0.4)?;
+        let point = sketch.add_point2(2.0, 1.0)?;
+
+        let constr1 = Rc::new(RefCell::new(PointOnEllipse::new(
+            ellipse.clone(),
+            point.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::PointOnEllipse(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("ellipse: {:?}", ellipse.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
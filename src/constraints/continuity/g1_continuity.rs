@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{
+        continuity::{tangent_error, ContinuityCurve},
+        ConstraintLike,
+    },
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that makes two segments meet with the same tangent direction (G1
+// continuity). The segments have to share the joint, e.g. through a common point or a coincident
+// constraint.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct G1Continuity {
+    curve1: ContinuityCurve,
+    curve2: ContinuityCurve,
+}
+
+impl G1Continuity {
+    pub fn new(curve1: ContinuityCurve, curve2: ContinuityCurve) -> Self {
+        Self { curve1, curve2 }
+    }
+
+    pub fn curve1(&self) -> ContinuityCurve {
+        self.curve1.clone()
+    }
+
+    pub fn set_curve1(&mut self, curve1: ContinuityCurve) {
+        self.curve1 = curve1;
+    }
+
+    pub fn curve2(&self) -> ContinuityCurve {
+        self.curve2.clone()
+    }
+
+    pub fn set_curve2(&mut self, curve2: ContinuityCurve) {
+        self.curve2 = curve2;
+    }
+}
+
+impl ConstraintLike for G1Continuity {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve1.primitive(), self.curve2.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let end1 = self.curve1.end_derivatives();
+        let end2 = self.curve2.end_derivatives();
+        match tangent_error(&end1.first, &end2.first) {
+            Some((error, _, _)) => error,
+            None => 0.0,
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        let end1 = self.curve1.end_derivatives();
+        let end2 = self.curve2.end_derivatives();
+        let Some((_, grad_from_first1, grad_from_first2)) = tangent_error(&end1.first, &end2.first)
+        else {
+            return;
+        };
+
+        self.curve1
+            .add_to_gradient(&(grad_from_first1 * &end1.first_gradient));
+        self.curve2
+            .add_to_gradient(&(grad_from_first2 * &end2.first_gradient));
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::G1Continuity(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            continuity::{g1_continuity::G1Continuity, ContinuityCurve, CurveEnd},
+            ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_g1_continuity_line_bezier() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let point_a = sketch.add_point2(-2.0, 0.3)?;
+        let point_b = sketch.add_point2(0.0, 0.0)?;
+        let control1 = sketch.add_point2(0.5, 1.0)?;
+        let control2 = sketch.add_point2(1.5, 1.0)?;
+        let point_c = sketch.add_point2(2.0, 0.0)?;
+        let line = sketch.add_line(point_a, point_b.clone())?;
+        let bezier = sketch.add_bezier(point_b, control1, control2, point_c)?;
+
+        let constr1 = Rc::new(RefCell::new(G1Continuity::new(
+            ContinuityCurve::Line(line.clone(), CurveEnd::End),
+            ContinuityCurve::Bezier(bezier.clone(), CurveEnd::Start),
+        )));
+        sketch.add_constraint(ConstraintCell::G1Continuity(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("bezier: {:?}", bezier.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_g1_continuity_arc_bezier() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, true, 2.0, 0.3)?;
+        let start = sketch.add_point2(1.0, 0.2)?;
+        let control1 = sketch.add_point2(1.5, 0.5)?;
+        let control2 = sketch.add_point2(2.5, 0.5)?;
+        let end = sketch.add_point2(3.0, 0.0)?;
+        let bezier = sketch.add_bezier(start, control1, control2, end)?;
+
+        let constr1 = Rc::new(RefCell::new(G1Continuity::new(
+            ContinuityCurve::Arc(arc.clone(), CurveEnd::End),
+            ContinuityCurve::Bezier(bezier.clone(), CurveEnd::Start),
+        )));
+        sketch.add_constraint(ConstraintCell::G1Continuity(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+        println!("bezier: {:?}", bezier.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{
+        continuity::{curvature, tangent_error, ContinuityCurve},
+        ConstraintLike,
+    },
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that makes two segments meet with the same tangent direction and the
+// same curvature (G2 continuity). The segments have to share the joint, e.g. through a common point
+// or a coincident constraint.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct G2Continuity {
+    curve1: ContinuityCurve,
+    curve2: ContinuityCurve,
+}
+
+impl G2Continuity {
+    pub fn new(curve1: ContinuityCurve, curve2: ContinuityCurve) -> Self {
+        Self { curve1, curve2 }
+    }
+
+    pub fn curve1(&self) -> ContinuityCurve {
+        self.curve1.clone()
+    }
+
+    pub fn set_curve1(&mut self, curve1: ContinuityCurve) {
+        self.curve1 = curve1;
+    }
+
+    pub fn curve2(&self) -> ContinuityCurve {
+        self.curve2.clone()
+    }
+
+    pub fn set_curve2(&mut self, curve2: ContinuityCurve) {
+        self.curve2 = curve2;
+    }
+}
+
+impl ConstraintLike for G2Continuity {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve1.primitive(), self.curve2.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let end1 = self.curve1.end_derivatives();
+        let end2 = self.curve2.end_derivatives();
+        let (Some((tangent, _, _)), Some((curvature1, _, _)), Some((curvature2, _, _))) = (
+            tangent_error(&end1.first, &end2.first),
+            curvature(&end1.first, &end1.second),
+            curvature(&end2.first, &end2.second),
+        ) else {
+            return 0.0;
+        };
+
+        // Both parameterizations run away from the joint, so the curvatures have opposite signs
+        let curvature_error = curvature1 + curvature2;
+        tangent + 0.5 * curvature_error * curvature_error
+    }
+
+    fn update_gradient(&mut self) {
+        let end1 = self.curve1.end_derivatives();
+        let end2 = self.curve2.end_derivatives();
+        let (
+            Some((_, grad_tangent_from_first1, grad_tangent_from_first2)),
+            Some((curvature1, grad_curvature1_from_first1, grad_curvature1_from_second1)),
+            Some((curvature2, grad_curvature2_from_first2, grad_curvature2_from_second2)),
+        ) = (
+            tangent_error(&end1.first, &end2.first),
+            curvature(&end1.first, &end1.second),
+            curvature(&end2.first, &end2.second),
+        )
+        else {
+            return;
+        };
+        let curvature_error = curvature1 + curvature2;
+
+        let grad_from_first1 =
+            grad_tangent_from_first1 + curvature_error * grad_curvature1_from_first1;
+        let grad_from_second1 = curvature_error * grad_curvature1_from_second1;
+        let grad_from_first2 =
+            grad_tangent_from_first2 + curvature_error * grad_curvature2_from_first2;
+        let grad_from_second2 = curvature_error * grad_curvature2_from_second2;
+
+        self.curve1.add_to_gradient(
+            &(grad_from_first1 * &end1.first_gradient + grad_from_second1 * &end1.second_gradient),
+        );
+        self.curve2.add_to_gradient(
+            &(grad_from_first2 * &end2.first_gradient + grad_from_second2 * &end2.second_gradient),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::G2Continuity(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            continuity::{g2_continuity::G2Continuity, ContinuityCurve, CurveEnd},
+            ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_g2_continuity_bezier_bezier() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(-3.0, 0.0)?;
+        let control1 = sketch.add_point2(-2.0, 1.5)?;
+        let control2 = sketch.add_point2(-1.0, 0.8)?;
+        let joint = sketch.add_point2(0.0, 0.0)?;
+        let control3 = sketch.add_point2(0.7, 0.4)?;
+        let control4 = sketch.add_point2(2.0, -1.0)?;
+        let end = sketch.add_point2(3.0, 0.0)?;
+        let bezier1 = sketch.add_bezier(start, control1, control2, joint.clone())?;
+        let bezier2 = sketch.add_bezier(joint, control3, control4, end)?;
+
+        let constr1 = Rc::new(RefCell::new(G2Continuity::new(
+            ContinuityCurve::Bezier(bezier1.clone(), CurveEnd::End),
+            ContinuityCurve::Bezier(bezier2.clone(), CurveEnd::Start),
+        )));
+        sketch.add_constraint(ConstraintCell::G2Continuity(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-4);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("bezier1: {:?}", bezier1.as_ref().borrow());
+        println!("bezier2: {:?}", bezier2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_g2_continuity_arc_bezier() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.2, 1.5)?;
+        let start = sketch.add_point2(1.0, 0.2)?;
+        let control1 = sketch.add_point2(1.1, -0.5)?;
+        let control2 = sketch.add_point2(2.0, -1.0)?;
+        let end = sketch.add_point2(3.0, -1.0)?;
+        let bezier = sketch.add_bezier(start, control1, control2, end)?;
+
+        let constr1 = Rc::new(RefCell::new(G2Continuity::new(
+            ContinuityCurve::Arc(arc.clone(), CurveEnd::Start),
+            ContinuityCurve::Bezier(bezier.clone(), CurveEnd::Start),
+        )));
+        sketch.add_constraint(ConstraintCell::G2Continuity(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-4);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+        println!("bezier: {:?}", bezier.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::{Matrix2, Matrix2xX, RowDVector, SMatrix, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::primitives::{arc::Arc, bezier::Bezier, line::Line, PrimitiveCell};
+
+pub mod g1_continuity;
+pub mod g2_continuity;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum CurveEnd {
+    Start,
+    End,
+}
+
+// A segment that can take part in a continuity constraint, together with the end at which it
+// meets the other segment.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum ContinuityCurve {
+    Line(Rc<RefCell<Line>>, CurveEnd),
+    Arc(Rc<RefCell<Arc>>, CurveEnd),
+    Bezier(Rc<RefCell<Bezier>>, CurveEnd),
+}
+
+// First and second derivative at the joining end of a curve, for a parameterization that runs away
+// from the joint, and their gradients by the parameters of the primitive.
+pub(crate) struct EndDerivatives {
+    pub(crate) first: Vector2<f64>,
+    pub(crate) second: Vector2<f64>,
+    pub(crate) first_gradient: Matrix2xX<f64>,
+    pub(crate) second_gradient: Matrix2xX<f64>,
+}
+
+impl ContinuityCurve {
+    pub fn primitive(&self) -> PrimitiveCell {
+        match self {
+            ContinuityCurve::Line(line, _) => PrimitiveCell::Line(line.clone()),
+            ContinuityCurve::Arc(arc, _) => PrimitiveCell::Arc(arc.clone()),
+            ContinuityCurve::Bezier(bezier, _) => PrimitiveCell::Bezier(bezier.clone()),
+        }
+    }
+
+    pub fn end(&self) -> CurveEnd {
+        match self {
+            ContinuityCurve::Line(_, end)
+            | ContinuityCurve::Arc(_, end)
+            | ContinuityCurve::Bezier(_, end) => *end,
+        }
+    }
+
+    pub(crate) fn end_derivatives(&self) -> EndDerivatives {
+        // Reversing the parameterization flips the first derivative but keeps the second one
+        let sign = match self.end() {
+            CurveEnd::Start => 1.0,
+            CurveEnd::End => -1.0,
+        };
+
+        match self {
+            ContinuityCurve::Line(line, _) => {
+                let line = line.borrow();
+                let direction = line.end().borrow().data() - line.start().borrow().data();
+                EndDerivatives {
+                    first: sign * direction,
+                    second: Vector2::zeros(),
+                    first_gradient: Matrix2xX::from_iterator(
+                        4,
+                        (sign * (line.end_gradient() - line.start_gradient()))
+                            .iter()
+                            .copied(),
+                    ),
+                    second_gradient: Matrix2xX::zeros(4),
+                }
+            }
+            ContinuityCurve::Arc(arc, end) => {
+                let arc = arc.borrow();
+                // Arcs run counter-clockwise with the angle unless they are clockwise
+                let sign = if arc.clockwise() { -sign } else { sign };
+                let (angle, angle_index) = match end {
+                    CurveEnd::Start => (arc.start_angle(), 3),
+                    CurveEnd::End => (arc.end_angle(), 4),
+                };
+                let radius = arc.radius();
+                let (sin, cos) = angle.sin_cos();
+
+                let mut first_gradient = SMatrix::<f64, 2, 5>::zeros();
+                first_gradient[(0, 2)] = -sign * sin;
+                first_gradient[(1, 2)] = sign * cos;
+                first_gradient[(0, angle_index)] = -sign * radius * cos;
+                first_gradient[(1, angle_index)] = -sign * radius * sin;
+
+                let mut second_gradient = SMatrix::<f64, 2, 5>::zeros();
+                second_gradient[(0, 2)] = -cos;
+                second_gradient[(1, 2)] = -sin;
+                second_gradient[(0, angle_index)] = radius * sin;
+                second_gradient[(1, angle_index)] = -radius * cos;
+
+                EndDerivatives {
+                    first: sign * radius * Vector2::new(-sin, cos),
+                    second: -radius * Vector2::new(cos, sin),
+                    first_gradient: Matrix2xX::from_iterator(5, first_gradient.iter().copied()),
+                    second_gradient: Matrix2xX::from_iterator(5, second_gradient.iter().copied()),
+                }
+            }
+            ContinuityCurve::Bezier(bezier, end) => {
+                let bezier = bezier.borrow();
+                let t = match end {
+                    CurveEnd::Start => 0.0,
+                    CurveEnd::End => 1.0,
+                };
+                EndDerivatives {
+                    first: sign * bezier.derivative_at(t),
+                    second: bezier.second_derivative_at(t),
+                    first_gradient: Matrix2xX::from_iterator(
+                        8,
+                        (sign * bezier.derivative_at_gradient(t)).iter().copied(),
+                    ),
+                    second_gradient: Matrix2xX::from_iterator(
+                        8,
+                        bezier.second_derivative_at_gradient(t).iter().copied(),
+                    ),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn add_to_gradient(&self, gradient: &RowDVector<f64>) {
+        match self {
+            ContinuityCurve::Line(line, _) => line.borrow_mut().add_to_gradient(
+                SMatrix::<f64, 1, 4>::from_iterator(gradient.iter().copied()).as_view(),
+            ),
+            ContinuityCurve::Arc(arc, _) => arc.borrow_mut().add_to_gradient(
+                SMatrix::<f64, 1, 5>::from_iterator(gradient.iter().copied()).as_view(),
+            ),
+            ContinuityCurve::Bezier(bezier, _) => bezier.borrow_mut().add_to_gradient(
+                SMatrix::<f64, 1, 8>::from_iterator(gradient.iter().copied()).as_view(),
+            ),
+        }
+    }
+}
+
+// The tangent error |u1 + u2|^2 / 2 of the unit tangents pointing away from the joint, which is
+// zero if the tangents are opposite, and its gradients by the two first derivatives. Returns None
+// for degenerate tangents.
+pub(crate) fn tangent_error(
+    first1: &Vector2<f64>,
+    first2: &Vector2<f64>,
+) -> Option<(f64, SMatrix<f64, 1, 2>, SMatrix<f64, 1, 2>)> {
+    let norm1 = first1.norm();
+    let norm2 = first2.norm();
+    if norm1 < 1e-12 || norm2 < 1e-12 {
+        return None;
+    }
+    let unit1 = first1 / norm1;
+    let unit2 = first2 / norm2;
+    let sum = unit1 + unit2;
+
+    let grad_unit1_from_first1 = (Matrix2::identity() - unit1 * unit1.transpose()) / norm1;
+    let grad_unit2_from_first2 = (Matrix2::identity() - unit2 * unit2.transpose()) / norm2;
+
+    Some((
+        0.5 * sum.norm_squared(),
+        sum.transpose() * grad_unit1_from_first1,
+        sum.transpose() * grad_unit2_from_first2,
+    ))
+}
+
+// The signed curvature (first x second) / |first|^3 and its gradients by the first and second
+// derivative. Returns None for a degenerate tangent.
+pub(crate) fn curvature(
+    first: &Vector2<f64>,
+    second: &Vector2<f64>,
+) -> Option<(f64, SMatrix<f64, 1, 2>, SMatrix<f64, 1, 2>)> {
+    let norm = first.norm();
+    if norm < 1e-12 {
+        return None;
+    }
+    let cross = first.x * second.y - first.y * second.x;
+    let curvature = cross / norm.powi(3);
+
+    let grad_from_first = SMatrix::<f64, 1, 2>::new(second.y, -second.x) / norm.powi(3)
+        - 3.0 * curvature * first.transpose() / (norm * norm);
+    let grad_from_second = SMatrix::<f64, 1, 2>::new(-first.y, first.x) / norm.powi(3);
+
+    Some((curvature, grad_from_first, grad_from_second))
+}
@@ -0,0 +1,197 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{distance::signed_distance, ConstraintLike},
+    primitives::{circle::Circle, line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that fixes the gap between the infinite extension of a line and the
+// circumference of a circle. A distance of zero makes the line tangent to the circle. A signed
+// distance keeps the circle on the left of the line, looking from its start to its end; reverse
+// the line to keep it on the right.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct LineCircleDistance {
+    line: Rc<RefCell<Line>>,
+    circle: Rc<RefCell<Circle>>,
+
+    desired_distance: f64,
+    signed: bool,
+}
+
+impl LineCircleDistance {
+    pub fn new(
+        line: Rc<RefCell<Line>>,
+        circle: Rc<RefCell<Circle>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Self {
+        assert!(desired_distance.is_finite());
+        Self {
+            line,
+            circle,
+            desired_distance,
+            signed,
+        }
+    }
+
+    pub fn line(&self) -> Rc<RefCell<Line>> {
+        self.line.clone()
+    }
+
+    pub fn set_line(&mut self, line: Rc<RefCell<Line>>) {
+        self.line = line;
+    }
+
+    pub fn circle(&self) -> Rc<RefCell<Circle>> {
+        self.circle.clone()
+    }
+
+    pub fn set_circle(&mut self, circle: Rc<RefCell<Circle>>) {
+        self.circle = circle;
+    }
+
+    pub fn desired_distance(&self) -> f64 {
+        self.desired_distance
+    }
+
+    pub fn set_desired_distance(&mut self, desired_distance: f64) {
+        self.desired_distance = desired_distance;
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+    }
+
+    pub fn current_distance(&self) -> f64 {
+        let line = self.line.borrow();
+        let circle = self.circle.borrow();
+        let start = line.start().borrow().data();
+        let end = line.end().borrow().data();
+        match signed_distance(&circle.center().borrow().data(), &start, &end) {
+            Some((distance, _, _, _)) if self.signed => distance - circle.radius(),
+            Some((distance, _, _, _)) => distance.abs() - circle.radius(),
+            None => 0.0,
+        }
+    }
+}
+
+impl ConstraintLike for LineCircleDistance {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line.clone()),
+            PrimitiveCell::Circle(self.circle.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.current_distance() - self.desired_distance;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let line = self.line.borrow().clone();
+        let circle = self.circle.borrow().clone();
+        let start = line.start().borrow().data();
+        let end = line.end().borrow().data();
+        let Some((distance, grad_from_center, grad_from_start, grad_from_end)) =
+            signed_distance(&circle.center().borrow().data(), &start, &end)
+        else {
+            return;
+        };
+
+        let err = self.current_distance() - self.desired_distance;
+        let grad_from_distance = if self.signed {
+            err
+        } else {
+            err * distance.signum()
+        };
+
+        self.line.borrow_mut().add_to_gradient(
+            (grad_from_distance
+                * (grad_from_start * line.start_gradient() + grad_from_end * line.end_gradient()))
+            .as_view(),
+        );
+        self.circle.borrow_mut().add_to_gradient(
+            (grad_from_distance * grad_from_center * circle.center_gradient()
+                - err * circle.radius_gradient())
+            .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::LineCircleDistance(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_line_circle_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(-2.0, 0.0)?;
+        let end = sketch.add_point2(2.0, 0.5)?;
+        let line = sketch.add_line(start, end)?;
+        let center = sketch.add_point2(0.5, 1.0)?;
+        let circle = sketch.add_circle(center, 0.5)?;
+
+        let constr1 =
+            sketch.constrain_distance_line_circle(line.clone(), circle.clone(), 0.0, false)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("circle: {:?}", circle.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_line_circle_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(-2.0, 0.0)?;
+        let end = sketch.add_point2(2.0, 0.5)?;
+        let line = sketch.add_line(start, end)?;
+        let center = sketch.add_point2(0.5, -1.0)?;
+        let circle = sketch.add_circle(center, 0.5)?;
+
+        // The circle starts on the right of the line and has to move to the left
+        let constr1 =
+            sketch.constrain_distance_line_circle(line.clone(), circle.clone(), 0.25, true)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("circle: {:?}", circle.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((constr1.borrow().current_distance() - 0.25).abs() < 1e-5);
+        Ok(())
+    }
+}
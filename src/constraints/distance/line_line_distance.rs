@@ -0,0 +1,178 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{distance::signed_distance, ConstraintLike},
+    primitives::{line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that fixes the offset between two parallel lines, measured from the
+// first line to the midpoint of the second. It does not make the lines parallel by itself, combine
+// it with a parallel constraint for that. A signed offset is positive on the left of the first
+// line, looking from its start to its end.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct LineLineDistance {
+    line1: Rc<RefCell<Line>>,
+    line2: Rc<RefCell<Line>>,
+
+    desired_distance: f64,
+    signed: bool,
+}
+
+impl LineLineDistance {
+    pub fn new(
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Self {
+        assert!(desired_distance.is_finite());
+        Self {
+            line1,
+            line2,
+            desired_distance,
+            signed,
+        }
+    }
+
+    pub fn line1(&self) -> Rc<RefCell<Line>> {
+        self.line1.clone()
+    }
+
+    pub fn set_line1(&mut self, line1: Rc<RefCell<Line>>) {
+        self.line1 = line1;
+    }
+
+    pub fn line2(&self) -> Rc<RefCell<Line>> {
+        self.line2.clone()
+    }
+
+    pub fn set_line2(&mut self, line2: Rc<RefCell<Line>>) {
+        self.line2 = line2;
+    }
+
+    pub fn desired_distance(&self) -> f64 {
+        self.desired_distance
+    }
+
+    pub fn set_desired_distance(&mut self, desired_distance: f64) {
+        self.desired_distance = desired_distance;
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+    }
+
+    pub fn current_distance(&self) -> f64 {
+        let line1 = self.line1.borrow();
+        let line2 = self.line2.borrow();
+        let midpoint = 0.5 * (line2.start().borrow().data() + line2.end().borrow().data());
+        let start = line1.start().borrow().data();
+        let end = line1.end().borrow().data();
+        match signed_distance(&midpoint, &start, &end) {
+            Some((distance, _, _, _)) if self.signed => distance,
+            Some((distance, _, _, _)) => distance.abs(),
+            None => 0.0,
+        }
+    }
+}
+
+impl ConstraintLike for LineLineDistance {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line1.clone()),
+            PrimitiveCell::Line(self.line2.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.current_distance() - self.desired_distance;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let line1 = self.line1.borrow().clone();
+        let line2 = self.line2.borrow().clone();
+        let midpoint = 0.5 * (line2.start().borrow().data() + line2.end().borrow().data());
+        let start = line1.start().borrow().data();
+        let end = line1.end().borrow().data();
+        let Some((distance, grad_from_midpoint, grad_from_start, grad_from_end)) =
+            signed_distance(&midpoint, &start, &end)
+        else {
+            return;
+        };
+
+        let err = self.current_distance() - self.desired_distance;
+        let grad_from_distance = if self.signed {
+            err
+        } else {
+            err * distance.signum()
+        };
+
+        self.line1.borrow_mut().add_to_gradient(
+            (grad_from_distance
+                * (grad_from_start * line1.start_gradient()
+                    + grad_from_end * line1.end_gradient()))
+            .as_view(),
+        );
+        self.line2.borrow_mut().add_to_gradient(
+            (0.5 * grad_from_distance
+                * grad_from_midpoint
+                * (line2.start_gradient() + line2.end_gradient()))
+            .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::LineLineDistance(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_line_line_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(3.0, 0.5)?;
+        let c = sketch.add_point2(0.5, 2.0)?;
+        let d = sketch.add_point2(2.5, 2.5)?;
+        let line1 = sketch.add_line(a, b)?;
+        let line2 = sketch.add_line(c, d)?;
+
+        let constr1 =
+            sketch.constrain_distance_line_line(line1.clone(), line2.clone(), 1.0, true)?;
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+
+        let parallel = sketch.constrain_parallel_lines(line1.clone(), line2.clone())?;
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line1: {:?}", line1.as_ref().borrow());
+        println!("line2: {:?}", line2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!(parallel.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
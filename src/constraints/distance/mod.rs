@@ -1,3 +1,37 @@
+use nalgebra::{RowVector2, Vector2};
+
 pub mod euclidian_distance_between_points;
 pub mod horizontal_distance_between_points;
+pub mod line_circle_distance;
+pub mod line_line_distance;
+pub mod point_circle_distance;
+pub mod point_line_distance;
 pub mod vertical_distance_between_points;
+
+// A signed distance and its gradients by the point, the start and the end of the line
+type SignedDistance = (f64, RowVector2<f64>, RowVector2<f64>, RowVector2<f64>);
+
+// The signed perpendicular distance of a point to the infinite line through start and end. It is
+// positive on the left of the line, looking from start to end. Returns None if the line is
+// degenerate.
+pub(crate) fn signed_distance(
+    point: &Vector2<f64>,
+    start: &Vector2<f64>,
+    end: &Vector2<f64>,
+) -> Option<SignedDistance> {
+    let d = end - start;
+    let length = d.norm();
+    if length < 1e-12 {
+        return None;
+    }
+    let relative = point - start;
+    let cross_product = d.x * relative.y - d.y * relative.x;
+    let distance = cross_product / length;
+
+    let grad_from_point = RowVector2::new(-d.y, d.x) / length;
+    let grad_from_d = RowVector2::new(relative.y, -relative.x) / length
+        - cross_product * d.transpose() / (length * length * length);
+    let grad_from_start = -grad_from_point - grad_from_d;
+
+    Some((distance, grad_from_point, grad_from_start, grad_from_d))
+}
@@ -0,0 +1,166 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{circle::Circle, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that fixes the distance of a point to the circumference of a circle.
+// A signed distance is positive outside and negative inside the circle, so the point can not jump
+// through the circumference while solving.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct PointCircleDistance {
+    point: Rc<RefCell<Point2>>,
+    circle: Rc<RefCell<Circle>>,
+
+    desired_distance: f64,
+    signed: bool,
+}
+
+impl PointCircleDistance {
+    pub fn new(
+        point: Rc<RefCell<Point2>>,
+        circle: Rc<RefCell<Circle>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Self {
+        assert!(desired_distance.is_finite());
+        Self {
+            point,
+            circle,
+            desired_distance,
+            signed,
+        }
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+
+    pub fn circle(&self) -> Rc<RefCell<Circle>> {
+        self.circle.clone()
+    }
+
+    pub fn set_circle(&mut self, circle: Rc<RefCell<Circle>>) {
+        self.circle = circle;
+    }
+
+    pub fn desired_distance(&self) -> f64 {
+        self.desired_distance
+    }
+
+    pub fn set_desired_distance(&mut self, desired_distance: f64) {
+        self.desired_distance = desired_distance;
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+    }
+
+    pub fn current_distance(&self) -> f64 {
+        let circle = self.circle.borrow();
+        let d = self.point.borrow().data() - circle.center().borrow().data();
+        let distance = d.norm() - circle.radius();
+        if self.signed {
+            distance
+        } else {
+            distance.abs()
+        }
+    }
+}
+
+impl ConstraintLike for PointCircleDistance {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Point2(self.point.clone()),
+            PrimitiveCell::Circle(self.circle.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.current_distance() - self.desired_distance;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let circle = self.circle.borrow().clone();
+        let d = self.point.borrow().data() - circle.center().borrow().data();
+        let center_distance = d.norm();
+        if center_distance < 1e-12 {
+            return;
+        }
+
+        let err = self.current_distance() - self.desired_distance;
+        let grad_from_distance = if self.signed {
+            err
+        } else {
+            err * (center_distance - circle.radius()).signum()
+        };
+        let grad_from_d = grad_from_distance * d.transpose() / center_distance;
+
+        let grad_point = self.point.borrow().point_gradient();
+        self.point
+            .borrow_mut()
+            .add_to_gradient((grad_from_d * grad_point).as_view());
+        self.circle.borrow_mut().add_to_gradient(
+            (-grad_from_d * circle.center_gradient()
+                - grad_from_distance * circle.radius_gradient())
+            .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::PointCircleDistance(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_point_circle_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center, 1.0)?;
+        let point = sketch.add_point2(0.5, 0.25)?;
+
+        // The point starts inside the circle and has to move outside
+        let constr1 =
+            sketch.constrain_distance_point_circle(point.clone(), circle.clone(), 0.5, true)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("circle: {:?}", circle.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((constr1.borrow().current_distance() - 0.5).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{distance::signed_distance, ConstraintLike},
+    primitives::{line::Line, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that fixes the perpendicular distance of a point to the infinite
+// extension of a line. A signed distance is positive on the left of the line, looking from its
+// start to its end, so the point can not jump to the other side while solving.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct PointLineDistance {
+    point: Rc<RefCell<Point2>>,
+    line: Rc<RefCell<Line>>,
+
+    desired_distance: f64,
+    signed: bool,
+}
+
+impl PointLineDistance {
+    pub fn new(
+        point: Rc<RefCell<Point2>>,
+        line: Rc<RefCell<Line>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Self {
+        assert!(desired_distance.is_finite());
+        Self {
+            point,
+            line,
+            desired_distance,
+            signed,
+        }
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+
+    pub fn line(&self) -> Rc<RefCell<Line>> {
+        self.line.clone()
+    }
+
+    pub fn set_line(&mut self, line: Rc<RefCell<Line>>) {
+        self.line = line;
+    }
+
+    pub fn desired_distance(&self) -> f64 {
+        self.desired_distance
+    }
+
+    pub fn set_desired_distance(&mut self, desired_distance: f64) {
+        self.desired_distance = desired_distance;
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+    }
+
+    pub fn current_distance(&self) -> f64 {
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        match signed_distance(&self.point.borrow().data(), &start, &end) {
+            Some((distance, _, _, _)) if self.signed => distance,
+            Some((distance, _, _, _)) => distance.abs(),
+            None => 0.0,
+        }
+    }
+}
+
+impl ConstraintLike for PointLineDistance {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Point2(self.point.clone()),
+            PrimitiveCell::Line(self.line.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.current_distance() - self.desired_distance;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let line = self.line.borrow().clone();
+        let start = line.start().borrow().data();
+        let end = line.end().borrow().data();
+        let Some((distance, grad_from_point, grad_from_start, grad_from_end)) =
+            signed_distance(&self.point.borrow().data(), &start, &end)
+        else {
+            return;
+        };
+
+        let err = self.current_distance() - self.desired_distance;
+        let grad_from_distance = if self.signed {
+            err
+        } else {
+            err * distance.signum()
+        };
+
+        let grad_point = self.point.borrow().point_gradient();
+        self.point
+            .borrow_mut()
+            .add_to_gradient((grad_from_distance * grad_from_point * grad_point).as_view());
+        self.line.borrow_mut().add_to_gradient(
+            (grad_from_distance
+                * (grad_from_start * line.start_gradient() + grad_from_end * line.end_gradient()))
+            .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::PointLineDistance(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_point_line_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(3.0, 1.0)?;
+        let line = sketch.add_line(start, end)?;
+        let point = sketch.add_point2(1.0, 2.0)?;
+
+        let constr1 =
+            sketch.constrain_distance_point_line(point.clone(), line.clone(), 0.5, false)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_point_line_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(3.0, 1.0)?;
+        let line = sketch.add_line(start, end)?;
+        let point = sketch.add_point2(1.0, 2.0)?;
+
+        // The point starts on the left of the line and has to move to the right
+        let constr1 =
+            sketch.constrain_distance_point_line(point.clone(), line.clone(), -1.0, true)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((constr1.borrow().current_distance() + 1.0).abs() < 1e-5);
+        Ok(())
+    }
+}
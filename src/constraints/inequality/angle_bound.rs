@@ -0,0 +1,138 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{angle_between_points::AngleBetweenPoints, inequality::Bound, ConstraintLike},
+    primitives::{point2::Point2, PrimitiveCell},
+    units::Angle,
+};
+
+// This is a sketch constraint that keeps the angle at the middle point between two points above or below a limit.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct AngleBound {
+    angle: AngleBetweenPoints,
+    bound: Bound,
+}
+
+impl AngleBound {
+    pub fn new(
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        middle_point: Rc<RefCell<Point2>>,
+        limit: Angle,
+        bound: Bound,
+    ) -> Self {
+        Self {
+            angle: AngleBetweenPoints::new(point1, point2, middle_point, limit),
+            bound,
+        }
+    }
+
+    pub fn point1(&self) -> Rc<RefCell<Point2>> {
+        self.angle.point1()
+    }
+
+    pub fn point2(&self) -> Rc<RefCell<Point2>> {
+        self.angle.point2()
+    }
+
+    pub fn middle_point(&self) -> Rc<RefCell<Point2>> {
+        self.angle.middle_point()
+    }
+
+    pub fn limit(&self) -> f64 {
+        self.angle.desired_angle()
+    }
+
+    pub fn set_limit(&mut self, limit: f64) {
+        self.angle.set_desired_angle(limit);
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub fn set_bound(&mut self, bound: Bound) {
+        self.bound = bound;
+    }
+
+    pub fn current_angle(&self) -> f64 {
+        self.angle.current_angle()
+    }
+
+    pub fn is_violated(&self) -> bool {
+        self.bound
+            .is_violated(self.angle.current_angle(), self.limit())
+    }
+}
+
+impl ConstraintLike for AngleBound {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.angle.references()
+    }
+
+    fn loss_value(&self) -> f64 {
+        if self.is_violated() {
+            self.angle.loss_value()
+        } else {
+            0.0
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        if self.is_violated() {
+            self.angle.update_gradient();
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::AngleBound(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, f64::consts::PI};
+
+    use crate::units::Angle;
+    use crate::{
+        constraints::{inequality::Bound, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_min_angle() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let point1 = sketch.add_point2(1.0, 0.0)?;
+        let point2 = sketch.add_point2(1.0, 0.2)?;
+        let middle_point = sketch.add_point2(0.0, 0.0)?;
+
+        let constr1 = sketch.constrain_angle_bound(
+            point1.clone(),
+            point2.clone(),
+            middle_point.clone(),
+            Angle::degrees(45.0),
+            Bound::Min,
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("point1: {:?}", point1.as_ref().borrow());
+        println!("point2: {:?}", point2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!(constr1.borrow().current_angle() > PI / 4.0 - 1e-5);
+        Ok(())
+    }
+}
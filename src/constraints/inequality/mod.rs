@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+pub mod angle_bound;
+pub mod euclidean_distance_bound;
+pub mod horizontal_distance_bound;
+pub mod radius_bound;
+pub mod vertical_distance_bound;
+
+// Inequality constraints wrap the equality constraint of the same dimension with the limit as the
+// desired value. While the limit is respected their loss and gradient are zero, otherwise they act
+// like the equality constraint and pull the dimension back to the limit. The loss is continuously
+// differentiable at the limit, and satisfied inequalities have empty rows in the Jacobian.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Bound {
+    // The value has to be at least the limit
+    Min,
+    // The value has to be at most the limit
+    Max,
+}
+
+impl Bound {
+    pub fn is_violated(&self, value: f64, limit: f64) -> bool {
+        match self {
+            Bound::Min => value < limit,
+            Bound::Max => value > limit,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{
+        inequality::Bound,
+        radius::{radius_dimension::RadiusDimension, RadialCurve},
+        ConstraintLike,
+    },
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that keeps the radius of a circle or arc above or below a limit.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RadiusBound {
+    radius: RadiusDimension,
+    bound: Bound,
+}
+
+impl RadiusBound {
+    pub fn new(curve: RadialCurve, limit: f64, bound: Bound) -> Self {
+        Self {
+            radius: RadiusDimension::new(curve, limit),
+            bound,
+        }
+    }
+
+    pub fn curve(&self) -> RadialCurve {
+        self.radius.curve()
+    }
+
+    pub fn limit(&self) -> f64 {
+        self.radius.desired_radius()
+    }
+
+    pub fn set_limit(&mut self, limit: f64) {
+        self.radius.set_desired_radius(limit);
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub fn set_bound(&mut self, bound: Bound) {
+        self.bound = bound;
+    }
+
+    pub fn current_radius(&self) -> f64 {
+        self.radius.curve().radius()
+    }
+
+    pub fn is_violated(&self) -> bool {
+        self.bound.is_violated(self.current_radius(), self.limit())
+    }
+}
+
+impl ConstraintLike for RadiusBound {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.radius.references()
+    }
+
+    fn loss_value(&self) -> f64 {
+        if self.is_violated() {
+            self.radius.loss_value()
+        } else {
+            0.0
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        if self.is_violated() {
+            self.radius.update_gradient();
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::RadiusBound(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{inequality::Bound, radius::RadialCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_max_radius() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center.clone(), 7.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.0, 1.0)?;
+
+        let constr1 =
+            sketch.constrain_radius_bound(RadialCurve::Circle(circle.clone()), 5.0, Bound::Max)?;
+        let constr2 =
+            sketch.constrain_radius_bound(RadialCurve::Arc(arc.clone()), 5.0, Bound::Max)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("circle: {:?}", circle.as_ref().borrow());
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!(circle.borrow().radius() < 5.0 + 1e-5);
+        // The arc was already small enough and is left alone
+        assert!(!constr2.borrow().is_violated());
+        assert_eq!(arc.borrow().radius(), 1.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{
+        distance::vertical_distance_between_points::VerticalDistanceBetweenPoints,
+        inequality::Bound, ConstraintLike,
+    },
+    primitives::{point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that keeps the vertical distance from point1 to point2 above or below a limit.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct VerticalDistanceBound {
+    distance: VerticalDistanceBetweenPoints,
+    bound: Bound,
+}
+
+impl VerticalDistanceBound {
+    pub fn new(
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        limit: f64,
+        bound: Bound,
+    ) -> Self {
+        Self {
+            distance: VerticalDistanceBetweenPoints::new(point1, point2, limit),
+            bound,
+        }
+    }
+
+    pub fn point1(&self) -> Rc<RefCell<Point2>> {
+        self.distance.point1()
+    }
+
+    pub fn point2(&self) -> Rc<RefCell<Point2>> {
+        self.distance.point2()
+    }
+
+    pub fn limit(&self) -> f64 {
+        self.distance.desired_distance()
+    }
+
+    pub fn set_limit(&mut self, limit: f64) {
+        self.distance.set_desired_distance(limit);
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub fn set_bound(&mut self, bound: Bound) {
+        self.bound = bound;
+    }
+
+    pub fn current_distance(&self) -> f64 {
+        self.distance.current_distance()
+    }
+
+    pub fn is_violated(&self) -> bool {
+        self.bound
+            .is_violated(self.distance.current_distance(), self.limit())
+    }
+}
+
+impl ConstraintLike for VerticalDistanceBound {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.distance.references()
+    }
+
+    fn loss_value(&self) -> f64 {
+        if self.is_violated() {
+            self.distance.loss_value()
+        } else {
+            0.0
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        if self.is_violated() {
+            self.distance.update_gradient();
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::VerticalDistanceBound(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{inequality::Bound, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_min_vertical_distance() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let point1 = sketch.add_point2(0.0, 0.0)?;
+        let point2 = sketch.add_point2(1.0, 0.5)?;
+
+        let constr1 = sketch.constrain_vertical_distance_bound(
+            point1.clone(),
+            point2.clone(),
+            2.0,
+            Bound::Min,
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("point1: {:?}", point1.as_ref().borrow());
+        println!("point2: {:?}", point2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!(constr1.borrow().current_distance() > 2.0 - 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{length::LengthCurve, ConstraintLike},
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that gives any number of lines, arcs and circles the same length.
+// All curves are compared to the first one, which keeps it to a single row in the Jacobian.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EqualLengths {
+    curves: Vec<LengthCurve>,
+}
+
+impl EqualLengths {
+    pub fn new(curves: Vec<LengthCurve>) -> Self {
+        assert!(curves.len() >= 2);
+        Self { curves }
+    }
+
+    pub fn curves(&self) -> Vec<LengthCurve> {
+        self.curves.clone()
+    }
+
+    pub fn set_curves(&mut self, curves: Vec<LengthCurve>) {
+        assert!(curves.len() >= 2);
+        self.curves = curves;
+    }
+}
+
+impl ConstraintLike for EqualLengths {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.curves.iter().map(|c| c.primitive()).collect()
+    }
+
+    fn loss_value(&self) -> f64 {
+        let first = self.curves[0].length();
+        self.curves[1..]
+            .iter()
+            .map(|c| {
+                let err = c.length() - first;
+                0.5 * err * err
+            })
+            .sum()
+    }
+
+    fn update_gradient(&mut self) {
+        let first = self.curves[0].length();
+        let mut grad_first = 0.0;
+        for curve in self.curves[1..].iter() {
+            let err = curve.length() - first;
+            curve.add_to_gradient(err);
+            grad_first -= err;
+        }
+        self.curves[0].add_to_gradient(grad_first);
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::EqualLengths(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, f64::consts::PI};
+
+    use crate::{
+        constraints::{length::LengthCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_equal_lengths() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(3.0, 1.0)?;
+        let line = sketch.add_line(start, end)?;
+        let center1 = sketch.add_point2(-2.0, 1.0)?;
+        let arc = sketch.add_arc(center1, 1.0, false, 0.0, 0.5 * PI)?;
+        let center2 = sketch.add_point2(2.0, -2.0)?;
+        let circle = sketch.add_circle(center2, 0.5)?;
+
+        let constr1 = sketch.constrain_equal_lengths(vec![
+            LengthCurve::Line(line.clone()),
+            LengthCurve::Arc(arc.clone()),
+            LengthCurve::Circle(circle.clone()),
+        ])?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("arc: {:?}", arc.as_ref().borrow());
+        println!("circle: {:?}", circle.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        let length = LengthCurve::Line(line).length();
+        assert!((arc.borrow().arc_length() - length).abs() < 1e-5);
+        assert!((2.0 * circle.borrow().radius() - length).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equal_arc_lengths() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center1 = sketch.add_point2(0.0, 0.0)?;
+        let arc1 = sketch.add_arc(center1, 1.0, false, 0.0, 1.0)?;
+        let center2 = sketch.add_point2(3.0, 0.0)?;
+        let arc2 = sketch.add_arc(center2, 2.0, true, 0.5, 0.0)?;
+
+        let constr1 = sketch.constrain_equal_lengths(vec![
+            LengthCurve::Arc(arc1.clone()),
+            LengthCurve::Arc(arc2.clone()),
+        ])?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc1: {:?}", arc1.as_ref().borrow());
+        println!("arc2: {:?}", arc2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::primitives::{arc::Arc, circle::Circle, line::Line, PrimitiveCell};
+
+pub mod equal_lengths;
+
+// A primitive with a length-like quantity: the length of a line, the arc length of an arc, or the
+// diameter of a circle
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum LengthCurve {
+    Line(Rc<RefCell<Line>>),
+    Arc(Rc<RefCell<Arc>>),
+    Circle(Rc<RefCell<Circle>>),
+}
+
+impl LengthCurve {
+    pub fn primitive(&self) -> PrimitiveCell {
+        match self {
+            LengthCurve::Line(line) => PrimitiveCell::Line(line.clone()),
+            LengthCurve::Arc(arc) => PrimitiveCell::Arc(arc.clone()),
+            LengthCurve::Circle(circle) => PrimitiveCell::Circle(circle.clone()),
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        match self {
+            LengthCurve::Line(line) => {
+                let line = line.borrow();
+                (line.end().borrow().data() - line.start().borrow().data()).norm()
+            }
+            LengthCurve::Arc(arc) => arc.borrow().arc_length(),
+            LengthCurve::Circle(circle) => 2.0 * circle.borrow().radius(),
+        }
+    }
+
+    // Adds the gradient of the loss by the length to the parameters of the primitive
+    pub(crate) fn add_to_gradient(&self, gradient: f64) {
+        match self {
+            LengthCurve::Line(line) => {
+                let d = line.borrow().end().borrow().data() - line.borrow().start().borrow().data();
+                let grad_line = d.transpose() / d.norm()
+                    * (line.borrow().end_gradient() - line.borrow().start_gradient());
+                line.borrow_mut()
+                    .add_to_gradient((gradient * grad_line).as_view());
+            }
+            LengthCurve::Arc(arc) => {
+                let grad_arc = gradient * arc.borrow().arc_length_gradient();
+                arc.borrow_mut().add_to_gradient(grad_arc.as_view());
+            }
+            LengthCurve::Circle(circle) => {
+                let grad_circle = 2.0 * gradient * circle.borrow().radius_gradient();
+                circle.borrow_mut().add_to_gradient(grad_circle.as_view());
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::{cell::RefCell, f64::consts::PI, rc::Rc};
+
+use nalgebra::SMatrix;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{line::Line, PrimitiveCell},
+    units::Angle,
+};
+
+// This is a sketch constraint that fixes the angle between the directions of two lines, measured
+// from line1 to line2. A signed angle is counter-clockwise positive in (-PI, PI], an unsigned
+// angle lies in [0, PI]. Because the directions of the lines are used, an angle and its
+// supplementary angle are different solutions and the solver does not flip between them.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct AngleBetweenLines {
+    line1: Rc<RefCell<Line>>,
+    line2: Rc<RefCell<Line>>,
+
+    desired_angle: f64,
+    signed: bool,
+}
+
+impl AngleBetweenLines {
+    pub fn new(
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+        desired_angle: Angle,
+        signed: bool,
+    ) -> Self {
+        Self {
+            line1,
+            line2,
+            desired_angle: desired_angle.to_radians(),
+            signed,
+        }
+    }
+
+    pub fn line1(&self) -> Rc<RefCell<Line>> {
+        self.line1.clone()
+    }
+
+    pub fn set_line1(&mut self, line1: Rc<RefCell<Line>>) {
+        self.line1 = line1;
+    }
+
+    pub fn line2(&self) -> Rc<RefCell<Line>> {
+        self.line2.clone()
+    }
+
+    pub fn set_line2(&mut self, line2: Rc<RefCell<Line>>) {
+        self.line2 = line2;
+    }
+
+    pub fn desired_angle(&self) -> f64 {
+        self.desired_angle
+    }
+
+    pub fn set_desired_angle(&mut self, desired_angle: f64) {
+        self.desired_angle = desired_angle;
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+    }
+
+    // The signed angle from line1 to line2 in (-PI, PI]
+    pub fn current_angle(&self) -> f64 {
+        let line1 = self.line1.borrow();
+        let line2 = self.line2.borrow();
+        let dir1 = line1.end().borrow().data() - line1.start().borrow().data();
+        let dir2 = line2.end().borrow().data() - line2.start().borrow().data();
+        let cross_product = dir1.x * dir2.y - dir1.y * dir2.x;
+        cross_product.atan2(dir1.dot(&dir2))
+    }
+
+    // The difference to the desired angle. A signed angle is compared modulo 2 * PI, so the
+    // error is the shortest rotation to the desired angle.
+    fn error(&self) -> f64 {
+        let angle = self.current_angle();
+        if self.signed {
+            (angle - self.desired_angle + PI).rem_euclid(2.0 * PI) - PI
+        } else {
+            angle.abs() - self.desired_angle
+        }
+    }
+}
+
+impl ConstraintLike for AngleBetweenLines {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line1.clone()),
+            PrimitiveCell::Line(self.line2.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let error = self.error();
+        0.5 * error * error
+    }
+
+    fn update_gradient(&mut self) {
+        let line1 = self.line1.borrow().clone();
+        let line2 = self.line2.borrow().clone();
+        let dir1 = line1.end().borrow().data() - line1.start().borrow().data();
+        let dir2 = line2.end().borrow().data() - line2.start().borrow().data();
+        let length1_squared = dir1.norm_squared();
+        let length2_squared = dir2.norm_squared();
+        if length1_squared < 1e-24 || length2_squared < 1e-24 {
+            return;
+        }
+
+        let error = self.error();
+        let grad_from_angle = if self.signed {
+            error
+        } else {
+            error * self.current_angle().signum()
+        };
+
+        // Rotating a direction counter-clockwise turns it by its perpendicular over its length
+        let grad_angle_from_dir1 =
+            SMatrix::<f64, 1, 2>::from_row_slice(&[dir1.y, -dir1.x]) / length1_squared;
+        let grad_angle_from_dir2 =
+            SMatrix::<f64, 1, 2>::from_row_slice(&[-dir2.y, dir2.x]) / length2_squared;
+
+        self.line1.borrow_mut().add_to_gradient(
+            (grad_from_angle
+                * grad_angle_from_dir1
+                * (line1.end_gradient() - line1.start_gradient()))
+            .as_view(),
+        );
+        self.line2.borrow_mut().add_to_gradient(
+            (grad_from_angle
+                * grad_angle_from_dir2
+                * (line2.end_gradient() - line2.start_gradient()))
+            .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::AngleBetweenLines(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, f64::consts::PI};
+
+    use crate::units::Angle;
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_angle_between_lines() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(2.0, 0.5)?;
+        let c = sketch.add_point2(3.0, 1.0)?;
+        let d = sketch.add_point2(3.5, 2.0)?;
+        let line1 = sketch.add_line(a, b)?;
+        let line2 = sketch.add_line(c, d)?;
+
+        let constr1 = sketch.constrain_angle_between_lines(
+            line1.clone(),
+            line2.clone(),
+            Angle::degrees(60.0),
+            false,
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line1: {:?}", line1.as_ref().borrow());
+        println!("line2: {:?}", line2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((constr1.borrow().current_angle().abs() - PI / 3.0).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_angle_between_lines() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(2.0, 0.5)?;
+        let c = sketch.add_point2(3.0, 1.0)?;
+        let d = sketch.add_point2(3.5, 2.0)?;
+        let line1 = sketch.add_line(a, b)?;
+        let line2 = sketch.add_line(c, d)?;
+
+        // Line2 starts counter-clockwise of line1, but has to end up clockwise of it
+        let constr1 = sketch.constrain_angle_between_lines(
+            line1.clone(),
+            line2.clone(),
+            Angle::degrees(-120.0),
+            true,
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line1: {:?}", line1.as_ref().borrow());
+        println!("line2: {:?}", line2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((constr1.borrow().current_angle() + 2.0 * PI / 3.0).abs() < 1e-5);
+
+        // The desired angle can be changed after the constraint was added
+        constr1.borrow_mut().set_desired_angle(PI / 2.0);
+        solver.solve(&mut sketch)?;
+        assert!((constr1.borrow().current_angle() - PI / 2.0).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{distance::signed_distance, ConstraintLike},
+    primitives::{line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that puts two lines on the same infinite line. The loss is the
+// squared perpendicular distance of both ends of line2 to line1, so it is measured in lengths and
+// does not depend on how long the lines are.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CollinearLines {
+    line1: Rc<RefCell<Line>>,
+    line2: Rc<RefCell<Line>>,
+}
+
+impl CollinearLines {
+    pub fn new(line1: Rc<RefCell<Line>>, line2: Rc<RefCell<Line>>) -> Self {
+        Self { line1, line2 }
+    }
+
+    pub fn line1(&self) -> Rc<RefCell<Line>> {
+        self.line1.clone()
+    }
+
+    pub fn set_line1(&mut self, line1: Rc<RefCell<Line>>) {
+        self.line1 = line1;
+    }
+
+    pub fn line2(&self) -> Rc<RefCell<Line>> {
+        self.line2.clone()
+    }
+
+    pub fn set_line2(&mut self, line2: Rc<RefCell<Line>>) {
+        self.line2 = line2;
+    }
+}
+
+impl ConstraintLike for CollinearLines {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line1.clone()),
+            PrimitiveCell::Line(self.line2.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let line1 = self.line1.borrow();
+        let line2 = self.line2.borrow();
+        let start = line1.start().borrow().data();
+        let end = line1.end().borrow().data();
+
+        [line2.start(), line2.end()]
+            .iter()
+            .filter_map(|point| signed_distance(&point.borrow().data(), &start, &end))
+            .map(|(distance, _, _, _)| 0.5 * distance * distance)
+            .sum()
+    }
+
+    fn update_gradient(&mut self) {
+        let line1 = self.line1.borrow().clone();
+        let line2 = self.line2.borrow().clone();
+        let start = line1.start().borrow().data();
+        let end = line1.end().borrow().data();
+
+        for (point, grad_point) in [
+            (line2.start(), line2.start_gradient()),
+            (line2.end(), line2.end_gradient()),
+        ] {
+            let Some((distance, grad_from_point, grad_from_start, grad_from_end)) =
+                signed_distance(&point.borrow().data(), &start, &end)
+            else {
+                continue;
+            };
+
+            self.line1.borrow_mut().add_to_gradient(
+                (distance
+                    * (grad_from_start * line1.start_gradient()
+                        + grad_from_end * line1.end_gradient()))
+                .as_view(),
+            );
+            self.line2
+                .borrow_mut()
+                .add_to_gradient((distance * grad_from_point * grad_point).as_view());
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::CollinearLines(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_collinear_lines() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        // The two sides of a slot opening
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.1)?;
+        let c = sketch.add_point2(2.0, 0.5)?;
+        let d = sketch.add_point2(3.0, 0.3)?;
+        let line1 = sketch.add_line(a, b)?;
+        let line2 = sketch.add_line(c, d)?;
+
+        let constr1 = sketch.constrain_collinear_lines(line1.clone(), line2.clone())?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line1: {:?}", line1.as_ref().borrow());
+        println!("line2: {:?}", line2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::RowVector2;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{distance::signed_distance, ConstraintLike},
+    primitives::{point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that puts any number of points on a common line. The line runs
+// through the first and the last point, and the loss is the sum of the squared perpendicular
+// distances of the points in between.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CollinearPoints {
+    points: Vec<Rc<RefCell<Point2>>>,
+}
+
+impl CollinearPoints {
+    pub fn new(points: Vec<Rc<RefCell<Point2>>>) -> Self {
+        Self { points }
+    }
+
+    pub fn points(&self) -> Vec<Rc<RefCell<Point2>>> {
+        self.points.clone()
+    }
+
+    pub fn set_points(&mut self, points: Vec<Rc<RefCell<Point2>>>) {
+        self.points = points;
+    }
+
+    // The points between the first and the last one
+    fn inner_points(&self) -> &[Rc<RefCell<Point2>>] {
+        match self.points.len() {
+            0..=2 => &[],
+            n => &self.points[1..n - 1],
+        }
+    }
+}
+
+impl ConstraintLike for CollinearPoints {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.points
+            .iter()
+            .map(|point| PrimitiveCell::Point2(point.clone()))
+            .collect()
+    }
+
+    fn loss_value(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return 0.0;
+        };
+        let start = first.borrow().data();
+        let end = last.borrow().data();
+
+        self.inner_points()
+            .iter()
+            .filter_map(|point| signed_distance(&point.borrow().data(), &start, &end))
+            .map(|(distance, _, _, _)| 0.5 * distance * distance)
+            .sum()
+    }
+
+    fn update_gradient(&mut self) {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return;
+        };
+        let start = first.borrow().data();
+        let end = last.borrow().data();
+
+        let mut grad_first = RowVector2::<f64>::zeros();
+        let mut grad_last = RowVector2::<f64>::zeros();
+        for point in self.inner_points() {
+            let Some((distance, grad_from_point, grad_from_start, grad_from_end)) =
+                signed_distance(&point.borrow().data(), &start, &end)
+            else {
+                continue;
+            };
+
+            let grad_point = point.borrow().point_gradient();
+            point
+                .borrow_mut()
+                .add_to_gradient((distance * grad_from_point * grad_point).as_view());
+            grad_first += distance * grad_from_start;
+            grad_last += distance * grad_from_end;
+        }
+
+        let grad_point = first.borrow().point_gradient();
+        first
+            .borrow_mut()
+            .add_to_gradient((grad_first * grad_point).as_view());
+        let grad_point = last.borrow().point_gradient();
+        last.borrow_mut()
+            .add_to_gradient((grad_last * grad_point).as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::CollinearPoints(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_collinear_points() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.5)?;
+        let c = sketch.add_point2(2.0, -0.3)?;
+        let d = sketch.add_point2(3.0, 1.0)?;
+
+        let constr1 =
+            sketch.constrain_collinear_points(vec![a.clone(), b.clone(), c.clone(), d.clone()])?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("a: {:?}", a.as_ref().borrow());
+        println!("b: {:?}", b.as_ref().borrow());
+        println!("c: {:?}", c.as_ref().borrow());
+        println!("d: {:?}", d.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
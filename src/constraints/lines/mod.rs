@@ -1,3 +1,6 @@
+pub mod angle_between_lines;
+pub mod collinear_lines;
+pub mod collinear_points;
 pub mod equal_length;
 pub mod horizontal_line;
 pub mod parallel_lines;
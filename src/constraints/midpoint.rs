@@ -0,0 +1,195 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::{SMatrix, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{arc::Arc, line::Line, point2::Point2, PrimitiveCell},
+};
+
+// A curve that has a midpoint: the middle of a line, or the point halfway along an arc.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum MidpointCurve {
+    Line(Rc<RefCell<Line>>),
+    Arc(Rc<RefCell<Arc>>),
+}
+
+impl MidpointCurve {
+    pub fn primitive(&self) -> PrimitiveCell {
+        match self {
+            MidpointCurve::Line(line) => PrimitiveCell::Line(line.clone()),
+            MidpointCurve::Arc(arc) => PrimitiveCell::Arc(arc.clone()),
+        }
+    }
+
+    pub fn midpoint(&self) -> Vector2<f64> {
+        match self {
+            MidpointCurve::Line(line) => {
+                let line = line.borrow();
+                0.5 * (line.start().borrow().data() + line.end().borrow().data())
+            }
+            MidpointCurve::Arc(arc) => arc.borrow().mid_point(),
+        }
+    }
+
+    // Adds the gradient of the loss by the midpoint to the parameters of the curve
+    fn add_to_gradient(&self, gradient: SMatrix<f64, 1, 2>) {
+        match self {
+            MidpointCurve::Line(line) => {
+                let grad_line =
+                    0.5 * (line.borrow().start_gradient() + line.borrow().end_gradient());
+                line.borrow_mut()
+                    .add_to_gradient((gradient * grad_line).as_view());
+            }
+            MidpointCurve::Arc(arc) => {
+                let grad_arc = arc.borrow().mid_point_gradient();
+                arc.borrow_mut()
+                    .add_to_gradient((gradient * grad_arc).as_view());
+            }
+        }
+    }
+}
+
+// This is a sketch constraint that places a point on the midpoint of a line or arc.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Midpoint {
+    curve: MidpointCurve,
+    point: Rc<RefCell<Point2>>,
+}
+
+impl Midpoint {
+    pub fn new(curve: MidpointCurve, point: Rc<RefCell<Point2>>) -> Self {
+        Self { curve, point }
+    }
+
+    pub fn curve(&self) -> MidpointCurve {
+        self.curve.clone()
+    }
+
+    pub fn set_curve(&mut self, curve: MidpointCurve) {
+        self.curve = curve;
+    }
+
+    pub fn point(&self) -> Rc<RefCell<Point2>> {
+        self.point.clone()
+    }
+
+    pub fn set_point(&mut self, point: Rc<RefCell<Point2>>) {
+        self.point = point;
+    }
+}
+
+impl ConstraintLike for Midpoint {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            self.curve.primitive(),
+            PrimitiveCell::Point2(self.point.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let midpoint = self.curve.midpoint();
+        let point = self.point.borrow().data();
+        0.5 * (midpoint - point).norm_squared()
+    }
+
+    fn update_gradient(&mut self) {
+        let midpoint = self.curve.midpoint();
+        let point = self.point.borrow().data();
+        let gradient_constraint = (midpoint - point).transpose();
+
+        let grad_point = self.point.borrow().point_gradient();
+
+        self.curve.add_to_gradient(gradient_constraint);
+        self.point
+            .borrow_mut()
+            .add_to_gradient((-gradient_constraint * grad_point).as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::Midpoint(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, f64::consts::PI};
+
+    use crate::{
+        constraints::{midpoint::MidpointCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_line_midpoint() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(2.0, 1.0)?;
+        let line = sketch.add_line(start, end)?;
+        let point = sketch.add_point2(3.0, -1.0)?;
+
+        let constr1 =
+            sketch.constrain_midpoint(MidpointCurve::Line(line.clone()), point.clone())?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line: {:?}", line.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arc_midpoint() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.0, 0.5 * PI)?;
+        let point = sketch.add_point2(0.0, -1.0)?;
+
+        // The midpoint of the quarter arc lies on the diagonal
+        let midpoint = arc.borrow().mid_point();
+        assert!((midpoint.x - 0.5f64.sqrt()).abs() < 1e-12);
+        assert!((midpoint.y - 0.5f64.sqrt()).abs() < 1e-12);
+
+        let constr1 = sketch.constrain_midpoint(MidpointCurve::Arc(arc.clone()), point.clone())?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+        println!("point: {:?}", point.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clockwise_arc_midpoint_wraps_around() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        // A clockwise arc from 0.5 to 2.5 runs through -PI, the long way around
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 2.0, true, 0.5, 2.5)?;
+
+        let angle = arc.borrow().mid_angle();
+        assert!((angle - (1.5 - PI)).abs() < 1e-12);
+        assert!((arc.borrow().sweep_angle() - (2.0 * PI - 2.0)).abs() < 1e-12);
+        Ok(())
+    }
+}
@@ -8,13 +8,24 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "tsify")]
 use tsify::Tsify;
 
+use crate::error::ISOTopeError;
 use crate::primitives::PrimitiveCell;
+use crate::units::Quantity;
 
 pub mod angle_between_points;
+pub mod arcs;
 pub mod coincident;
+pub mod continuity;
 pub mod distance;
 pub mod fix_point;
+pub mod inequality;
+pub mod length;
 pub mod lines;
+pub mod midpoint;
+pub mod pattern;
+pub mod radius;
+pub mod symmetric;
+pub mod tangent;
 
 pub trait ConstraintLike: Debug {
     fn references(&self) -> Vec<PrimitiveCell>;
@@ -30,6 +41,17 @@ pub enum Constraint {
     AngleBetweenPoints(angle_between_points::AngleBetweenPoints),
     ArcEndPointCoincident(coincident::arc_end_point_coincident::ArcEndPointCoincident),
     ArcStartPointCoincident(coincident::arc_start_point_coincident::ArcStartPointCoincident),
+    BSplineEndPointCoincident(coincident::bspline_end_point_coincident::BSplineEndPointCoincident),
+    BSplineStartPointCoincident(
+        coincident::bspline_start_point_coincident::BSplineStartPointCoincident,
+    ),
+    EllipticalArcEndPointCoincident(
+        coincident::elliptical_arc_end_point_coincident::EllipticalArcEndPointCoincident,
+    ),
+    EllipticalArcStartPointCoincident(
+        coincident::elliptical_arc_start_point_coincident::EllipticalArcStartPointCoincident,
+    ),
+    PointOnEllipse(coincident::point_on_ellipse::PointOnEllipse),
     EuclideanDistance(distance::euclidian_distance_between_points::EuclidianDistanceBetweenPoints),
     HorizontalDistance(
         distance::horizontal_distance_between_points::HorizontalDistanceBetweenPoints,
@@ -41,6 +63,35 @@ pub enum Constraint {
     VerticalLine(lines::vertical_line::VerticalLine),
     ParallelLines(lines::parallel_lines::ParallelLines),
     PerpendicularLines(lines::perpendicular_lines::PerpendicularLines),
+    LineEllipseTangent(tangent::line_ellipse_tangent::LineEllipseTangent),
+    BSplineEndTangent(tangent::bspline_end_tangent::BSplineEndTangent),
+    BSplineStartTangent(tangent::bspline_start_tangent::BSplineStartTangent),
+    G1Continuity(continuity::g1_continuity::G1Continuity),
+    G2Continuity(continuity::g2_continuity::G2Continuity),
+    Symmetric(symmetric::symmetric_points::Symmetric),
+    SymmetricLines(symmetric::symmetric_lines::SymmetricLines),
+    SymmetricArcs(symmetric::symmetric_arcs::SymmetricArcs),
+    Midpoint(midpoint::Midpoint),
+    RadiusDimension(radius::radius_dimension::RadiusDimension),
+    DiameterDimension(radius::diameter_dimension::DiameterDimension),
+    EqualRadius(radius::equal_radius::EqualRadius),
+    RadiusDifference(radius::radius_difference::RadiusDifference),
+    AngleBetweenLines(lines::angle_between_lines::AngleBetweenLines),
+    PointLineDistance(distance::point_line_distance::PointLineDistance),
+    LineLineDistance(distance::line_line_distance::LineLineDistance),
+    PointCircleDistance(distance::point_circle_distance::PointCircleDistance),
+    LineCircleDistance(distance::line_circle_distance::LineCircleDistance),
+    CollinearLines(lines::collinear_lines::CollinearLines),
+    CollinearPoints(lines::collinear_points::CollinearPoints),
+    ArcSweepAngle(arcs::arc_sweep_angle::ArcSweepAngle),
+    ArcLength(arcs::arc_length::ArcLength),
+    EuclideanDistanceBound(inequality::euclidean_distance_bound::EuclideanDistanceBound),
+    HorizontalDistanceBound(inequality::horizontal_distance_bound::HorizontalDistanceBound),
+    VerticalDistanceBound(inequality::vertical_distance_bound::VerticalDistanceBound),
+    RadiusBound(inequality::radius_bound::RadiusBound),
+    AngleBound(inequality::angle_bound::AngleBound),
+    Pattern(pattern::Pattern),
+    EqualLengths(length::equal_lengths::EqualLengths),
 }
 
 impl Constraint {
@@ -49,6 +100,9 @@ impl Constraint {
             Constraint::AngleBetweenPoints(c) => c,
             Constraint::ArcEndPointCoincident(c) => c,
             Constraint::ArcStartPointCoincident(c) => c,
+            Constraint::EllipticalArcEndPointCoincident(c) => c,
+            Constraint::EllipticalArcStartPointCoincident(c) => c,
+            Constraint::PointOnEllipse(c) => c,
             Constraint::EuclideanDistance(c) => c,
             Constraint::HorizontalDistance(c) => c,
             Constraint::VerticalDistance(c) => c,
@@ -58,6 +112,37 @@ impl Constraint {
             Constraint::VerticalLine(c) => c,
             Constraint::ParallelLines(c) => c,
             Constraint::PerpendicularLines(c) => c,
+            Constraint::LineEllipseTangent(c) => c,
+            Constraint::BSplineEndPointCoincident(c) => c,
+            Constraint::BSplineStartPointCoincident(c) => c,
+            Constraint::BSplineEndTangent(c) => c,
+            Constraint::BSplineStartTangent(c) => c,
+            Constraint::G1Continuity(c) => c,
+            Constraint::G2Continuity(c) => c,
+            Constraint::Symmetric(c) => c,
+            Constraint::SymmetricLines(c) => c,
+            Constraint::SymmetricArcs(c) => c,
+            Constraint::Midpoint(c) => c,
+            Constraint::RadiusDimension(c) => c,
+            Constraint::DiameterDimension(c) => c,
+            Constraint::EqualRadius(c) => c,
+            Constraint::RadiusDifference(c) => c,
+            Constraint::AngleBetweenLines(c) => c,
+            Constraint::PointLineDistance(c) => c,
+            Constraint::LineLineDistance(c) => c,
+            Constraint::PointCircleDistance(c) => c,
+            Constraint::LineCircleDistance(c) => c,
+            Constraint::CollinearLines(c) => c,
+            Constraint::CollinearPoints(c) => c,
+            Constraint::ArcSweepAngle(c) => c,
+            Constraint::ArcLength(c) => c,
+            Constraint::EuclideanDistanceBound(c) => c,
+            Constraint::HorizontalDistanceBound(c) => c,
+            Constraint::VerticalDistanceBound(c) => c,
+            Constraint::RadiusBound(c) => c,
+            Constraint::AngleBound(c) => c,
+            Constraint::Pattern(c) => c,
+            Constraint::EqualLengths(c) => c,
         }
     }
 }
@@ -71,6 +156,27 @@ pub enum ConstraintCell {
     ArcStartPointCoincident(
         Rc<RefCell<coincident::arc_start_point_coincident::ArcStartPointCoincident>>,
     ),
+    BSplineEndPointCoincident(
+        Rc<RefCell<
+            coincident::bspline_end_point_coincident::BSplineEndPointCoincident,
+        >>,
+    ),
+    BSplineStartPointCoincident(
+        Rc<RefCell<
+            coincident::bspline_start_point_coincident::BSplineStartPointCoincident,
+        >>,
+    ),
+    EllipticalArcEndPointCoincident(
+        Rc<RefCell<
+            coincident::elliptical_arc_end_point_coincident::EllipticalArcEndPointCoincident,
+        >>,
+    ),
+    EllipticalArcStartPointCoincident(
+        Rc<RefCell<
+            coincident::elliptical_arc_start_point_coincident::EllipticalArcStartPointCoincident,
+        >>,
+    ),
+    PointOnEllipse(Rc<RefCell<coincident::point_on_ellipse::PointOnEllipse>>),
     EuclideanDistance(
         Rc<RefCell<distance::euclidian_distance_between_points::EuclidianDistanceBetweenPoints>>,
     ),
@@ -86,14 +192,46 @@ pub enum ConstraintCell {
     VerticalLine(Rc<RefCell<lines::vertical_line::VerticalLine>>),
     ParallelLines(Rc<RefCell<lines::parallel_lines::ParallelLines>>),
     PerpendicularLines(Rc<RefCell<lines::perpendicular_lines::PerpendicularLines>>),
+    LineEllipseTangent(Rc<RefCell<tangent::line_ellipse_tangent::LineEllipseTangent>>),
+    BSplineEndTangent(Rc<RefCell<tangent::bspline_end_tangent::BSplineEndTangent>>),
+    BSplineStartTangent(Rc<RefCell<tangent::bspline_start_tangent::BSplineStartTangent>>),
+    G1Continuity(Rc<RefCell<continuity::g1_continuity::G1Continuity>>),
+    G2Continuity(Rc<RefCell<continuity::g2_continuity::G2Continuity>>),
+    Symmetric(Rc<RefCell<symmetric::symmetric_points::Symmetric>>),
+    SymmetricLines(Rc<RefCell<symmetric::symmetric_lines::SymmetricLines>>),
+    SymmetricArcs(Rc<RefCell<symmetric::symmetric_arcs::SymmetricArcs>>),
+    Midpoint(Rc<RefCell<midpoint::Midpoint>>),
+    RadiusDimension(Rc<RefCell<radius::radius_dimension::RadiusDimension>>),
+    DiameterDimension(Rc<RefCell<radius::diameter_dimension::DiameterDimension>>),
+    EqualRadius(Rc<RefCell<radius::equal_radius::EqualRadius>>),
+    RadiusDifference(Rc<RefCell<radius::radius_difference::RadiusDifference>>),
+    AngleBetweenLines(Rc<RefCell<lines::angle_between_lines::AngleBetweenLines>>),
+    PointLineDistance(Rc<RefCell<distance::point_line_distance::PointLineDistance>>),
+    LineLineDistance(Rc<RefCell<distance::line_line_distance::LineLineDistance>>),
+    PointCircleDistance(Rc<RefCell<distance::point_circle_distance::PointCircleDistance>>),
+    LineCircleDistance(Rc<RefCell<distance::line_circle_distance::LineCircleDistance>>),
+    CollinearLines(Rc<RefCell<lines::collinear_lines::CollinearLines>>),
+    CollinearPoints(Rc<RefCell<lines::collinear_points::CollinearPoints>>),
+    ArcSweepAngle(Rc<RefCell<arcs::arc_sweep_angle::ArcSweepAngle>>),
+    ArcLength(Rc<RefCell<arcs::arc_length::ArcLength>>),
+    EuclideanDistanceBound(Rc<RefCell<inequality::euclidean_distance_bound::EuclideanDistanceBound>>),
+    HorizontalDistanceBound(Rc<RefCell<inequality::horizontal_distance_bound::HorizontalDistanceBound>>),
+    VerticalDistanceBound(Rc<RefCell<inequality::vertical_distance_bound::VerticalDistanceBound>>),
+    RadiusBound(Rc<RefCell<inequality::radius_bound::RadiusBound>>),
+    AngleBound(Rc<RefCell<inequality::angle_bound::AngleBound>>),
+    Pattern(Rc<RefCell<pattern::Pattern>>),
+    EqualLengths(Rc<RefCell<length::equal_lengths::EqualLengths>>),
 }
 
 impl ConstraintCell {
-    pub fn borrow(&self) -> Ref<dyn ConstraintLike> {
+    pub fn borrow(&self) -> Ref<'_, dyn ConstraintLike> {
         match self {
             ConstraintCell::AngleBetweenPoints(c) => c.borrow(),
             ConstraintCell::ArcEndPointCoincident(c) => c.borrow(),
             ConstraintCell::ArcStartPointCoincident(c) => c.borrow(),
+            ConstraintCell::EllipticalArcEndPointCoincident(c) => c.borrow(),
+            ConstraintCell::EllipticalArcStartPointCoincident(c) => c.borrow(),
+            ConstraintCell::PointOnEllipse(c) => c.borrow(),
             ConstraintCell::EuclideanDistance(c) => c.borrow(),
             ConstraintCell::HorizontalDistance(c) => c.borrow(),
             ConstraintCell::VerticalDistance(c) => c.borrow(),
@@ -103,14 +241,48 @@ impl ConstraintCell {
             ConstraintCell::VerticalLine(c) => c.borrow(),
             ConstraintCell::ParallelLines(c) => c.borrow(),
             ConstraintCell::PerpendicularLines(c) => c.borrow(),
+            ConstraintCell::LineEllipseTangent(c) => c.borrow(),
+            ConstraintCell::BSplineEndPointCoincident(c) => c.borrow(),
+            ConstraintCell::BSplineStartPointCoincident(c) => c.borrow(),
+            ConstraintCell::BSplineEndTangent(c) => c.borrow(),
+            ConstraintCell::BSplineStartTangent(c) => c.borrow(),
+            ConstraintCell::G1Continuity(c) => c.borrow(),
+            ConstraintCell::G2Continuity(c) => c.borrow(),
+            ConstraintCell::Symmetric(c) => c.borrow(),
+            ConstraintCell::SymmetricLines(c) => c.borrow(),
+            ConstraintCell::SymmetricArcs(c) => c.borrow(),
+            ConstraintCell::Midpoint(c) => c.borrow(),
+            ConstraintCell::RadiusDimension(c) => c.borrow(),
+            ConstraintCell::DiameterDimension(c) => c.borrow(),
+            ConstraintCell::EqualRadius(c) => c.borrow(),
+            ConstraintCell::RadiusDifference(c) => c.borrow(),
+            ConstraintCell::AngleBetweenLines(c) => c.borrow(),
+            ConstraintCell::PointLineDistance(c) => c.borrow(),
+            ConstraintCell::LineLineDistance(c) => c.borrow(),
+            ConstraintCell::PointCircleDistance(c) => c.borrow(),
+            ConstraintCell::LineCircleDistance(c) => c.borrow(),
+            ConstraintCell::CollinearLines(c) => c.borrow(),
+            ConstraintCell::CollinearPoints(c) => c.borrow(),
+            ConstraintCell::ArcSweepAngle(c) => c.borrow(),
+            ConstraintCell::ArcLength(c) => c.borrow(),
+            ConstraintCell::EuclideanDistanceBound(c) => c.borrow(),
+            ConstraintCell::HorizontalDistanceBound(c) => c.borrow(),
+            ConstraintCell::VerticalDistanceBound(c) => c.borrow(),
+            ConstraintCell::RadiusBound(c) => c.borrow(),
+            ConstraintCell::AngleBound(c) => c.borrow(),
+            ConstraintCell::Pattern(c) => c.borrow(),
+            ConstraintCell::EqualLengths(c) => c.borrow(),
         }
     }
 
-    pub fn borrow_mut(&self) -> RefMut<dyn ConstraintLike> {
+    pub fn borrow_mut(&self) -> RefMut<'_, dyn ConstraintLike> {
         match self {
             ConstraintCell::AngleBetweenPoints(c) => c.borrow_mut(),
             ConstraintCell::ArcEndPointCoincident(c) => c.borrow_mut(),
             ConstraintCell::ArcStartPointCoincident(c) => c.borrow_mut(),
+            ConstraintCell::EllipticalArcEndPointCoincident(c) => c.borrow_mut(),
+            ConstraintCell::EllipticalArcStartPointCoincident(c) => c.borrow_mut(),
+            ConstraintCell::PointOnEllipse(c) => c.borrow_mut(),
             ConstraintCell::EuclideanDistance(c) => c.borrow_mut(),
             ConstraintCell::HorizontalDistance(c) => c.borrow_mut(),
             ConstraintCell::VerticalDistance(c) => c.borrow_mut(),
@@ -120,6 +292,37 @@ impl ConstraintCell {
             ConstraintCell::VerticalLine(c) => c.borrow_mut(),
             ConstraintCell::ParallelLines(c) => c.borrow_mut(),
             ConstraintCell::PerpendicularLines(c) => c.borrow_mut(),
+            ConstraintCell::LineEllipseTangent(c) => c.borrow_mut(),
+            ConstraintCell::BSplineEndPointCoincident(c) => c.borrow_mut(),
+            ConstraintCell::BSplineStartPointCoincident(c) => c.borrow_mut(),
+            ConstraintCell::BSplineEndTangent(c) => c.borrow_mut(),
+            ConstraintCell::BSplineStartTangent(c) => c.borrow_mut(),
+            ConstraintCell::G1Continuity(c) => c.borrow_mut(),
+            ConstraintCell::G2Continuity(c) => c.borrow_mut(),
+            ConstraintCell::Symmetric(c) => c.borrow_mut(),
+            ConstraintCell::SymmetricLines(c) => c.borrow_mut(),
+            ConstraintCell::SymmetricArcs(c) => c.borrow_mut(),
+            ConstraintCell::Midpoint(c) => c.borrow_mut(),
+            ConstraintCell::RadiusDimension(c) => c.borrow_mut(),
+            ConstraintCell::DiameterDimension(c) => c.borrow_mut(),
+            ConstraintCell::EqualRadius(c) => c.borrow_mut(),
+            ConstraintCell::RadiusDifference(c) => c.borrow_mut(),
+            ConstraintCell::AngleBetweenLines(c) => c.borrow_mut(),
+            ConstraintCell::PointLineDistance(c) => c.borrow_mut(),
+            ConstraintCell::LineLineDistance(c) => c.borrow_mut(),
+            ConstraintCell::PointCircleDistance(c) => c.borrow_mut(),
+            ConstraintCell::LineCircleDistance(c) => c.borrow_mut(),
+            ConstraintCell::CollinearLines(c) => c.borrow_mut(),
+            ConstraintCell::CollinearPoints(c) => c.borrow_mut(),
+            ConstraintCell::ArcSweepAngle(c) => c.borrow_mut(),
+            ConstraintCell::ArcLength(c) => c.borrow_mut(),
+            ConstraintCell::EuclideanDistanceBound(c) => c.borrow_mut(),
+            ConstraintCell::HorizontalDistanceBound(c) => c.borrow_mut(),
+            ConstraintCell::VerticalDistanceBound(c) => c.borrow_mut(),
+            ConstraintCell::RadiusBound(c) => c.borrow_mut(),
+            ConstraintCell::AngleBound(c) => c.borrow_mut(),
+            ConstraintCell::Pattern(c) => c.borrow_mut(),
+            ConstraintCell::EqualLengths(c) => c.borrow_mut(),
         }
     }
 
@@ -128,6 +331,9 @@ impl ConstraintCell {
             ConstraintCell::AngleBetweenPoints(c) => c.as_ptr(),
             ConstraintCell::ArcEndPointCoincident(c) => c.as_ptr(),
             ConstraintCell::ArcStartPointCoincident(c) => c.as_ptr(),
+            ConstraintCell::EllipticalArcEndPointCoincident(c) => c.as_ptr(),
+            ConstraintCell::EllipticalArcStartPointCoincident(c) => c.as_ptr(),
+            ConstraintCell::PointOnEllipse(c) => c.as_ptr(),
             ConstraintCell::EuclideanDistance(c) => c.as_ptr(),
             ConstraintCell::HorizontalDistance(c) => c.as_ptr(),
             ConstraintCell::VerticalDistance(c) => c.as_ptr(),
@@ -137,7 +343,180 @@ impl ConstraintCell {
             ConstraintCell::VerticalLine(c) => c.as_ptr(),
             ConstraintCell::ParallelLines(c) => c.as_ptr(),
             ConstraintCell::PerpendicularLines(c) => c.as_ptr(),
+            ConstraintCell::LineEllipseTangent(c) => c.as_ptr(),
+            ConstraintCell::BSplineEndPointCoincident(c) => c.as_ptr(),
+            ConstraintCell::BSplineStartPointCoincident(c) => c.as_ptr(),
+            ConstraintCell::BSplineEndTangent(c) => c.as_ptr(),
+            ConstraintCell::BSplineStartTangent(c) => c.as_ptr(),
+            ConstraintCell::G1Continuity(c) => c.as_ptr(),
+            ConstraintCell::G2Continuity(c) => c.as_ptr(),
+            ConstraintCell::Symmetric(c) => c.as_ptr(),
+            ConstraintCell::SymmetricLines(c) => c.as_ptr(),
+            ConstraintCell::SymmetricArcs(c) => c.as_ptr(),
+            ConstraintCell::Midpoint(c) => c.as_ptr(),
+            ConstraintCell::RadiusDimension(c) => c.as_ptr(),
+            ConstraintCell::DiameterDimension(c) => c.as_ptr(),
+            ConstraintCell::EqualRadius(c) => c.as_ptr(),
+            ConstraintCell::RadiusDifference(c) => c.as_ptr(),
+            ConstraintCell::AngleBetweenLines(c) => c.as_ptr(),
+            ConstraintCell::PointLineDistance(c) => c.as_ptr(),
+            ConstraintCell::LineLineDistance(c) => c.as_ptr(),
+            ConstraintCell::PointCircleDistance(c) => c.as_ptr(),
+            ConstraintCell::LineCircleDistance(c) => c.as_ptr(),
+            ConstraintCell::CollinearLines(c) => c.as_ptr(),
+            ConstraintCell::CollinearPoints(c) => c.as_ptr(),
+            ConstraintCell::ArcSweepAngle(c) => c.as_ptr(),
+            ConstraintCell::ArcLength(c) => c.as_ptr(),
+            ConstraintCell::EuclideanDistanceBound(c) => c.as_ptr(),
+            ConstraintCell::HorizontalDistanceBound(c) => c.as_ptr(),
+            ConstraintCell::VerticalDistanceBound(c) => c.as_ptr(),
+            ConstraintCell::RadiusBound(c) => c.as_ptr(),
+            ConstraintCell::AngleBound(c) => c.as_ptr(),
+            ConstraintCell::Pattern(c) => c.as_ptr(),
+            ConstraintCell::EqualLengths(c) => c.as_ptr(),
+        }
+    }
+}
+
+impl ConstraintCell {
+    // The values of a dimension constraint, like a distance or an angle. Fixed points have their
+    // x and y coordinates, geometric constraints have none.
+    pub fn dimensions(&self) -> Vec<f64> {
+        match self {
+            ConstraintCell::EuclideanDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::HorizontalDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::VerticalDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::PointLineDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::LineLineDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::PointCircleDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::LineCircleDistance(c) => vec![c.borrow().desired_distance()],
+            ConstraintCell::AngleBetweenPoints(c) => vec![c.borrow().desired_angle()],
+            ConstraintCell::AngleBetweenLines(c) => vec![c.borrow().desired_angle()],
+            ConstraintCell::ArcSweepAngle(c) => vec![c.borrow().desired_angle()],
+            ConstraintCell::ArcLength(c) => vec![c.borrow().desired_length()],
+            ConstraintCell::RadiusDimension(c) => vec![c.borrow().desired_radius()],
+            ConstraintCell::DiameterDimension(c) => vec![c.borrow().desired_diameter()],
+            ConstraintCell::RadiusDifference(c) => vec![c.borrow().desired_difference()],
+            ConstraintCell::EuclideanDistanceBound(c) => vec![c.borrow().limit()],
+            ConstraintCell::HorizontalDistanceBound(c) => vec![c.borrow().limit()],
+            ConstraintCell::VerticalDistanceBound(c) => vec![c.borrow().limit()],
+            ConstraintCell::RadiusBound(c) => vec![c.borrow().limit()],
+            ConstraintCell::AngleBound(c) => vec![c.borrow().limit()],
+            ConstraintCell::FixPoint(c) => {
+                let desired_pos = c.borrow().desired_pos();
+                vec![desired_pos.x, desired_pos.y]
+            }
+            ConstraintCell::Pattern(c) => match c.borrow().kind() {
+                pattern::PatternKind::Linear { spacing } => vec![spacing.x, spacing.y],
+                pattern::PatternKind::Circular { step_angle, .. } => vec![step_angle],
+            },
+            _ => vec![],
+        }
+    }
+
+    // Whether each dimension is a length or an angle, in the same order as `dimensions`
+    pub fn dimension_quantities(&self) -> Vec<Quantity> {
+        match self {
+            ConstraintCell::AngleBetweenPoints(_)
+            | ConstraintCell::AngleBetweenLines(_)
+            | ConstraintCell::ArcSweepAngle(_)
+            | ConstraintCell::AngleBound(_) => vec![Quantity::Angle],
+            ConstraintCell::Pattern(c) => match c.borrow().kind() {
+                pattern::PatternKind::Linear { .. } => vec![Quantity::Length; 2],
+                pattern::PatternKind::Circular { .. } => vec![Quantity::Angle],
+            },
+            _ => vec![Quantity::Length; self.dimensions().len()],
+        }
+    }
+
+    // The current values of a dimension constraint measured from the geometry, in the same order
+    // as `dimensions`. Patterns and geometric constraints can not be measured.
+    pub fn measure(&self) -> Vec<f64> {
+        match self {
+            ConstraintCell::EuclideanDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::HorizontalDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::VerticalDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::PointLineDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::LineLineDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::PointCircleDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::LineCircleDistance(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::AngleBetweenPoints(c) => vec![c.borrow().current_angle()],
+            ConstraintCell::ArcSweepAngle(c) => vec![c.borrow().arc().borrow().sweep_angle()],
+            ConstraintCell::ArcLength(c) => vec![c.borrow().arc().borrow().arc_length()],
+            ConstraintCell::RadiusDimension(c) => vec![c.borrow().curve().radius()],
+            ConstraintCell::DiameterDimension(c) => vec![2.0 * c.borrow().curve().radius()],
+            ConstraintCell::RadiusDifference(c) => vec![c.borrow().current_difference()],
+            ConstraintCell::EuclideanDistanceBound(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::HorizontalDistanceBound(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::VerticalDistanceBound(c) => vec![c.borrow().current_distance()],
+            ConstraintCell::RadiusBound(c) => vec![c.borrow().current_radius()],
+            ConstraintCell::AngleBound(c) => vec![c.borrow().current_angle()],
+            ConstraintCell::AngleBetweenLines(c) => {
+                let c = c.borrow();
+                if c.signed() {
+                    vec![c.current_angle()]
+                } else {
+                    vec![c.current_angle().abs()]
+                }
+            }
+            ConstraintCell::FixPoint(c) => {
+                let point = c.borrow().point().borrow().data();
+                vec![point.x, point.y]
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn set_dimension(&self, index: usize, value: f64) -> Result<(), ISOTopeError> {
+        if index >= self.dimensions().len() {
+            return Err(ISOTopeError::NotADimension(index));
+        }
+        match (self, index) {
+            (ConstraintCell::EuclideanDistance(c), 0) => c.borrow_mut().set_desired_distance(value),
+            (ConstraintCell::HorizontalDistance(c), 0) => {
+                c.borrow_mut().set_desired_distance(value)
+            }
+            (ConstraintCell::VerticalDistance(c), 0) => c.borrow_mut().set_desired_distance(value),
+            (ConstraintCell::PointLineDistance(c), 0) => c.borrow_mut().set_desired_distance(value),
+            (ConstraintCell::LineLineDistance(c), 0) => c.borrow_mut().set_desired_distance(value),
+            (ConstraintCell::PointCircleDistance(c), 0) => {
+                c.borrow_mut().set_desired_distance(value)
+            }
+            (ConstraintCell::LineCircleDistance(c), 0) => {
+                c.borrow_mut().set_desired_distance(value)
+            }
+            (ConstraintCell::AngleBetweenPoints(c), 0) => c.borrow_mut().set_desired_angle(value),
+            (ConstraintCell::AngleBetweenLines(c), 0) => c.borrow_mut().set_desired_angle(value),
+            (ConstraintCell::ArcSweepAngle(c), 0) => c.borrow_mut().set_desired_angle(value),
+            (ConstraintCell::ArcLength(c), 0) => c.borrow_mut().set_desired_length(value),
+            (ConstraintCell::RadiusDimension(c), 0) => c.borrow_mut().set_desired_radius(value),
+            (ConstraintCell::DiameterDimension(c), 0) => c.borrow_mut().set_desired_diameter(value),
+            (ConstraintCell::RadiusDifference(c), 0) => {
+                c.borrow_mut().set_desired_difference(value)
+            }
+            (ConstraintCell::EuclideanDistanceBound(c), 0) => c.borrow_mut().set_limit(value),
+            (ConstraintCell::HorizontalDistanceBound(c), 0) => c.borrow_mut().set_limit(value),
+            (ConstraintCell::VerticalDistanceBound(c), 0) => c.borrow_mut().set_limit(value),
+            (ConstraintCell::RadiusBound(c), 0) => c.borrow_mut().set_limit(value),
+            (ConstraintCell::AngleBound(c), 0) => c.borrow_mut().set_limit(value),
+            (ConstraintCell::FixPoint(c), _) => {
+                let mut desired_pos = c.borrow().desired_pos();
+                desired_pos[index] = value;
+                c.borrow_mut().set_desired_pos(desired_pos);
+            }
+            (ConstraintCell::Pattern(c), _) => {
+                let kind = c.borrow().kind();
+                match kind {
+                    pattern::PatternKind::Linear { mut spacing } => {
+                        spacing[index] = value;
+                        c.borrow_mut().set_spacing(spacing);
+                    }
+                    pattern::PatternKind::Circular { .. } => c.borrow_mut().set_step_angle(value),
+                }
+            }
+            _ => return Err(ISOTopeError::NotADimension(index)),
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,447 @@
+use std::{cell::RefCell, cmp::Ordering, f64::consts::PI, rc::Rc};
+
+use nalgebra::{DVector, Matrix2, SMatrix, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{point2::Point2, PrimitiveCell},
+};
+
+// How the copies of a pattern are placed relative to the seed.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum PatternKind {
+    // The k-th copy is shifted by k times the spacing
+    Linear {
+        spacing: Vector2<f64>,
+    },
+    // The k-th copy is rotated by k times the step angle around the center
+    Circular {
+        center: Rc<RefCell<Point2>>,
+        step_angle: f64,
+    },
+}
+
+impl PatternKind {
+    // The rotation of the k-th copy
+    fn rotation(&self, k: usize) -> Matrix2<f64> {
+        match self {
+            PatternKind::Linear { .. } => Matrix2::identity(),
+            PatternKind::Circular { step_angle, .. } => {
+                let (sin, cos) = (k as f64 * step_angle).sin_cos();
+                Matrix2::new(cos, -sin, sin, cos)
+            }
+        }
+    }
+
+    // Where the k-th copy of a seed point goes
+    fn transform(&self, k: usize, point: Vector2<f64>) -> Vector2<f64> {
+        match self {
+            PatternKind::Linear { spacing } => point + k as f64 * spacing,
+            PatternKind::Circular { center, .. } => {
+                let center = center.borrow().data();
+                center + self.rotation(k) * (point - center)
+            }
+        }
+    }
+
+    // The angle added to the angles and rotations of the k-th copy
+    fn angle(&self, k: usize) -> f64 {
+        match self {
+            PatternKind::Linear { .. } => 0.0,
+            PatternKind::Circular { step_angle, .. } => k as f64 * step_angle,
+        }
+    }
+}
+
+// The parameters of a primitive that are rotated along with a circular pattern
+fn angle_indices(primitive: &PrimitiveCell) -> &'static [usize] {
+    match primitive {
+        PrimitiveCell::Arc(_) => &[1, 2],
+        PrimitiveCell::Ellipse(_) | PrimitiveCell::EllipticalArc(_) => &[2],
+        _ => &[],
+    }
+}
+
+// Adds the gradient by the own parameters of a primitive, the referenced points are handled separately
+fn add_to_own_gradient(primitive: &PrimitiveCell, gradient: &DVector<f64>) {
+    let padded = [0.0, 0.0].into_iter().chain(gradient.iter().copied());
+    match primitive {
+        PrimitiveCell::Circle(c) => c
+            .borrow_mut()
+            .add_to_gradient(SMatrix::<f64, 1, 3>::from_iterator(padded).as_view()),
+        PrimitiveCell::Arc(a) => a
+            .borrow_mut()
+            .add_to_gradient(SMatrix::<f64, 1, 5>::from_iterator(padded).as_view()),
+        PrimitiveCell::Ellipse(e) => e
+            .borrow_mut()
+            .add_to_gradient(SMatrix::<f64, 1, 5>::from_iterator(padded).as_view()),
+        PrimitiveCell::EllipticalArc(e) => e
+            .borrow_mut()
+            .add_to_gradient(SMatrix::<f64, 1, 7>::from_iterator(padded).as_view()),
+        _ => {}
+    }
+}
+
+// This is a sketch constraint that ties copies of a set of primitives to a seed. The seed lists
+// the referenced points before the primitives using them, and every copy lists its primitives in
+// the same order as the seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Pattern {
+    seed: Vec<PrimitiveCell>,
+    copies: Vec<Vec<PrimitiveCell>>,
+    kind: PatternKind,
+}
+
+// Primitive cells are only compared by identity, so patterns have no meaningful order
+impl PartialOrd for Pattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
+
+impl Pattern {
+    pub fn new(
+        seed: Vec<PrimitiveCell>,
+        copies: Vec<Vec<PrimitiveCell>>,
+        kind: PatternKind,
+    ) -> Self {
+        assert!(Self::is_valid(&seed, &copies));
+        if let PatternKind::Circular { step_angle, .. } = &kind {
+            assert!(step_angle.is_finite());
+        }
+        Self { seed, copies, kind }
+    }
+
+    // Every primitive of the seed has to come after the points it references, and every copy has
+    // to match the seed primitive by primitive
+    pub fn is_valid(seed: &[PrimitiveCell], copies: &[Vec<PrimitiveCell>]) -> bool {
+        let closed = seed.iter().enumerate().all(|(i, primitive)| {
+            primitive
+                .borrow()
+                .references()
+                .iter()
+                .all(|reference| seed[..i].contains(reference))
+        });
+        let matching = copies.iter().all(|copy| {
+            copy.len() == seed.len()
+                && copy
+                    .iter()
+                    .zip(seed.iter())
+                    .all(|(c, s)| std::mem::discriminant(c) == std::mem::discriminant(s))
+        });
+        closed && matching
+    }
+
+    pub fn seed(&self) -> Vec<PrimitiveCell> {
+        self.seed.clone()
+    }
+
+    pub fn copies(&self) -> Vec<Vec<PrimitiveCell>> {
+        self.copies.clone()
+    }
+
+    // The number of copies, not counting the seed
+    pub fn count(&self) -> usize {
+        self.copies.len()
+    }
+
+    pub fn kind(&self) -> PatternKind {
+        self.kind.clone()
+    }
+
+    pub fn set_kind(&mut self, kind: PatternKind) {
+        self.kind = kind;
+    }
+
+    pub fn set_spacing(&mut self, spacing: Vector2<f64>) {
+        assert!(spacing.iter().all(|x| x.is_finite()));
+        if let PatternKind::Linear { spacing: s } = &mut self.kind {
+            *s = spacing;
+        }
+    }
+
+    pub fn set_step_angle(&mut self, step_angle: f64) {
+        assert!(step_angle.is_finite());
+        if let PatternKind::Circular { step_angle: s, .. } = &mut self.kind {
+            *s = step_angle;
+        }
+    }
+
+    // The step angle that spreads the seed and the given number of copies evenly over a full circle
+    pub fn full_circle_step(count: usize) -> f64 {
+        2.0 * PI / (count + 1) as f64
+    }
+
+    pub(crate) fn push_copy(&mut self, copy: Vec<PrimitiveCell>) {
+        assert!(copy.len() == self.seed.len());
+        self.copies.push(copy);
+    }
+
+    pub(crate) fn pop_copy(&mut self) -> Option<Vec<PrimitiveCell>> {
+        self.copies.pop()
+    }
+
+    // New primitives for the k-th copy, placed exactly where the pattern puts them
+    pub(crate) fn instantiate_copy(&self, k: usize) -> Vec<PrimitiveCell> {
+        let mut copy: Vec<PrimitiveCell> = Vec::with_capacity(self.seed.len());
+        for (i, primitive) in self.seed.iter().enumerate() {
+            // The seed lists referenced points first, so their copies already exist
+            let duplicate = primitive.duplicate(&mut |point| {
+                self.seed
+                    .iter()
+                    .zip(copy.iter())
+                    .find_map(|(s, c)| match (s, c) {
+                        (PrimitiveCell::Point2(s), PrimitiveCell::Point2(c))
+                            if Rc::ptr_eq(s, point) =>
+                        {
+                            Some(c.clone())
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| point.clone())
+            });
+            duplicate
+                .borrow_mut()
+                .set_data(self.target_data(i, k).as_view());
+            copy.push(duplicate);
+        }
+        copy
+    }
+
+    // The parameters the i-th primitive of the k-th copy should have, counting copies from 1
+    pub fn target_data(&self, i: usize, k: usize) -> DVector<f64> {
+        let seed = &self.seed[i];
+        match seed {
+            PrimitiveCell::Point2(p) => {
+                let target = self.kind.transform(k, p.borrow().data());
+                DVector::from_column_slice(target.as_slice())
+            }
+            _ => {
+                let mut target = seed.borrow().get_data().clone_owned();
+                for &index in angle_indices(seed) {
+                    target[index] += self.kind.angle(k);
+                }
+                target
+            }
+        }
+    }
+
+    fn residuals(&self) -> impl Iterator<Item = (usize, usize, DVector<f64>)> + '_ {
+        self.copies
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, copy)| {
+                copy.iter().enumerate().map(move |(i, primitive)| {
+                    let residual = primitive.borrow().get_data() - self.target_data(i, index + 1);
+                    (index + 1, i, residual)
+                })
+            })
+    }
+}
+
+impl ConstraintLike for Pattern {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        let mut references = self.seed.clone();
+        references.extend(self.copies.iter().flatten().cloned());
+        if let PatternKind::Circular { center, .. } = &self.kind {
+            references.push(PrimitiveCell::Point2(center.clone()));
+        }
+        references
+    }
+
+    fn loss_value(&self) -> f64 {
+        self.residuals()
+            .map(|(_, _, residual)| 0.5 * residual.norm_squared())
+            .sum()
+    }
+
+    fn update_gradient(&mut self) {
+        let residuals: Vec<_> = self.residuals().collect();
+        for (k, i, residual) in residuals {
+            let seed = &self.seed[i];
+            let copy = &self.copies[k - 1][i];
+            match (seed, copy) {
+                (PrimitiveCell::Point2(seed), PrimitiveCell::Point2(copy)) => {
+                    let gradient_constraint = Vector2::new(residual[0], residual[1]).transpose();
+                    let rotation = self.kind.rotation(k);
+
+                    let grad_seed = seed.borrow().point_gradient();
+                    let grad_copy = copy.borrow().point_gradient();
+                    seed.borrow_mut()
+                        .add_to_gradient((-gradient_constraint * rotation * grad_seed).as_view());
+                    copy.borrow_mut()
+                        .add_to_gradient((gradient_constraint * grad_copy).as_view());
+
+                    if let PatternKind::Circular { center, .. } = &self.kind {
+                        let grad_center = center.borrow().point_gradient();
+                        center.borrow_mut().add_to_gradient(
+                            (-gradient_constraint * (Matrix2::identity() - rotation) * grad_center)
+                                .as_view(),
+                        );
+                    }
+                }
+                _ => {
+                    add_to_own_gradient(seed, &-&residual);
+                    add_to_own_gradient(copy, &residual);
+                }
+            }
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::Pattern(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, f64::consts::PI};
+
+    use nalgebra::{DVector, Vector2};
+
+    use crate::{
+        constraints::{
+            pattern::{Pattern, PatternKind},
+            ConstraintLike,
+        },
+        primitives::PrimitiveCell,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_linear_pattern() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center.clone(), 0.5)?;
+
+        let pattern = sketch.add_pattern(
+            vec![PrimitiveCell::Circle(circle.clone())],
+            PatternKind::Linear {
+                spacing: Vector2::new(2.0, 0.0),
+            },
+            3,
+        )?;
+        assert_eq!(sketch.get_num_primitives(), 8);
+        assert!(pattern.borrow().loss_value() < 1e-16);
+
+        // Move the seed and perturb a copy, the pattern pulls the copies along
+        center.borrow_mut().set_x(1.0);
+        circle.borrow_mut().set_radius(0.75);
+        let copy = pattern.borrow().copies()[1][0].clone();
+        copy.borrow_mut()
+            .set_data(DVector::from_vec(vec![4.5, 0.5]).as_view());
+
+        sketch.check_gradients(1e-6, pattern.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("pattern: {:?}", pattern.as_ref().borrow());
+
+        assert!(pattern.borrow().loss_value() < 1e-10);
+        let PrimitiveCell::Circle(last) = pattern.borrow().copies()[2][1].clone() else {
+            return Err("the copy should be a circle".into());
+        };
+        let expected = center.borrow().data() + Vector2::new(6.0, 0.0);
+        assert!((last.borrow().center().borrow().data() - expected).norm() < 1e-5);
+        assert!((last.borrow().radius() - circle.borrow().radius()).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_circular_pattern() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let hub = sketch.add_point2(1.0, 0.0)?;
+        let start = sketch.add_point2(1.5, -0.2)?;
+        let end = sketch.add_point2(2.0, 0.2)?;
+        let line = sketch.add_line(start, end)?;
+        let arc = sketch.add_arc(hub, 0.3, false, -0.5, 0.5)?;
+
+        let pattern = sketch.add_pattern(
+            vec![PrimitiveCell::Line(line), PrimitiveCell::Arc(arc.clone())],
+            PatternKind::Circular {
+                center: center.clone(),
+                step_angle: Pattern::full_circle_step(5),
+            },
+            5,
+        )?;
+        assert!(pattern.borrow().loss_value() < 1e-16);
+
+        // The third copy of the arc is rotated by half a turn
+        let PrimitiveCell::Arc(opposite) = pattern.borrow().copies()[2][4].clone() else {
+            return Err("the copy should be an arc".into());
+        };
+        assert!(
+            (opposite.borrow().center().borrow().data() - Vector2::new(-1.0, 0.0)).norm() < 1e-12
+        );
+        assert!((opposite.borrow().start_angle() - (PI - 0.5)).abs() < 1e-12);
+
+        // Move the center and resize the step, the copies follow
+        center.borrow_mut().set_y(0.05);
+        pattern.borrow_mut().set_step_angle(1.0);
+        arc.borrow_mut().set_radius(0.35);
+
+        // The loss sums over all copies, so the forward differences are less accurate
+        sketch.check_gradients(1e-6, pattern.clone(), 1e-4);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("pattern: {:?}", pattern.as_ref().borrow());
+
+        assert!(pattern.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_count() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(0.0, 1.0)?;
+        let line = sketch.add_line(start, end)?;
+
+        let pattern = sketch.add_pattern(
+            vec![PrimitiveCell::Line(line)],
+            PatternKind::Linear {
+                spacing: Vector2::new(1.0, 0.0),
+            },
+            2,
+        )?;
+        assert_eq!(sketch.get_num_primitives(), 9);
+
+        // Constraints on a copy go away with the copy
+        let PrimitiveCell::Line(last) = pattern.borrow().copies()[1][2].clone() else {
+            return Err("the copy should be a line".into());
+        };
+        sketch.constrain_parallel_lines(last.clone(), last)?;
+        assert_eq!(sketch.get_num_constraints(), 2);
+
+        sketch.set_pattern_count(&pattern, 4)?;
+        assert_eq!(sketch.get_num_primitives(), 15);
+        assert!(pattern.borrow().loss_value() < 1e-16);
+
+        sketch.set_pattern_count(&pattern, 1)?;
+        assert_eq!(pattern.borrow().count(), 1);
+        assert_eq!(sketch.get_num_primitives(), 6);
+        assert_eq!(sketch.get_num_constraints(), 1);
+
+        // Changing the spacing moves the remaining copy
+        pattern.borrow_mut().set_spacing(Vector2::new(0.0, -2.0));
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+        assert!(pattern.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{radius::RadialCurve, ConstraintLike},
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that dimensions the diameter of a circle or arc.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DiameterDimension {
+    curve: RadialCurve,
+
+    desired_diameter: f64,
+}
+
+impl DiameterDimension {
+    pub fn new(curve: RadialCurve, desired_diameter: f64) -> Self {
+        assert!(desired_diameter.is_finite());
+        Self {
+            curve,
+            desired_diameter,
+        }
+    }
+
+    pub fn curve(&self) -> RadialCurve {
+        self.curve.clone()
+    }
+
+    pub fn set_curve(&mut self, curve: RadialCurve) {
+        self.curve = curve;
+    }
+
+    pub fn desired_diameter(&self) -> f64 {
+        self.desired_diameter
+    }
+
+    pub fn set_desired_diameter(&mut self, desired_diameter: f64) {
+        self.desired_diameter = desired_diameter;
+    }
+}
+
+impl ConstraintLike for DiameterDimension {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = 2.0 * self.curve.radius() - self.desired_diameter;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = 2.0 * self.curve.radius() - self.desired_diameter;
+        self.curve.add_to_gradient(2.0 * err);
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::DiameterDimension(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_diameter_dimension() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center, 0.5)?;
+
+        let constr1 = sketch.constrain_diameter(RadialCurve::Circle(circle.clone()), 3.0)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("circle: {:?}", circle.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((circle.borrow().radius() - 1.5).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{radius::RadialCurve, ConstraintLike},
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that makes two circles or arcs have the same radius.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EqualRadius {
+    curve1: RadialCurve,
+    curve2: RadialCurve,
+}
+
+impl EqualRadius {
+    pub fn new(curve1: RadialCurve, curve2: RadialCurve) -> Self {
+        Self { curve1, curve2 }
+    }
+
+    pub fn curve1(&self) -> RadialCurve {
+        self.curve1.clone()
+    }
+
+    pub fn set_curve1(&mut self, curve1: RadialCurve) {
+        self.curve1 = curve1;
+    }
+
+    pub fn curve2(&self) -> RadialCurve {
+        self.curve2.clone()
+    }
+
+    pub fn set_curve2(&mut self, curve2: RadialCurve) {
+        self.curve2 = curve2;
+    }
+}
+
+impl ConstraintLike for EqualRadius {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve1.primitive(), self.curve2.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.curve1.radius() - self.curve2.radius();
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = self.curve1.radius() - self.curve2.radius();
+        self.curve1.add_to_gradient(err);
+        self.curve2.add_to_gradient(-err);
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::EqualRadius(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_equal_radius() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center1 = sketch.add_point2(0.0, 0.0)?;
+        let center2 = sketch.add_point2(3.0, 1.0)?;
+        let circle = sketch.add_circle(center1, 0.5)?;
+        let arc = sketch.add_arc(center2, 2.0, true, 1.0, -1.0)?;
+
+        let constr1 = sketch.constrain_equal_radius(
+            RadialCurve::Circle(circle.clone()),
+            RadialCurve::Arc(arc.clone()),
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("circle: {:?}", circle.as_ref().borrow());
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::primitives::{arc::Arc, circle::Circle, PrimitiveCell};
+
+pub mod diameter_dimension;
+pub mod equal_radius;
+pub mod radius_difference;
+pub mod radius_dimension;
+
+// A primitive with a radius that can be dimensioned
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum RadialCurve {
+    Circle(Rc<RefCell<Circle>>),
+    Arc(Rc<RefCell<Arc>>),
+}
+
+impl RadialCurve {
+    pub fn primitive(&self) -> PrimitiveCell {
+        match self {
+            RadialCurve::Circle(circle) => PrimitiveCell::Circle(circle.clone()),
+            RadialCurve::Arc(arc) => PrimitiveCell::Arc(arc.clone()),
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        match self {
+            RadialCurve::Circle(circle) => circle.borrow().radius(),
+            RadialCurve::Arc(arc) => arc.borrow().radius(),
+        }
+    }
+
+    // Adds the gradient of the loss by the radius to the parameters of the primitive
+    pub(crate) fn add_to_gradient(&self, gradient: f64) {
+        match self {
+            RadialCurve::Circle(circle) => {
+                let grad_circle = gradient * circle.borrow().radius_gradient();
+                circle.borrow_mut().add_to_gradient(grad_circle.as_view());
+            }
+            RadialCurve::Arc(arc) => {
+                let grad_arc = gradient * arc.borrow().radius_gradient();
+                arc.borrow_mut().add_to_gradient(grad_arc.as_view());
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{radius::RadialCurve, ConstraintLike},
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that makes the radius of the second circle or arc larger than that
+// of the first by the desired difference, e.g. for an offset around the same center.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RadiusDifference {
+    curve1: RadialCurve,
+    curve2: RadialCurve,
+    desired_difference: f64,
+}
+
+impl RadiusDifference {
+    pub fn new(curve1: RadialCurve, curve2: RadialCurve, desired_difference: f64) -> Self {
+        Self {
+            curve1,
+            curve2,
+            desired_difference,
+        }
+    }
+
+    pub fn curve1(&self) -> RadialCurve {
+        self.curve1.clone()
+    }
+
+    pub fn set_curve1(&mut self, curve1: RadialCurve) {
+        self.curve1 = curve1;
+    }
+
+    pub fn curve2(&self) -> RadialCurve {
+        self.curve2.clone()
+    }
+
+    pub fn set_curve2(&mut self, curve2: RadialCurve) {
+        self.curve2 = curve2;
+    }
+
+    pub fn desired_difference(&self) -> f64 {
+        self.desired_difference
+    }
+
+    pub fn set_desired_difference(&mut self, desired_difference: f64) {
+        self.desired_difference = desired_difference;
+    }
+
+    pub fn current_difference(&self) -> f64 {
+        self.curve2.radius() - self.curve1.radius()
+    }
+}
+
+impl ConstraintLike for RadiusDifference {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve1.primitive(), self.curve2.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.current_difference() - self.desired_difference;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = self.current_difference() - self.desired_difference;
+        self.curve1.add_to_gradient(-err);
+        self.curve2.add_to_gradient(err);
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::RadiusDifference(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_radius_difference() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center.clone(), 0.5)?;
+        let arc = sketch.add_arc(center, 2.0, true, 1.0, -1.0)?;
+
+        let constr1 = sketch.constrain_radius_difference(
+            RadialCurve::Circle(circle.clone()),
+            RadialCurve::Arc(arc.clone()),
+            -0.25,
+        )?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((arc.borrow().radius() - circle.borrow().radius() + 0.25).abs() < 1e-5);
+        Ok(())
+    }
+}
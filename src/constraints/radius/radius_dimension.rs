@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{radius::RadialCurve, ConstraintLike},
+    primitives::PrimitiveCell,
+};
+
+// This is a sketch constraint that dimensions the radius of a circle or arc.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RadiusDimension {
+    curve: RadialCurve,
+
+    desired_radius: f64,
+}
+
+impl RadiusDimension {
+    pub fn new(curve: RadialCurve, desired_radius: f64) -> Self {
+        assert!(desired_radius.is_finite());
+        Self {
+            curve,
+            desired_radius,
+        }
+    }
+
+    pub fn curve(&self) -> RadialCurve {
+        self.curve.clone()
+    }
+
+    pub fn set_curve(&mut self, curve: RadialCurve) {
+        self.curve = curve;
+    }
+
+    pub fn desired_radius(&self) -> f64 {
+        self.desired_radius
+    }
+
+    pub fn set_desired_radius(&mut self, desired_radius: f64) {
+        self.desired_radius = desired_radius;
+    }
+}
+
+impl ConstraintLike for RadiusDimension {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![self.curve.primitive()]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let err = self.curve.radius() - self.desired_radius;
+        0.5 * err * err
+    }
+
+    fn update_gradient(&mut self) {
+        let err = self.curve.radius() - self.desired_radius;
+        self.curve.add_to_gradient(err);
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::RadiusDimension(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_radius_dimension() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(1.0, 2.0)?;
+        let circle = sketch.add_circle(center, 0.5)?;
+
+        let constr1 = sketch.constrain_radius(RadialCurve::Circle(circle.clone()), 2.0)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("circle: {:?}", circle.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((circle.borrow().radius() - 2.0).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arc_radius_dimension() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(1.0, 2.0)?;
+        let arc = sketch.add_arc(center, 3.0, false, 0.0, 1.0)?;
+
+        let constr1 = sketch.constrain_radius(RadialCurve::Arc(arc.clone()), 1.5)?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc: {:?}", arc.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((arc.borrow().radius() - 1.5).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use nalgebra::{Matrix2, Vector2};
+
+pub mod symmetric_arcs;
+pub mod symmetric_lines;
+pub mod symmetric_points;
+
+// A mirrored point and its gradients by the point, the start and the end of the symmetry line
+type Mirrored = (Vector2<f64>, Matrix2<f64>, Matrix2<f64>, Matrix2<f64>);
+
+// The mirror image m = 2 * a + 2 * s * d - p of the point p across the line from a to b, with
+// d = b - a and s = (p - a) . d / |d|^2, together with its gradients by p, a and b. Returns None if
+// the line is degenerate.
+pub(crate) fn mirror(
+    point: &Vector2<f64>,
+    start: &Vector2<f64>,
+    end: &Vector2<f64>,
+) -> Option<Mirrored> {
+    let d = end - start;
+    let length_squared = d.norm_squared();
+    if length_squared < 1e-24 {
+        return None;
+    }
+    let relative = point - start;
+    let s = relative.dot(&d) / length_squared;
+    let mirrored = 2.0 * start + 2.0 * s * d - point;
+
+    let projection = d * d.transpose() / length_squared;
+    let grad_from_point = 2.0 * projection - Matrix2::identity();
+    let grad_s_from_d = (relative.transpose() - 2.0 * s * d.transpose()) / length_squared;
+    let grad_from_d = 2.0 * s * Matrix2::identity() + 2.0 * d * grad_s_from_d;
+    let grad_from_start = 2.0 * Matrix2::identity() - 2.0 * projection - grad_from_d;
+    let grad_from_end = grad_from_d;
+
+    Some((mirrored, grad_from_point, grad_from_start, grad_from_end))
+}
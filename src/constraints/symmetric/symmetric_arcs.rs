@@ -0,0 +1,156 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{symmetric::mirror, ConstraintLike},
+    primitives::{arc::Arc, line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes two arcs mirror images of each other across a symmetry
+// line: the centers are mirrored and the radii are equal.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SymmetricArcs {
+    arc1: Rc<RefCell<Arc>>,
+    arc2: Rc<RefCell<Arc>>,
+    axis: Rc<RefCell<Line>>,
+}
+
+impl SymmetricArcs {
+    pub fn new(arc1: Rc<RefCell<Arc>>, arc2: Rc<RefCell<Arc>>, axis: Rc<RefCell<Line>>) -> Self {
+        Self { arc1, arc2, axis }
+    }
+
+    pub fn arc1(&self) -> Rc<RefCell<Arc>> {
+        self.arc1.clone()
+    }
+
+    pub fn set_arc1(&mut self, arc1: Rc<RefCell<Arc>>) {
+        self.arc1 = arc1;
+    }
+
+    pub fn arc2(&self) -> Rc<RefCell<Arc>> {
+        self.arc2.clone()
+    }
+
+    pub fn set_arc2(&mut self, arc2: Rc<RefCell<Arc>>) {
+        self.arc2 = arc2;
+    }
+
+    pub fn axis(&self) -> Rc<RefCell<Line>> {
+        self.axis.clone()
+    }
+
+    pub fn set_axis(&mut self, axis: Rc<RefCell<Line>>) {
+        self.axis = axis;
+    }
+}
+
+impl ConstraintLike for SymmetricArcs {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Arc(self.arc1.clone()),
+            PrimitiveCell::Arc(self.arc2.clone()),
+            PrimitiveCell::Line(self.axis.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let axis_start = self.axis.borrow().start().borrow().data();
+        let axis_end = self.axis.borrow().end().borrow().data();
+        let arc1 = self.arc1.borrow();
+        let arc2 = self.arc2.borrow();
+
+        let radius_error = arc2.radius() - arc1.radius();
+        let center1 = arc1.center().borrow().data();
+        let center2 = arc2.center().borrow().data();
+        let center_loss = match mirror(&center1, &axis_start, &axis_end) {
+            Some((mirrored, _, _, _)) => 0.5 * (center2 - mirrored).norm_squared(),
+            None => 0.0,
+        };
+        center_loss + 0.5 * radius_error * radius_error
+    }
+
+    fn update_gradient(&mut self) {
+        let axis_start = self.axis.borrow().start().borrow().data();
+        let axis_end = self.axis.borrow().end().borrow().data();
+        let arc1 = self.arc1.borrow().clone();
+        let arc2 = self.arc2.borrow().clone();
+
+        let radius_error = arc2.radius() - arc1.radius();
+        let mut grad_arc1 = -radius_error * arc1.radius_gradient();
+        let mut grad_arc2 = radius_error * arc2.radius_gradient();
+
+        let center1 = arc1.center().borrow().data();
+        let center2 = arc2.center().borrow().data();
+        if let Some((mirrored, grad_from_center1, grad_from_start, grad_from_end)) =
+            mirror(&center1, &axis_start, &axis_end)
+        {
+            let error = (center2 - mirrored).transpose();
+            grad_arc1 -= error * grad_from_center1 * arc1.center_gradient();
+            grad_arc2 += error * arc2.center_gradient();
+
+            let grad_axis_start = self.axis.borrow().start_gradient();
+            let grad_axis_end = self.axis.borrow().end_gradient();
+            self.axis.borrow_mut().add_to_gradient(
+                (-error * (grad_from_start * grad_axis_start + grad_from_end * grad_axis_end))
+                    .as_view(),
+            );
+        }
+
+        self.arc1.borrow_mut().add_to_gradient(grad_arc1.as_view());
+        self.arc2.borrow_mut().add_to_gradient(grad_arc2.as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::SymmetricArcs(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{symmetric::symmetric_arcs::SymmetricArcs, ConstraintCell, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_symmetric_arcs() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let axis_start = sketch.add_point2(0.0, 0.0)?;
+        let axis_end = sketch.add_point2(1.0, 1.0)?;
+        let axis = sketch.add_line(axis_start, axis_end)?;
+        let center1 = sketch.add_point2(-1.0, 1.5)?;
+        let center2 = sketch.add_point2(1.0, -0.5)?;
+        let arc1 = sketch.add_arc(center1, 0.5, false, 0.0, 1.0)?;
+        let arc2 = sketch.add_arc(center2, 0.8, true, 0.5, -1.0)?;
+
+        let constr1 = Rc::new(RefCell::new(SymmetricArcs::new(
+            arc1.clone(),
+            arc2.clone(),
+            axis.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::SymmetricArcs(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("arc1: {:?}", arc1.as_ref().borrow());
+        println!("arc2: {:?}", arc2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        assert!((arc1.borrow().radius() - arc2.borrow().radius()).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{symmetric::mirror, ConstraintLike},
+    primitives::{line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes two lines mirror images of each other across a symmetry
+// line. The start of the first line is mirrored onto the start of the second, and the same for
+// the ends.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SymmetricLines {
+    line1: Rc<RefCell<Line>>,
+    line2: Rc<RefCell<Line>>,
+    axis: Rc<RefCell<Line>>,
+}
+
+impl SymmetricLines {
+    pub fn new(
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+        axis: Rc<RefCell<Line>>,
+    ) -> Self {
+        Self { line1, line2, axis }
+    }
+
+    pub fn line1(&self) -> Rc<RefCell<Line>> {
+        self.line1.clone()
+    }
+
+    pub fn set_line1(&mut self, line1: Rc<RefCell<Line>>) {
+        self.line1 = line1;
+    }
+
+    pub fn line2(&self) -> Rc<RefCell<Line>> {
+        self.line2.clone()
+    }
+
+    pub fn set_line2(&mut self, line2: Rc<RefCell<Line>>) {
+        self.line2 = line2;
+    }
+
+    pub fn axis(&self) -> Rc<RefCell<Line>> {
+        self.axis.clone()
+    }
+
+    pub fn set_axis(&mut self, axis: Rc<RefCell<Line>>) {
+        self.axis = axis;
+    }
+}
+
+impl ConstraintLike for SymmetricLines {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line1.clone()),
+            PrimitiveCell::Line(self.line2.clone()),
+            PrimitiveCell::Line(self.axis.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let axis_start = self.axis.borrow().start().borrow().data();
+        let axis_end = self.axis.borrow().end().borrow().data();
+        let line1 = self.line1.borrow();
+        let line2 = self.line2.borrow();
+
+        let mut loss = 0.0;
+        for (point1, point2) in [(line1.start(), line2.start()), (line1.end(), line2.end())] {
+            if let Some((mirrored, _, _, _)) =
+                mirror(&point1.borrow().data(), &axis_start, &axis_end)
+            {
+                loss += 0.5 * (point2.borrow().data() - mirrored).norm_squared();
+            }
+        }
+        loss
+    }
+
+    fn update_gradient(&mut self) {
+        let axis_start = self.axis.borrow().start().borrow().data();
+        let axis_end = self.axis.borrow().end().borrow().data();
+        let line1 = self.line1.borrow().clone();
+        let line2 = self.line2.borrow().clone();
+
+        let grad_axis_start = self.axis.borrow().start_gradient();
+        let grad_axis_end = self.axis.borrow().end_gradient();
+
+        for (point1, point2, grad_point1, grad_point2) in [
+            (
+                line1.start(),
+                line2.start(),
+                line1.start_gradient(),
+                line2.start_gradient(),
+            ),
+            (
+                line1.end(),
+                line2.end(),
+                line1.end_gradient(),
+                line2.end_gradient(),
+            ),
+        ] {
+            let Some((mirrored, grad_from_point1, grad_from_start, grad_from_end)) =
+                mirror(&point1.borrow().data(), &axis_start, &axis_end)
+            else {
+                continue;
+            };
+            let error = (point2.borrow().data() - mirrored).transpose();
+
+            self.line1
+                .borrow_mut()
+                .add_to_gradient((-error * grad_from_point1 * grad_point1).as_view());
+            self.line2
+                .borrow_mut()
+                .add_to_gradient((error * grad_point2).as_view());
+            self.axis.borrow_mut().add_to_gradient(
+                (-error * (grad_from_start * grad_axis_start + grad_from_end * grad_axis_end))
+                    .as_view(),
+            );
+        }
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::SymmetricLines(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{symmetric::symmetric_lines::SymmetricLines, ConstraintCell, ConstraintLike},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_symmetric_lines() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let axis_start = sketch.add_point2(0.0, 0.0)?;
+        let axis_end = sketch.add_point2(0.0, 4.0)?;
+        let axis = sketch.add_line(axis_start, axis_end)?;
+        let a = sketch.add_point2(-1.0, 0.2)?;
+        let b = sketch.add_point2(-2.0, 1.0)?;
+        let c = sketch.add_point2(1.5, -0.2)?;
+        let d = sketch.add_point2(1.8, 1.3)?;
+        let line1 = sketch.add_line(a, b)?;
+        let line2 = sketch.add_line(c, d)?;
+
+        let constr1 = Rc::new(RefCell::new(SymmetricLines::new(
+            line1.clone(),
+            line2.clone(),
+            axis.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::SymmetricLines(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("line1: {:?}", line1.as_ref().borrow());
+        println!("line2: {:?}", line2.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::{symmetric::mirror, ConstraintLike},
+    primitives::{line::Line, point2::Point2, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes two points mirror images of each other across a line, so
+// their midpoint lies on the line and the segment between them is perpendicular to it.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Symmetric {
+    point1: Rc<RefCell<Point2>>,
+    point2: Rc<RefCell<Point2>>,
+    line: Rc<RefCell<Line>>,
+}
+
+impl Symmetric {
+    pub fn new(
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        line: Rc<RefCell<Line>>,
+    ) -> Self {
+        Self {
+            point1,
+            point2,
+            line,
+        }
+    }
+
+    pub fn point1(&self) -> Rc<RefCell<Point2>> {
+        self.point1.clone()
+    }
+
+    pub fn set_point1(&mut self, point1: Rc<RefCell<Point2>>) {
+        self.point1 = point1;
+    }
+
+    pub fn point2(&self) -> Rc<RefCell<Point2>> {
+        self.point2.clone()
+    }
+
+    pub fn set_point2(&mut self, point2: Rc<RefCell<Point2>>) {
+        self.point2 = point2;
+    }
+
+    pub fn line(&self) -> Rc<RefCell<Line>> {
+        self.line.clone()
+    }
+
+    pub fn set_line(&mut self, line: Rc<RefCell<Line>>) {
+        self.line = line;
+    }
+}
+
+impl ConstraintLike for Symmetric {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Point2(self.point1.clone()),
+            PrimitiveCell::Point2(self.point2.clone()),
+            PrimitiveCell::Line(self.line.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let point1 = self.point1.borrow().data();
+        let point2 = self.point2.borrow().data();
+        match mirror(&point1, &start, &end) {
+            Some((mirrored, _, _, _)) => 0.5 * (point2 - mirrored).norm_squared(),
+            None => 0.0,
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let point1 = self.point1.borrow().data();
+        let point2 = self.point2.borrow().data();
+        let Some((mirrored, grad_from_point1, grad_from_start, grad_from_end)) =
+            mirror(&point1, &start, &end)
+        else {
+            return;
+        };
+
+        let error = (point2 - mirrored).transpose();
+
+        let grad_point1 = self.point1.borrow().point_gradient();
+        let grad_point2 = self.point2.borrow().point_gradient();
+        let grad_start = self.line.borrow().start_gradient();
+        let grad_end = self.line.borrow().end_gradient();
+
+        self.point1
+            .borrow_mut()
+            .add_to_gradient((-error * grad_from_point1 * grad_point1).as_view());
+        self.point2
+            .borrow_mut()
+            .add_to_gradient((error * grad_point2).as_view());
+        self.line.borrow_mut().add_to_gradient(
+            (-error * (grad_from_start * grad_start + grad_from_end * grad_end)).as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::Symmetric(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::ConstraintLike,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_symmetric() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let start = sketch.add_point2(0.0, -1.0)?;
+        let end = sketch.add_point2(0.3, 2.0)?;
+        let line = sketch.add_line(start, end)?;
+        let point1 = sketch.add_point2(-1.0, 0.5)?;
+        let point2 = sketch.add_point2(2.0, 1.5)?;
+
+        let constr1 = sketch.constrain_symmetric(point1.clone(), point2.clone(), line.clone())?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("point1: {:?}", point1.as_ref().borrow());
+        println!("point2: {:?}", point2.as_ref().borrow());
+        println!("line: {:?}", line.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+
+        // The midpoint is on the line and the connection is perpendicular to it
+        let start = line.borrow().start().borrow().data();
+        let direction = line.borrow().end().borrow().data() - start;
+        let point1 = point1.borrow().data();
+        let point2 = point2.borrow().data();
+        let midpoint = 0.5 * (point1 + point2) - start;
+        assert!((midpoint.x * direction.y - midpoint.y * direction.x).abs() < 1e-5);
+        assert!((point2 - point1).dot(&direction).abs() < 1e-5);
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::{Matrix2, SMatrix};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{bspline::BSpline, line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes the tangent at the start of a B-spline parallel to a line.
+// Combine it with a coincident constraint to attach the spline tangentially to the line.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct BSplineStartTangent {
+    spline: Rc<RefCell<BSpline>>,
+    line: Rc<RefCell<Line>>,
+}
+
+impl BSplineStartTangent {
+    pub fn new(spline: Rc<RefCell<BSpline>>, line: Rc<RefCell<Line>>) -> Self {
+        Self { spline, line }
+    }
+
+    pub fn spline(&self) -> Rc<RefCell<BSpline>> {
+        self.spline.clone()
+    }
+
+    pub fn set_spline(&mut self, spline: Rc<RefCell<BSpline>>) {
+        self.spline = spline;
+    }
+
+    pub fn line(&self) -> Rc<RefCell<Line>> {
+        self.line.clone()
+    }
+
+    pub fn set_line(&mut self, line: Rc<RefCell<Line>>) {
+        self.line = line;
+    }
+}
+
+impl ConstraintLike for BSplineStartTangent {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::BSpline(self.spline.clone()),
+            PrimitiveCell::Line(self.line.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        let spline = self.spline.borrow();
+        let tangent = spline.derivative_at(spline.start_parameter()).normalize();
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let direction = (end - start).normalize();
+        if !tangent.iter().all(|x| x.is_finite()) || !direction.iter().all(|x| x.is_finite()) {
+            return 0.0;
+        }
+
+        let cross_product = tangent.x * direction.y - tangent.y * direction.x;
+        0.5 * cross_product * cross_product
+    }
+
+    fn update_gradient(&mut self) {
+        let spline = self.spline.borrow().clone();
+        let tangent = spline.derivative_at(spline.start_parameter());
+        let tangent_norm = tangent.normalize();
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let direction = end - start;
+        let direction_norm = direction.normalize();
+        if !tangent_norm.iter().all(|x| x.is_finite())
+            || !direction_norm.iter().all(|x| x.is_finite())
+        {
+            return;
+        }
+
+        let cross_product = tangent_norm.x * direction_norm.y - tangent_norm.y * direction_norm.x;
+
+        let grad_cross_product_from_tangent_norm =
+            SMatrix::<f64, 1, 2>::from_row_slice(&[direction_norm.y, -direction_norm.x]);
+        let grad_cross_product_from_direction_norm =
+            SMatrix::<f64, 1, 2>::from_row_slice(&[-tangent_norm.y, tangent_norm.x]);
+        let grad_tangent_norm_from_tangent = (Matrix2::identity()
+            - tangent * tangent.transpose() / tangent.norm_squared())
+            / tangent.norm();
+        let grad_direction_norm_from_direction = (Matrix2::identity()
+            - direction * direction.transpose() / direction.norm_squared())
+            / direction.norm();
+
+        let grad_tangent = spline.derivative_at_gradient(spline.start_parameter());
+        let grad_start = self.line.borrow().start_gradient();
+        let grad_end = self.line.borrow().end_gradient();
+
+        self.spline.borrow_mut().add_to_gradient(
+            (cross_product
+                * grad_cross_product_from_tangent_norm
+                * grad_tangent_norm_from_tangent
+                * grad_tangent)
+                .as_view(),
+        );
+        self.line.borrow_mut().add_to_gradient(
+            (cross_product
+                * grad_cross_product_from_direction_norm
+                * grad_direction_norm_from_direction
+                * (grad_end - grad_start))
+                .as_view(),
+        );
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::BSplineStartTangent(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            tangent::bspline_start_tangent::BSplineStartTangent, ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_bspline_start_tangent() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let control_point0 = sketch.add_point2(0.0, 0.0)?;
+        let control_point1 = sketch.add_point2(1.0, 2.0)?;
+        let control_point2 = sketch.add_point2(2.0, -1.0)?;
+        let control_point3 = sketch.add_point2(3.0, 0.5)?;
+        let spline = sketch.add_bspline(
+            vec![
+                control_point0,
+                control_point1,
+                control_point2,
+                control_point3,
+            ],
+            2,
+            vec![0.0, 0.0, 0.0, 0.4, 1.0, 1.0, 1.0],
+            Some(vec![1.0, 2.0, 0.5, 1.0]),
+        )?;
+        let start = sketch.add_point2(-2.0, 0.3)?;
+        let end = sketch.add_point2(-1.0, 0.4)?;
+        let line = sketch.add_line(start, end)?;
+
+        let constr1 = Rc::new(RefCell::new(BSplineStartTangent::new(
+            spline.clone(),
+            line.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::BSplineStartTangent(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("spline: {:?}", spline.as_ref().borrow());
+        println!("line: {:?}", line.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nalgebra::{Matrix2, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::{
+    constraints::ConstraintLike,
+    primitives::{ellipse::Ellipse, line::Line, PrimitiveCell},
+};
+
+// This is a sketch constraint that makes a line tangent to an ellipse. The (infinite) line is
+// tangent if the distance from the center of the ellipse to the line equals the half-width of the
+// ellipse in the direction of the line normal.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct LineEllipseTangent {
+    line: Rc<RefCell<Line>>,
+    ellipse: Rc<RefCell<Ellipse>>,
+}
+
+impl LineEllipseTangent {
+    pub fn new(line: Rc<RefCell<Line>>, ellipse: Rc<RefCell<Ellipse>>) -> Self {
+        Self { line, ellipse }
+    }
+
+    pub fn line(&self) -> Rc<RefCell<Line>> {
+        self.line.clone()
+    }
+
+    pub fn set_line(&mut self, line: Rc<RefCell<Line>>) {
+        self.line = line;
+    }
+
+    pub fn ellipse(&self) -> Rc<RefCell<Ellipse>> {
+        self.ellipse.clone()
+    }
+
+    pub fn set_ellipse(&mut self, ellipse: Rc<RefCell<Ellipse>>) {
+        self.ellipse = ellipse;
+    }
+
+    // Returns the signed distance of the center to the line and the half-width of the ellipse
+    // along the line normal.
+    fn distances(&self) -> Option<(f64, f64)> {
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let ellipse = self.ellipse.borrow();
+        let center = ellipse.center().borrow().data();
+
+        let direction = end - start;
+        let length = direction.norm();
+        if length < 1e-12 {
+            return None;
+        }
+        let normal = Vector2::new(-direction.y, direction.x) / length;
+
+        let (sin_r, cos_r) = ellipse.rotation().sin_cos();
+        let q1 = normal.dot(&Vector2::new(cos_r, sin_r));
+        let q2 = normal.dot(&Vector2::new(-sin_r, cos_r));
+        let half_width =
+            ((ellipse.radius_x() * q1).powi(2) + (ellipse.radius_y() * q2).powi(2)).sqrt();
+
+        Some((normal.dot(&(center - start)), half_width))
+    }
+}
+
+impl ConstraintLike for LineEllipseTangent {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Line(self.line.clone()),
+            PrimitiveCell::Ellipse(self.ellipse.clone()),
+        ]
+    }
+
+    fn loss_value(&self) -> f64 {
+        match self.distances() {
+            Some((distance, half_width)) => {
+                let err = distance.abs() - half_width;
+                0.5 * err * err
+            }
+            None => 0.0,
+        }
+    }
+
+    fn update_gradient(&mut self) {
+        let Some((distance, half_width)) = self.distances() else {
+            return;
+        };
+        if half_width < 1e-12 {
+            return;
+        }
+        let err = distance.abs() - half_width;
+
+        let start = self.line.borrow().start().borrow().data();
+        let end = self.line.borrow().end().borrow().data();
+        let ellipse = self.ellipse.borrow().clone();
+        let center = ellipse.center().borrow().data();
+        let rx = ellipse.radius_x();
+        let ry = ellipse.radius_y();
+        let (sin_r, cos_r) = ellipse.rotation().sin_cos();
+        let axis1 = Vector2::new(cos_r, sin_r);
+        let axis2 = Vector2::new(-sin_r, cos_r);
+
+        let direction = end - start;
+        let length = direction.norm();
+        let normal = Vector2::new(-direction.y, direction.x) / length;
+        let q1 = normal.dot(&axis1);
+        let q2 = normal.dot(&axis2);
+
+        // d normal / d direction
+        let perpendicular = Matrix2::new(0.0, -1.0, 1.0, 0.0);
+        let grad_normal_from_direction =
+            perpendicular / length - normal * direction.transpose() / (length * length);
+
+        // Signed distance of the center to the line
+        let grad_distance_from_normal = (center - start).transpose();
+        let grad_distance_from_direction = grad_distance_from_normal * grad_normal_from_direction;
+        let grad_distance_from_center = normal.transpose();
+        let grad_distance_from_start = -normal.transpose() - grad_distance_from_direction;
+        let grad_distance_from_end = grad_distance_from_direction;
+
+        // Half-width of the ellipse along the normal
+        let grad_half_width_from_normal =
+            (rx * rx * q1 * axis1.transpose() + ry * ry * q2 * axis2.transpose()) / half_width;
+        let grad_half_width_from_direction =
+            grad_half_width_from_normal * grad_normal_from_direction;
+        let grad_half_width_from_rx = rx * q1 * q1 / half_width;
+        let grad_half_width_from_ry = ry * q2 * q2 / half_width;
+        let grad_half_width_from_rotation = (rx * rx - ry * ry) * q1 * q2 / half_width;
+
+        let sign = distance.signum();
+        let grad_err_from_start = sign * grad_distance_from_start + grad_half_width_from_direction;
+        let grad_err_from_end = sign * grad_distance_from_end - grad_half_width_from_direction;
+
+        let grad_start = self.line.borrow().start_gradient();
+        let grad_end = self.line.borrow().end_gradient();
+        let grad_line = err * (grad_err_from_start * grad_start + grad_err_from_end * grad_end);
+
+        let grad_ellipse = err
+            * (sign * grad_distance_from_center * ellipse.center_gradient()
+                - grad_half_width_from_rx * ellipse.radius_x_gradient()
+                - grad_half_width_from_ry * ellipse.radius_y_gradient()
+                - grad_half_width_from_rotation * ellipse.rotation_gradient());
+
+        self.line.borrow_mut().add_to_gradient(grad_line.as_view());
+        self.ellipse
+            .borrow_mut()
+            .add_to_gradient(grad_ellipse.as_view());
+    }
+
+    fn get_type(&self) -> crate::constraints::Constraint {
+        crate::constraints::Constraint::LineEllipseTangent(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use crate::{
+        constraints::{
+            tangent::line_ellipse_tangent::LineEllipseTangent, ConstraintCell, ConstraintLike,
+        },
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_line_ellipse_tangent() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+
+        let center = sketch.add_point2(0.2, 0.1)?;
+        let ellipse = sketch.add_ellipse(center.clone(), 2.0, 1.0, 0.3)?;
+        let start = sketch.add_point2(-3.0, 2.0)?;
+        let end = sketch.add_point2(3.0, 1.5)?;
+        let line = sketch.add_line(start.clone(), end.clone())?;
+
+        let constr1 = Rc::new(RefCell::new(LineEllipseTangent::new(
+            line.clone(),
+            ellipse.clone(),
+        )));
+        sketch.add_constraint(ConstraintCell::LineEllipseTangent(constr1.clone()))?;
+
+        sketch.check_gradients(1e-6, constr1.clone(), 1e-5);
+        let solver = BFGSSolver::new();
+        solver.solve(&mut sketch)?;
+
+        println!("ellipse: {:?}", ellipse.as_ref().borrow());
+        println!("line: {:?}", line.as_ref().borrow());
+
+        assert!(constr1.borrow().loss_value() < 1e-10);
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod bspline_end_tangent;
+pub mod bspline_start_tangent;
+pub mod line_ellipse_tangent;
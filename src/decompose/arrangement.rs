@@ -0,0 +1,845 @@
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::{Vector2, Vector3};
+
+use crate::intersections::{angle_from, intersections, line_parameter};
+use crate::primitives::arc::Arc;
+use crate::primitives::bezier::Bezier;
+use crate::primitives::bspline::BSpline;
+use crate::primitives::circle::Circle;
+use crate::primitives::ellipse::Ellipse;
+use crate::primitives::elliptical_arc::EllipticalArc;
+use crate::primitives::line::Line;
+use crate::primitives::point2::Point2;
+use crate::primitives::Primitive;
+
+use super::segment::Segment;
+
+// Points closer than this are the same point
+const TOLERANCE: f64 = 1e-9;
+// Samples per turn of arcs and ellipses when their crossings are looked for on polylines
+const SAMPLES_PER_TURN: f64 = 64.0;
+// Newton steps that move a point onto a curve, or onto the crossing of two curves
+const NEWTON_STEPS: usize = 32;
+
+// The planar arrangement of the segments, circles and ellipses: everything is split where it
+// crosses or touches something else, so that faces can be walked from end point to end point.
+// Circles and ellipses that are cut at least twice become arcs, the others are returned as they
+// are. Lines, arcs and circles are intersected exactly, and where they lie on top of each other the
+// pieces are kept once. Other curves are intersected where their polylines cross, moved onto the
+// curves, so places where they only touch something or overlap it are not found.
+pub fn arrange(
+    segments: Vec<Segment>,
+    circles: Vec<Circle>,
+    ellipses: Vec<Ellipse>,
+) -> (Vec<Segment>, Vec<Circle>, Vec<Ellipse>) {
+    let curves: Vec<Curve> = segments
+        .iter()
+        .map(Curve::Segment)
+        .chain(circles.iter().map(Curve::Circle))
+        .chain(ellipses.iter().map(Curve::Ellipse))
+        .collect();
+    // Crossings at an end are snapped to it, so that the pieces meet the other segment exactly
+    let ends: Vec<Vector2<f64>> = segments
+        .iter()
+        .flat_map(|segment| [segment.get_start(), segment.get_end()])
+        .collect();
+
+    let mut cuts: Vec<Vec<Vector2<f64>>> = vec![Vec::new(); curves.len()];
+    for i in 0..curves.len() {
+        for j in i + 1..curves.len() {
+            let points = match (curves[i].primitive(), curves[j].primitive()) {
+                (Some(a), Some(b)) => {
+                    let mut points = overlap(&a, &b);
+                    points.extend(intersections(a, b));
+                    points
+                }
+                _ => crossings(&curves[i], &curves[j]),
+            };
+            for point in points {
+                let point = ends
+                    .iter()
+                    .copied()
+                    .find(|end| (end - point).norm() <= TOLERANCE)
+                    .unwrap_or(point);
+                cuts[i].push(point);
+                cuts[j].push(point);
+            }
+        }
+    }
+
+    let mut arranged: Vec<Segment> = Vec::new();
+    let mut add = |pieces: Vec<Segment>| {
+        for piece in pieces {
+            // Lines and arcs that overlap leave the same piece twice
+            if !arranged.iter().any(|other| same_piece(other, &piece)) {
+                arranged.push(piece);
+            }
+        }
+    };
+    for (segment, cuts) in segments.iter().zip(cuts.iter()) {
+        add(split(segment, cuts));
+    }
+    let (circle_cuts, ellipse_cuts) = cuts[segments.len()..].split_at(circles.len());
+    let mut uncut_circles: Vec<Circle> = Vec::new();
+    for (circle, cuts) in circles.into_iter().zip(circle_cuts.iter()) {
+        let arcs = split_circle(&circle, cuts);
+        if !arcs.is_empty() {
+            add(arcs);
+        } else if !uncut_circles
+            .iter()
+            .any(|other| same_circle(other, &circle))
+        {
+            uncut_circles.push(circle);
+        }
+    }
+    let mut uncut_ellipses = Vec::new();
+    for (ellipse, cuts) in ellipses.into_iter().zip(ellipse_cuts.iter()) {
+        let arcs = split_ellipse(&ellipse, cuts);
+        if arcs.is_empty() {
+            uncut_ellipses.push(ellipse);
+        } else {
+            add(arcs);
+        }
+    }
+    (arranged, uncut_circles, uncut_ellipses)
+}
+
+// A curve of the arrangement, with a parameter that runs along it from its start
+#[derive(Clone, Copy)]
+enum Curve<'a> {
+    Segment(&'a Segment),
+    Circle(&'a Circle),
+    Ellipse(&'a Ellipse),
+}
+
+impl Curve<'_> {
+    // The curve as a primitive that is intersected exactly, if it is one
+    fn primitive(&self) -> Option<Primitive> {
+        match self {
+            Curve::Segment(Segment::Line(line)) => Some(Primitive::Line(line.clone())),
+            Curve::Segment(Segment::Arc(arc)) => Some(Primitive::Arc(arc.clone())),
+            Curve::Circle(circle) => Some(Primitive::Circle((*circle).clone())),
+            _ => None,
+        }
+    }
+
+    // Lines and Bezier curves run from 0 to 1, arcs, circles and ellipses by the angle or
+    // parameter turned from their start, and B-splines over their domain
+    fn range(&self) -> (f64, f64) {
+        match self {
+            Curve::Segment(Segment::Line(_)) | Curve::Segment(Segment::Bezier(_)) => (0.0, 1.0),
+            Curve::Segment(Segment::Arc(arc)) => (0.0, arc.sweep_angle()),
+            Curve::Segment(Segment::EllipticalArc(arc)) => (0.0, arc.sweep_parameter()),
+            Curve::Segment(Segment::BSpline(spline)) => {
+                (spline.start_parameter(), spline.end_parameter())
+            }
+            Curve::Circle(_) | Curve::Ellipse(_) => (0.0, 2.0 * PI),
+        }
+    }
+
+    fn closed(&self) -> bool {
+        matches!(self, Curve::Circle(_) | Curve::Ellipse(_))
+    }
+
+    fn point_at(&self, t: f64) -> Vector2<f64> {
+        match self {
+            Curve::Segment(Segment::Line(line)) => {
+                let (start, end) = (line.start().borrow().data(), line.end().borrow().data());
+                start + t * (end - start)
+            }
+            Curve::Segment(Segment::Arc(arc)) => {
+                let angle = arc.start_angle() + turn(arc.clockwise()) * t;
+                arc.center().borrow().data() + arc.radius() * Vector2::new(angle.cos(), angle.sin())
+            }
+            Curve::Segment(Segment::EllipticalArc(arc)) => {
+                arc.point_at(arc.start_parameter() + turn(arc.clockwise()) * t)
+            }
+            Curve::Segment(Segment::BSpline(spline)) => spline.point_at(t),
+            Curve::Segment(Segment::Bezier(bezier)) => bezier.point_at(t),
+            Curve::Circle(circle) => {
+                circle.center().borrow().data() + circle.radius() * Vector2::new(t.cos(), t.sin())
+            }
+            Curve::Ellipse(ellipse) => ellipse.point_at(t),
+        }
+    }
+
+    fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        match self {
+            Curve::Segment(Segment::Line(line)) => {
+                line.end().borrow().data() - line.start().borrow().data()
+            }
+            Curve::Segment(Segment::Arc(arc)) => {
+                let turn = turn(arc.clockwise());
+                let angle = arc.start_angle() + turn * t;
+                turn * arc.radius() * Vector2::new(-angle.sin(), angle.cos())
+            }
+            Curve::Segment(Segment::EllipticalArc(arc)) => {
+                let turn = turn(arc.clockwise());
+                turn * arc.derivative_at(arc.start_parameter() + turn * t)
+            }
+            Curve::Segment(Segment::BSpline(spline)) => spline.derivative_at(t),
+            Curve::Segment(Segment::Bezier(bezier)) => bezier.derivative_at(t),
+            Curve::Circle(circle) => circle.radius() * Vector2::new(-t.sin(), t.cos()),
+            Curve::Ellipse(ellipse) => ellipse.derivative_at(t),
+        }
+    }
+
+    // Points along the curve with their parameters, from its start to its end
+    fn samples(&self) -> Vec<(f64, Vector2<f64>)> {
+        let (first, last) = self.range();
+        let count = match self {
+            Curve::Segment(Segment::Line(_)) => 1,
+            Curve::Segment(Segment::Bezier(_)) => 32,
+            Curve::Segment(Segment::BSpline(spline)) => {
+                16 * (spline.knots().len() - 2 * spline.degree())
+            }
+            _ => ((last - first) / (2.0 * PI) * SAMPLES_PER_TURN)
+                .ceil()
+                .max(1.0) as usize,
+        };
+        (0..=count)
+            .map(|i| {
+                let t = first + (last - first) * i as f64 / count as f64;
+                (t, self.point_at(t))
+            })
+            .collect()
+    }
+
+    // The parameter of a point on the curve
+    fn parameter(&self, point: Vector2<f64>) -> f64 {
+        match self {
+            Curve::Segment(Segment::Line(line)) => line_parameter(
+                line.start().borrow().data(),
+                line.end().borrow().data(),
+                point,
+            ),
+            Curve::Segment(Segment::Arc(arc)) => angle_from(
+                arc.center().borrow().data(),
+                arc.start_angle(),
+                arc.clockwise(),
+                point,
+            ),
+            Curve::Segment(Segment::EllipticalArc(arc)) => {
+                let t = ellipse_parameter(
+                    arc.center().borrow().data(),
+                    arc.radius_x(),
+                    arc.radius_y(),
+                    arc.rotation(),
+                    point,
+                );
+                (turn(arc.clockwise()) * (t - arc.start_parameter())).rem_euclid(2.0 * PI)
+            }
+            Curve::Circle(circle) => angle_from(circle.center().borrow().data(), 0.0, false, point),
+            Curve::Ellipse(ellipse) => ellipse_parameter(
+                ellipse.center().borrow().data(),
+                ellipse.radius_x(),
+                ellipse.radius_y(),
+                ellipse.rotation(),
+                point,
+            )
+            .rem_euclid(2.0 * PI),
+            Curve::Segment(Segment::BSpline(_)) | Curve::Segment(Segment::Bezier(_)) => {
+                self.project(point)
+            }
+        }
+    }
+
+    // The parameter of the point of the curve closest to the point, found from the closest sample
+    fn project(&self, point: Vector2<f64>) -> f64 {
+        let (first, last) = self.range();
+        let mut t = self
+            .samples()
+            .into_iter()
+            .map(|(t, sample)| (t, (sample - point).norm()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(first, |(t, _)| t);
+        for _ in 0..NEWTON_STEPS {
+            let derivative = self.derivative_at(t);
+            if derivative.norm_squared() == 0.0 {
+                break;
+            }
+            let step = (point - self.point_at(t)).dot(&derivative) / derivative.norm_squared();
+            t = (t + step).clamp(first, last);
+        }
+        t
+    }
+
+    // The parameter kept on the curve, closed curves go on around
+    fn wrap(&self, t: f64) -> f64 {
+        let (first, last) = self.range();
+        if self.closed() {
+            first + (t - first).rem_euclid(last - first)
+        } else {
+            t.clamp(first, last)
+        }
+    }
+}
+
+// The sign of the parameter of an arc going in the direction
+fn turn(clockwise: bool) -> f64 {
+    if clockwise {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+// The parameter of a point on the ellipse, as used by `point_at`
+fn ellipse_parameter(
+    center: Vector2<f64>,
+    radius_x: f64,
+    radius_y: f64,
+    rotation: f64,
+    point: Vector2<f64>,
+) -> f64 {
+    let (sin_r, cos_r) = rotation.sin_cos();
+    let offset = point - center;
+    let x = cos_r * offset.x + sin_r * offset.y;
+    let y = -sin_r * offset.x + cos_r * offset.y;
+    (y / radius_y).atan2(x / radius_x)
+}
+
+// Where the curves cross. The crossings of their polylines are moved onto both curves.
+fn crossings(a: &Curve, b: &Curve) -> Vec<Vector2<f64>> {
+    let (samples_a, samples_b) = (a.samples(), b.samples());
+    let mut points = Vec::new();
+    for edge_a in samples_a.windows(2) {
+        for edge_b in samples_b.windows(2) {
+            let (p, q) = (edge_a[1].1 - edge_a[0].1, edge_b[1].1 - edge_b[0].1);
+            let denominator = p.perp(&q);
+            if denominator.abs() <= f64::EPSILON * p.norm() * q.norm() {
+                continue;
+            }
+            let offset = edge_b[0].1 - edge_a[0].1;
+            let (u, v) = (offset.perp(&q) / denominator, offset.perp(&p) / denominator);
+            // A little slack, so that crossings where the polylines meet are not missed
+            let inside = |x: f64| (-1e-6..=1.0 + 1e-6).contains(&x);
+            if !inside(u) || !inside(v) {
+                continue;
+            }
+            let s = edge_a[0].0 + u * (edge_a[1].0 - edge_a[0].0);
+            let t = edge_b[0].0 + v * (edge_b[1].0 - edge_b[0].0);
+            points.extend(refine(a, b, s, t));
+        }
+    }
+    points
+}
+
+// The crossing of the curves near the parameters, found with Newton steps
+fn refine(a: &Curve, b: &Curve, mut s: f64, mut t: f64) -> Option<Vector2<f64>> {
+    for _ in 0..NEWTON_STEPS {
+        let difference = b.point_at(t) - a.point_at(s);
+        if difference.norm() <= f64::EPSILON {
+            break;
+        }
+        // a(s + ds) = b(t + dt) to first order
+        let (da, db) = (a.derivative_at(s), b.derivative_at(t));
+        let determinant = da.perp(&db);
+        if determinant == 0.0 {
+            return None;
+        }
+        s = a.wrap(s + difference.perp(&db) / determinant);
+        t = b.wrap(t + difference.perp(&da) / determinant);
+    }
+    let point = a.point_at(s);
+    ((b.point_at(t) - point).norm() <= TOLERANCE).then_some(point)
+}
+
+// Where two lines, or two arcs or circles, on top of each other end, as they overlap instead of
+// crossing
+fn overlap(a: &Primitive, b: &Primitive) -> Vec<Vector2<f64>> {
+    if let (Primitive::Line(a), Primitive::Line(b)) = (a, b) {
+        let ends = |line: &Line| [line.start().borrow().data(), line.end().borrow().data()];
+        let [start, end] = ends(a);
+        let direction = (end - start).normalize();
+        let [other_start, other_end] = ends(b);
+        let on_line = |point: Vector2<f64>| (point - start).perp(&direction).abs() <= TOLERANCE;
+        return if on_line(other_start) && on_line(other_end) {
+            vec![start, end, other_start, other_end]
+        } else {
+            Vec::new()
+        };
+    }
+    match (round(a), round(b)) {
+        (Some((center, radius, ends)), Some((other_center, other_radius, other_ends)))
+            if (center - other_center).norm() <= TOLERANCE
+                && (radius - other_radius).abs() <= TOLERANCE =>
+        {
+            ends.into_iter().chain(other_ends).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+// The center, radius and ends of an arc or circle
+fn round(primitive: &Primitive) -> Option<(Vector2<f64>, f64, Vec<Vector2<f64>>)> {
+    match primitive {
+        Primitive::Arc(arc) => Some((
+            arc.center().borrow().data(),
+            arc.radius(),
+            vec![arc.start_point(), arc.end_point()],
+        )),
+        Primitive::Circle(circle) => {
+            Some((circle.center().borrow().data(), circle.radius(), Vec::new()))
+        }
+        _ => None,
+    }
+}
+
+// The pieces of the segment between the cuts that lie inside it, from its start to its end
+fn split(segment: &Segment, cuts: &[Vector2<f64>]) -> Vec<Segment> {
+    let cuts = sorted_inside(&Curve::Segment(segment), cuts);
+    match segment {
+        Segment::Line(line) => {
+            let mut points = vec![line.start()];
+            points.extend(cuts.iter().map(|(_, cut)| point(*cut)));
+            points.push(line.end());
+            points
+                .windows(2)
+                .map(|ends| Segment::Line(Line::new(ends[0].clone(), ends[1].clone())))
+                .collect()
+        }
+        Segment::Arc(arc) => {
+            let center = arc.center().borrow().data();
+            let mut angles = vec![arc.start_angle()];
+            angles.extend(cuts.iter().map(|(_, cut)| angle_of(cut - center)));
+            angles.push(arc.end_angle());
+            angles
+                .windows(2)
+                .map(|ends| {
+                    Segment::Arc(Arc::new(
+                        arc.center(),
+                        arc.radius(),
+                        arc.clockwise(),
+                        ends[0],
+                        ends[1],
+                    ))
+                })
+                .collect()
+        }
+        Segment::EllipticalArc(arc) => {
+            let turn = turn(arc.clockwise());
+            let mut parameters = vec![arc.start_parameter()];
+            parameters.extend(cuts.iter().map(|(t, _)| arc.start_parameter() + turn * t));
+            parameters.push(arc.end_parameter());
+            parameters
+                .windows(2)
+                .map(|ends| {
+                    Segment::EllipticalArc(EllipticalArc::new(
+                        arc.center(),
+                        arc.radius_x(),
+                        arc.radius_y(),
+                        arc.rotation(),
+                        arc.clockwise(),
+                        ends[0],
+                        ends[1],
+                    ))
+                })
+                .collect()
+        }
+        Segment::Bezier(bezier) => {
+            // Each cut is made in what is left after the one before, with the parameter scaled
+            let (mut rest, mut done) = (bezier.clone(), 0.0);
+            let mut pieces = Vec::new();
+            for (t, cut) in cuts {
+                let (piece, remainder) = split_bezier(&rest, (t - done) / (1.0 - done), cut);
+                pieces.push(Segment::Bezier(piece));
+                (rest, done) = (remainder, t);
+            }
+            pieces.push(Segment::Bezier(rest));
+            pieces
+        }
+        Segment::BSpline(spline) => {
+            let mut rest = spline.clone();
+            let mut pieces = Vec::new();
+            for (t, cut) in cuts {
+                if let Some((piece, remainder)) = split_bspline(&rest, t, cut) {
+                    pieces.push(Segment::BSpline(piece));
+                    rest = remainder;
+                }
+            }
+            pieces.push(Segment::BSpline(rest));
+            pieces
+        }
+    }
+}
+
+// The curve in two at the parameter, with de Casteljau's construction
+fn split_bezier(bezier: &Bezier, t: f64, cut: Vector2<f64>) -> (Bezier, Bezier) {
+    let lerp = |a: Vector2<f64>, b: Vector2<f64>| a + t * (b - a);
+    let [p0, p1, p2, p3] = [
+        bezier.start(),
+        bezier.control1(),
+        bezier.control2(),
+        bezier.end(),
+    ]
+    .map(|point| point.borrow().data());
+    let (p01, p12, p23) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+    let (p012, p123) = (lerp(p01, p12), lerp(p12, p23));
+    let middle = point(cut);
+    (
+        Bezier::new(bezier.start(), point(p01), point(p012), middle.clone()),
+        Bezier::new(middle, point(p123), point(p23), bezier.end()),
+    )
+}
+
+// The spline in two at the parameter. The knot is inserted until the curve passes through a
+// control point there, which both halves then share.
+fn split_bspline(spline: &BSpline, t: f64, cut: Vector2<f64>) -> Option<(BSpline, BSpline)> {
+    let degree = spline.degree();
+    let mut knots = spline.knots();
+    let weights = spline.weights();
+    // Homogeneous coordinates, so that rational splines are split the same way
+    let mut points: Vec<Vector3<f64>> = spline
+        .control_points()
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let weight = weights.as_ref().map_or(1.0, |weights| weights[i]);
+            let point = point.borrow().data();
+            Vector3::new(weight * point.x, weight * point.y, weight)
+        })
+        .collect();
+    while knots.iter().filter(|knot| **knot == t).count() < degree {
+        // The knot span [knots[k], knots[k + 1]) that holds the parameter
+        let k = (degree..points.len()).rev().find(|k| knots[*k] <= t)?;
+        let inserted = (0..=points.len())
+            .map(|i| {
+                if i + degree <= k {
+                    points[i]
+                } else if i > k {
+                    points[i - 1]
+                } else {
+                    let a = (t - knots[i]) / (knots[i + degree] - knots[i]);
+                    (1.0 - a) * points[i - 1] + a * points[i]
+                }
+            })
+            .collect();
+        knots.insert(k + 1, t);
+        points = inserted;
+    }
+
+    let last = knots.iter().rposition(|knot| *knot == t)?;
+    let shared = (last + 1).checked_sub(degree + 1)?;
+    let mut control_points: Vec<Rc<RefCell<Point2>>> = points
+        .iter()
+        .map(|point| self::point(point.xy() / point.z))
+        .collect();
+    control_points[shared] = self::point(cut);
+    let point_weights: Option<Vec<f64>> = weights.map(|_| points.iter().map(|p| p.z).collect());
+
+    let mut first_knots = knots[..=last].to_vec();
+    first_knots.push(t);
+    let mut second_knots = vec![t];
+    second_knots.extend_from_slice(&knots[shared + 1..]);
+    let first = BSpline::new(
+        control_points[..=shared].to_vec(),
+        degree,
+        first_knots,
+        point_weights
+            .as_ref()
+            .map(|weights| weights[..=shared].to_vec()),
+    );
+    let second = BSpline::new(
+        control_points[shared..].to_vec(),
+        degree,
+        second_knots,
+        point_weights.map(|weights| weights[shared..].to_vec()),
+    );
+    Some((first.ok()?, second.ok()?))
+}
+
+// The counterclockwise arcs of the circle between the cuts, none if there are less than two
+fn split_circle(circle: &Circle, cuts: &[Vector2<f64>]) -> Vec<Segment> {
+    let cuts = sorted_inside(&Curve::Circle(circle), cuts);
+    if cuts.len() < 2 {
+        return Vec::new();
+    }
+    (0..cuts.len())
+        .map(|i| {
+            Segment::Arc(Arc::new(
+                circle.center(),
+                circle.radius(),
+                false,
+                cuts[i].0,
+                cuts[(i + 1) % cuts.len()].0,
+            ))
+        })
+        .collect()
+}
+
+// The counterclockwise arcs of the ellipse between the cuts, none if there are less than two
+fn split_ellipse(ellipse: &Ellipse, cuts: &[Vector2<f64>]) -> Vec<Segment> {
+    let cuts = sorted_inside(&Curve::Ellipse(ellipse), cuts);
+    if cuts.len() < 2 {
+        return Vec::new();
+    }
+    (0..cuts.len())
+        .map(|i| {
+            Segment::EllipticalArc(EllipticalArc::new(
+                ellipse.center(),
+                ellipse.radius_x(),
+                ellipse.radius_y(),
+                ellipse.rotation(),
+                false,
+                cuts[i].0,
+                cuts[(i + 1) % cuts.len()].0,
+            ))
+        })
+        .collect()
+}
+
+// The parameters of the cuts inside the curve with the cuts, in order and without doubles. Cuts at
+// the ends of a segment are left out, the segment ends there anyway.
+fn sorted_inside(curve: &Curve, cuts: &[Vector2<f64>]) -> Vec<(f64, Vector2<f64>)> {
+    let (first, last) = curve.range();
+    let ends = match curve {
+        Curve::Segment(segment) => vec![segment.get_start(), segment.get_end()],
+        _ => Vec::new(),
+    };
+    let mut cuts: Vec<(f64, Vector2<f64>)> = cuts
+        .iter()
+        .filter(|cut| ends.iter().all(|end| (end - *cut).norm() > TOLERANCE))
+        .map(|cut| (curve.parameter(*cut), *cut))
+        .filter(|(t, _)| *t >= first && *t <= last)
+        .collect();
+    cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    cuts.dedup_by(|a, b| (a.1 - b.1).norm() <= TOLERANCE);
+    // Closed curves come back to their start
+    if let [(_, start), .., (_, end)] = cuts.as_slice() {
+        if curve.closed() && (end - start).norm() <= TOLERANCE {
+            cuts.pop();
+        }
+    }
+    cuts
+}
+
+// Whether the pieces run between the same ends the same way, in either direction
+fn same_piece(a: &Segment, b: &Segment) -> bool {
+    let near = |p: Vector2<f64>, q: Vector2<f64>| (p - q).norm() <= TOLERANCE;
+    let middle = |segment: &Segment| {
+        let curve = Curve::Segment(segment);
+        let (first, last) = curve.range();
+        curve.point_at(0.5 * (first + last))
+    };
+    let (a_start, a_end, b_start, b_end) = (a.get_start(), a.get_end(), b.get_start(), b.get_end());
+    ((near(a_start, b_start) && near(a_end, b_end))
+        || (near(a_start, b_end) && near(a_end, b_start)))
+        && near(middle(a), middle(b))
+}
+
+fn same_circle(a: &Circle, b: &Circle) -> bool {
+    (a.center().borrow().data() - b.center().borrow().data()).norm() <= TOLERANCE
+        && (a.radius() - b.radius()).abs() <= TOLERANCE
+}
+
+fn point(point: Vector2<f64>) -> Rc<RefCell<Point2>> {
+    Rc::new(RefCell::new(Point2::new(point.x, point.y)))
+}
+
+fn angle_of(offset: Vector2<f64>) -> f64 {
+    offset.y.atan2(offset.x)
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::decompose::find_rings;
+    use crate::decompose::segment::Segment;
+    use crate::primitives::arc::Arc;
+    use crate::primitives::bezier::Bezier;
+    use crate::primitives::bspline::BSpline;
+    use crate::primitives::circle::Circle;
+    use crate::primitives::ellipse::Ellipse;
+    use crate::primitives::line::Line;
+    use crate::primitives::point2::Point2;
+    use crate::sketch::Sketch;
+
+    use super::{arrange, split_bezier, split_bspline};
+
+    fn point(x: f64, y: f64) -> Rc<RefCell<Point2>> {
+        Rc::new(RefCell::new(Point2::new(x, y)))
+    }
+
+    #[test]
+    fn test_arrange() {
+        // A line through a circle, and a second one that ends on the first
+        let across = Segment::Line(Line::new(point(-2.0, 0.0), point(2.0, 0.0)));
+        let down = Segment::Line(Line::new(point(0.0, -2.0), point(0.0, 0.0)));
+        let circle = Circle::new(point(0.0, 0.0), 1.0);
+        let (segments, circles, _) = arrange(vec![across, down], vec![circle], Vec::new());
+        assert!(circles.is_empty());
+        let lines = segments
+            .iter()
+            .filter(|s| matches!(s, Segment::Line(_)))
+            .count();
+        let arcs = segments
+            .iter()
+            .filter(|s| matches!(s, Segment::Arc(_)))
+            .count();
+        // Four pieces of the first line, two of the second, three arcs
+        assert_eq!((lines, arcs), (6, 3));
+        for segment in segments.iter() {
+            let connected = segments.iter().filter(|other| {
+                other.ends_at(segment.get_start()) || other.ends_at(segment.get_end())
+            });
+            assert!(connected.count() >= 1);
+        }
+
+        // Nothing crosses a circle on its own
+        let circle = Circle::new(point(5.0, 0.0), 1.0);
+        let (segments, circles, _) = arrange(Vec::new(), vec![circle], Vec::new());
+        assert!(segments.is_empty());
+        assert_eq!(circles.len(), 1);
+    }
+
+    #[test]
+    fn test_find_rings_circle_split() {
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(0.0, 0.0).unwrap();
+        sketch.add_circle(center, 1.0).unwrap();
+        let start = sketch.add_point2(-2.0, 0.0).unwrap();
+        let end = sketch.add_point2(2.0, 0.0).unwrap();
+        sketch.add_line(start, end).unwrap();
+
+        // The two halves of the disk
+        let (rings, _) = find_rings(&sketch);
+        assert_eq!(rings.len(), 2);
+        for ring in rings {
+            assert!((ring.signed_area() - PI / 2.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_arrange_curves() {
+        // A line through a turned ellipse and across a Bezier arch
+        let across = Segment::Line(Line::new(point(-3.0, 0.5), point(6.0, 0.5)));
+        let ellipse = Ellipse::new(point(0.0, 0.0), 2.0, 1.0, 0.3);
+        let arch = Segment::Bezier(Bezier::new(
+            point(3.0, 0.0),
+            point(3.0, 2.0),
+            point(5.0, 2.0),
+            point(5.0, 0.0),
+        ));
+        let (segments, circles, ellipses) = arrange(vec![across, arch], Vec::new(), vec![ellipse]);
+        assert!(circles.is_empty() && ellipses.is_empty());
+        let count = |kind: fn(&Segment) -> bool| segments.iter().filter(|s| kind(s)).count();
+        assert_eq!(count(|s| matches!(s, Segment::Line(_))), 5);
+        assert_eq!(count(|s| matches!(s, Segment::EllipticalArc(_))), 2);
+        assert_eq!(count(|s| matches!(s, Segment::Bezier(_))), 3);
+        // Away from the ends of the line and the arch, two pieces of the line and two of a curve
+        // meet at each cut
+        let outer =
+            [(-3.0, 0.5), (6.0, 0.5), (3.0, 0.0), (5.0, 0.0)].map(|(x, y)| Vector2::new(x, y));
+        for segment in segments.iter() {
+            for end in [segment.get_start(), segment.get_end()] {
+                if outer.contains(&end) {
+                    continue;
+                }
+                let meets = segments
+                    .iter()
+                    .filter(|other| other.ends_at(end) || other.reverse().ends_at(end))
+                    .count();
+                assert!((end.y - 0.5).abs() < 1e-9);
+                assert_eq!(meets, 4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrange_overlapping_arcs() {
+        // Two arcs on the same circle that overlap, and the circle itself twice
+        let center = point(0.0, 0.0);
+        let first = Segment::Arc(Arc::new(center.clone(), 1.0, false, 0.0, PI));
+        let second = Segment::Arc(Arc::new(center.clone(), 1.0, true, -PI / 2.0, PI / 2.0));
+        let circle = Circle::new(center.clone(), 1.0);
+        let (segments, circles, _) = arrange(
+            vec![first, second],
+            vec![circle.clone(), circle],
+            Vec::new(),
+        );
+        // The quarters of the circle, each once
+        assert!(circles.is_empty());
+        assert_eq!(segments.len(), 4);
+
+        let (segments, circles, _) = arrange(
+            Vec::new(),
+            vec![Circle::new(center.clone(), 1.0), Circle::new(center, 1.0)],
+            Vec::new(),
+        );
+        assert!(segments.is_empty());
+        assert_eq!(circles.len(), 1);
+    }
+
+    #[test]
+    fn test_split_curves() {
+        let near = |a: Vector2<f64>, b: Vector2<f64>| (a - b).norm() < 1e-9;
+        let bezier = Bezier::new(
+            point(0.0, 0.0),
+            point(1.0, 2.0),
+            point(3.0, -1.0),
+            point(4.0, 1.0),
+        );
+        let (first, second) = split_bezier(&bezier, 0.3, bezier.point_at(0.3));
+        for u in [0.0, 0.25, 0.5, 1.0] {
+            assert!(near(first.point_at(u), bezier.point_at(0.3 * u)));
+            assert!(near(second.point_at(u), bezier.point_at(0.3 + 0.7 * u)));
+        }
+
+        // A rational spline, cut between knots and at one
+        let points = [(0.0, 0.0), (1.0, 2.0), (2.0, -1.0), (3.0, 2.0), (4.0, 0.0)]
+            .map(|(x, y)| point(x, y))
+            .to_vec();
+        let spline = BSpline::new(
+            points,
+            2,
+            vec![0.0, 0.0, 0.0, 0.25, 0.5, 1.0, 1.0, 1.0],
+            Some(vec![1.0, 2.0, 0.5, 1.0, 1.5]),
+        )
+        .unwrap();
+        for t in [0.37, 0.5] {
+            let (first, second) = split_bspline(&spline, t, spline.point_at(t)).unwrap();
+            assert_eq!(first.end_parameter(), t);
+            assert_eq!(second.start_parameter(), t);
+            for u in [0.0, 0.1, 0.2, 0.3, t] {
+                assert!(near(first.point_at(u), spline.point_at(u)));
+            }
+            for u in [t, 0.6, 0.8, 1.0] {
+                assert!(near(second.point_at(u), spline.point_at(u)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_rings_curves_split() {
+        // The halves of an ellipse, and an arch of a spline over a line
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(0.0, 0.0).unwrap();
+        sketch.add_ellipse(center, 2.0, 1.0, 0.0).unwrap();
+        let start = sketch.add_point2(-3.0, 0.0).unwrap();
+        let end = sketch.add_point2(9.0, 0.0).unwrap();
+        sketch.add_line(start, end).unwrap();
+        let points = [(4.0, -1.0), (5.0, 2.0), (7.0, 2.0), (8.0, -1.0)]
+            .map(|(x, y)| sketch.add_point2(x, y).unwrap())
+            .to_vec();
+        let knots = vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+        sketch.add_bspline(points, 3, knots, None).unwrap();
+
+        let (rings, _) = find_rings(&sketch);
+        assert_eq!(rings.len(), 3);
+        let halves = rings
+            .iter()
+            .filter(|ring| (ring.signed_area() - PI).abs() < 0.05)
+            .count();
+        assert_eq!(halves, 2);
+    }
+}
@@ -4,23 +4,31 @@ use std::f64::consts::{PI, TAU};
 use geo::Polygon;
 use geo::{Contains as _, InteriorPoint as _};
 
+use crate::primitives::circle::Circle;
+use crate::primitives::ellipse::Ellipse;
 use crate::primitives::PrimitiveCell;
 use crate::sketch::Sketch;
 
+use self::arrangement::arrange;
 use self::face::Face;
 use self::ring::Ring;
 use self::segment::Segment;
 
+pub mod arrangement;
 pub mod face;
 pub mod ring;
 pub mod segment;
 
 pub fn decompose_sketch(sketch: &Sketch) -> Vec<Face> {
-    // A primitive is now ether a Circle, Line, or Arc. Points can be ignored.
-    // Now chain all consecutive primitives that are connected into a ring.
-    // - Two primitives are connected if the end of the first primitive is the start of the second primitive.
-    // For now, assume there is only one ring in the sketch, such that the construction of the faces is simple.
-    // For the future, we will need a more complex algorithm that can handle multiple rings. But for the MVP, this is sufficient.
+    // A primitive is a curve: a line, arc, circle, ellipse, elliptical arc, Bezier or B-spline.
+    // Points can be ignored.
+    // First split all primitives where they cross or touch each other, into a planar arrangement.
+    // - Lines, arcs and circles are split exactly, also where they lie on top of each other.
+    // - The other curves are only split where they cross something: where they just touch it or
+    //   run along it, no faces are found.
+    // Now chain all consecutive pieces that are connected into a ring.
+    // - Two pieces are connected if the end of the first piece is the start of the second piece.
+    // Walking the smallest rings gives the faces, rings inside others become their holes.
 
     find_faces(sketch).0
 }
@@ -110,17 +118,43 @@ pub fn find_faces(sketch: &Sketch) -> (Vec<Face>, Vec<Segment>) {
 }
 
 pub fn find_rings(sketch: &Sketch) -> (Vec<Ring>, Vec<Segment>) {
-    let init_segments: Vec<Segment> = sketch
+    let segments: Vec<Segment> = sketch
         .primitives()
         .values()
+        .filter(|p| !p.construction())
         .filter_map(|p| match p {
             // We don't consider circles - we'll just add them to the rings directly (right?)
             PrimitiveCell::Line(l) => Some(Segment::Line(l.borrow().clone())),
             PrimitiveCell::Arc(a) => Some(Segment::Arc(a.borrow().clone())),
+            PrimitiveCell::EllipticalArc(a) => Some(Segment::EllipticalArc(a.borrow().clone())),
+            PrimitiveCell::BSpline(s) => Some(Segment::BSpline(s.borrow().clone())),
+            PrimitiveCell::Bezier(b) => Some(Segment::Bezier(b.borrow().clone())),
+            _ => None,
+        })
+        .collect();
+    let circles: Vec<Circle> = sketch
+        .primitives()
+        .values()
+        .filter(|p| !p.construction())
+        .filter_map(|p| match p {
+            PrimitiveCell::Circle(c) => Some(c.borrow().clone()),
+            _ => None,
+        })
+        .collect();
+    let ellipses: Vec<Ellipse> = sketch
+        .primitives()
+        .values()
+        .filter(|p| !p.construction())
+        .filter_map(|p| match p {
+            PrimitiveCell::Ellipse(e) => Some(e.borrow().clone()),
             _ => None,
         })
         .collect();
 
+    // Segments that cross or touch each other are split there first, so that rings can be walked
+    // along the pieces from end point to end point
+    let (init_segments, circles, ellipses) = arrange(segments, circles, ellipses);
+
     let segments_reversed = init_segments.iter().map(|s| s.reverse());
 
     // We consider all given segments and their reversed counterparts
@@ -146,7 +180,7 @@ pub fn find_rings(sketch: &Sketch) -> (Vec<Ring>, Vec<Segment>) {
         for _i in 1..all_segments.len() {
             new_ring_indices.push((next_segment_index, next_segment));
 
-            if next_segment.get_end() == start_point {
+            if next_segment.ends_at(start_point) {
                 new_rings.push(new_ring_indices.iter().map(|x| x.1).collect());
                 used_indices.extend(new_ring_indices.iter().map(|x| x.0));
                 break;
@@ -178,16 +212,9 @@ pub fn find_rings(sketch: &Sketch) -> (Vec<Ring>, Vec<Segment>) {
         all_rings.push(Ring::Segments(ring_segments));
     }
 
-    // Circles are rings too
-    let circles = sketch
-        .primitives()
-        .values()
-        .filter_map(|s| match s {
-            PrimitiveCell::Circle(c) => Some(Ring::Circle(c.borrow().clone())),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-    all_rings.extend(circles);
+    // Circles and ellipses that nothing crosses are rings too
+    all_rings.extend(circles.into_iter().map(Ring::Circle));
+    all_rings.extend(ellipses.into_iter().map(Ring::Ellipse));
 
     // Need to implement signed_area
     all_rings.sort_by(|a, b| a.signed_area().total_cmp(&b.signed_area()));
@@ -334,7 +361,7 @@ mod tests {
         let point_e = Rc::new(RefCell::new(Point2::new(2.0, 0.0)));
         let point_f = Rc::new(RefCell::new(Point2::new(3.0, 0.0)));
 
-        for pt in vec![&point_a, &point_b, &point_c, &point_d, &point_e, &point_f] {
+        for pt in [&point_a, &point_b, &point_c, &point_d, &point_e, &point_f] {
             sketch
                 .add_primitive(PrimitiveCell::Point2(pt.clone()))
                 .unwrap();
@@ -395,4 +422,144 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_find_rings_bspline() {
+        let mut sketch = Sketch::new();
+        let point_a = sketch.add_point2(0.0, 0.0).unwrap();
+        let point_b = sketch.add_point2(2.0, 0.0).unwrap();
+        let point_c = sketch.add_point2(2.0, 2.0).unwrap();
+        let point_d = sketch.add_point2(0.0, 2.0).unwrap();
+
+        sketch.add_line(point_a.clone(), point_b.clone()).unwrap();
+        sketch
+            .add_bspline(
+                vec![point_b, point_c, point_d, point_a],
+                3,
+                vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0],
+                None,
+            )
+            .unwrap();
+
+        let (rings, unused_segments) = find_rings(&sketch);
+        assert_eq!(rings.len(), 1);
+        assert!(unused_segments.is_empty());
+
+        // The cubic encloses 3/5 of the area of its control polygon, the tessellation slightly less
+        assert!((rings[0].signed_area() - 2.4).abs() < 0.05);
+        assert!(rings[0].as_polygon().exterior().0.len() > 10);
+    }
+
+    #[test]
+    fn test_find_rings_line_arc_bezier() {
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(0.0, 0.0).unwrap();
+        let point_a = sketch.add_point2(PI.cos(), PI.sin()).unwrap();
+        let point_b = sketch.add_point2(1.0, 0.0).unwrap();
+        let point_c = sketch.add_point2(1.0, -1.0).unwrap();
+        let control1 = sketch.add_point2(0.5, -2.0).unwrap();
+        let control2 = sketch.add_point2(-0.5, -2.0).unwrap();
+        let point_d = sketch.add_point2(-1.0, -1.0).unwrap();
+
+        // Rings are only closed by exactly matching end points
+        sketch.add_arc(center, 1.0, false, 0.0, PI).unwrap();
+        sketch.add_line(point_a.clone(), point_d.clone()).unwrap();
+        sketch
+            .add_bezier(point_d, control2, control1, point_c.clone())
+            .unwrap();
+        sketch.add_line(point_c, point_b).unwrap();
+
+        let (rings, unused_segments) = find_rings(&sketch);
+        assert_eq!(rings.len(), 1);
+        assert!(unused_segments.is_empty());
+    }
+
+    #[test]
+    fn test_decompose_elliptical_arc() {
+        for clockwise in [false, true] {
+            let mut sketch = Sketch::new();
+            let center = sketch.add_point2(0.0, 0.0).unwrap();
+            let corners = [(2.0, 0.0), (3.0, 2.0), (-3.0, 2.0), (-2.0, 0.0)]
+                .map(|(x, y)| sketch.add_point2(x, y).unwrap());
+            // Half an ellipse under a trapezoid, which leaves its right end steeper than the arc
+            // does and its left end less steep
+            let (start, end) = if clockwise { (PI, 0.0) } else { (0.0, PI) };
+            sketch
+                .add_elliptical_arc(center, 2.0, 1.0, 0.0, clockwise, start, end)
+                .unwrap();
+            sketch
+                .add_line(corners[3].clone(), corners[0].clone())
+                .unwrap();
+            for i in 0..3 {
+                sketch
+                    .add_line(corners[i].clone(), corners[i + 1].clone())
+                    .unwrap();
+            }
+
+            let faces = decompose_sketch(&sketch);
+            let mut areas = faces
+                .iter()
+                .map(|face| face.exterior.signed_area())
+                .collect::<Vec<_>>();
+            areas.sort_by(f64::total_cmp);
+            assert_eq!(areas.len(), 2);
+            for (area, expected) in areas.iter().zip([PI, 10.0 - PI]) {
+                assert!((area - expected).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompose_overlapping_rectangles() {
+        let mut sketch = Sketch::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 1.0)] {
+            let corners = [(x, y), (x + 2.0, y), (x + 2.0, y + 2.0), (x, y + 2.0)]
+                .map(|(x, y)| sketch.add_point2(x, y).unwrap());
+            for i in 0..4 {
+                sketch
+                    .add_line(corners[i].clone(), corners[(i + 1) % 4].clone())
+                    .unwrap();
+            }
+        }
+        // A line from an edge into the overlap ends in the middle of another edge
+        let start = sketch.add_point2(1.5, 1.0).unwrap();
+        let end = sketch.add_point2(1.5, 2.0).unwrap();
+        sketch.add_line(start, end).unwrap();
+
+        // Both L-shaped parts and the two halves of the overlap
+        let faces = decompose_sketch(&sketch);
+        let mut areas = faces
+            .iter()
+            .map(|face| face.exterior.signed_area())
+            .collect::<Vec<_>>();
+        areas.sort_by(f64::total_cmp);
+        assert_eq!(areas.len(), 4);
+        for (area, expected) in areas.iter().zip([0.5, 0.5, 3.0, 3.0]) {
+            assert!((area - expected).abs() < 1e-12);
+        }
+        assert!(faces.iter().all(|face| face.holes.is_empty()));
+    }
+
+    #[test]
+    fn test_find_rings_ignores_construction_geometry() {
+        let mut sketch = Sketch::new();
+        let point_a = sketch.add_point2(0.0, 0.0).unwrap();
+        let point_b = sketch.add_point2(1.0, 0.0).unwrap();
+        let point_c = sketch.add_point2(1.0, 1.0).unwrap();
+
+        sketch.add_line(point_a.clone(), point_b.clone()).unwrap();
+        sketch.add_line(point_b.clone(), point_c.clone()).unwrap();
+        let diagonal = sketch.add_line(point_c.clone(), point_a.clone()).unwrap();
+        let circle = sketch.add_circle(point_a, 0.5).unwrap();
+
+        // The circle around a corner crosses the triangle, which leaves three regions
+        assert_eq!(find_rings(&sketch).0.len(), 3);
+
+        diagonal.borrow_mut().set_construction(true);
+        circle.borrow_mut().set_construction(true);
+        let (rings, unused_segments) = find_rings(&sketch);
+        assert!(rings.is_empty());
+        assert_eq!(unused_segments.len(), 2);
+        assert!(decompose_sketch(&sketch).is_empty());
+    }
 }
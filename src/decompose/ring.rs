@@ -4,12 +4,14 @@ use geo::{LineString, Polygon};
 use serde::{Deserialize, Serialize};
 
 use crate::primitives::circle::Circle;
+use crate::primitives::ellipse::Ellipse;
 
 use super::segment::Segment;
 
 #[derive(Debug, Clone, PartialOrd, Serialize, Deserialize)]
 pub enum Ring {
     Circle(Circle),
+    Ellipse(Ellipse),
     Segments(Vec<Segment>),
 }
 
@@ -17,11 +19,17 @@ impl Ring {
     pub fn signed_area(&self) -> f64 {
         match self {
             Ring::Circle(circle) => circle.radius().powi(2) * std::f64::consts::PI,
+            Ring::Ellipse(ellipse) => {
+                (ellipse.radius_x() * ellipse.radius_y()).abs() * std::f64::consts::PI
+            }
             Ring::Segments(segments) => {
+                let points = segments
+                    .iter()
+                    .flat_map(|s| s.tessellate())
+                    .collect::<Vec<_>>();
                 let mut area = 0.0;
-                for segment in segments {
-                    let start = segment.get_start();
-                    let end = segment.get_end();
+                for (i, start) in points.iter().enumerate() {
+                    let end = points[(i + 1) % points.len()];
                     area += start.x * end.y - end.x * start.y;
                 }
                 area / 2.0
@@ -69,16 +77,25 @@ impl Ring {
 
                 Polygon::new(LineString::from(b), vec![])
             }
+            Ring::Ellipse(ellipse) => {
+                let num_pts = 36;
+                let b = (0..num_pts)
+                    .map(|i| {
+                        let point = ellipse.point_at(i as f64 / num_pts as f64 * TAU);
+                        (point.x, point.y)
+                    })
+                    .collect::<Vec<(f64, f64)>>();
+
+                Polygon::new(LineString::from(b), vec![])
+            }
             Ring::Segments(segments) => {
                 // we only ever push the start point. Imagine what happens for a closed
                 // square--the final closing segment is inferred.
                 // points.push(segments.last().unwrap().get_end());
                 let points = segments
                     .iter()
-                    .map(|s| {
-                        let start = s.get_start();
-                        (start.x, start.y)
-                    })
+                    .flat_map(|s| s.tessellate())
+                    .map(|point| (point.x, point.y))
                     .collect::<Vec<(f64, f64)>>();
 
                 Polygon::new(LineString::from(points), vec![])
@@ -91,6 +108,7 @@ impl PartialEq for Ring {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Ring::Circle(circle_a), Ring::Circle(circle_b)) => circle_a == circle_b,
+            (Ring::Ellipse(ellipse_a), Ring::Ellipse(ellipse_b)) => ellipse_a == ellipse_b,
             (Ring::Segments(segments_a), Ring::Segments(segments_b)) => {
                 segments_a.len() == segments_b.len()
                     && segments_a
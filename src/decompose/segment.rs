@@ -1,12 +1,22 @@
+use std::f64::consts::PI;
+
 use nalgebra::Vector2;
 use serde::{Deserialize, Serialize};
 
-use crate::primitives::{arc::Arc, line::Line};
+use crate::primitives::{
+    arc::Arc, bezier::Bezier, bspline::BSpline, elliptical_arc::EllipticalArc, line::Line,
+};
+
+// Segment ends closer than this are the same point
+const ENDPOINT_TOLERANCE: f64 = 1e-9;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Segment {
     Line(Line),
     Arc(Arc),
+    EllipticalArc(EllipticalArc),
+    BSpline(BSpline),
+    Bezier(Bezier),
 }
 
 impl Segment {
@@ -14,6 +24,9 @@ impl Segment {
         match self {
             Segment::Line(line) => line.start().borrow().data(),
             Segment::Arc(arc) => arc.start_point(),
+            Segment::EllipticalArc(arc) => arc.start_point(),
+            Segment::BSpline(spline) => spline.start_point(),
+            Segment::Bezier(bezier) => bezier.start().borrow().data(),
         }
     }
 
@@ -21,6 +34,9 @@ impl Segment {
         match self {
             Segment::Line(line) => line.end().borrow().data(),
             Segment::Arc(arc) => arc.end_point(),
+            Segment::EllipticalArc(arc) => arc.end_point(),
+            Segment::BSpline(spline) => spline.end_point(),
+            Segment::Bezier(bezier) => bezier.end().borrow().data(),
         }
     }
 
@@ -30,19 +46,71 @@ impl Segment {
                 Segment::Line(Line::new(line.end().clone(), line.start().clone()))
             }
             Segment::Arc(arc) => Segment::Arc(arc.reverse()),
+            Segment::EllipticalArc(arc) => Segment::EllipticalArc(arc.reverse()),
+            Segment::BSpline(spline) => Segment::BSpline(spline.reverse()),
+            Segment::Bezier(bezier) => Segment::Bezier(bezier.reverse()),
+        }
+    }
+
+    // Points along the segment from its start, without the end point, which is the start of the
+    // next segment in a ring. Arcs and free-form curves are sampled, the other segments only yield
+    // their start point.
+    pub fn tessellate(&self) -> Vec<Vector2<f64>> {
+        match self {
+            Segment::Arc(arc) => {
+                // At least one point every 1/32 of a turn
+                let sweep = arc.sweep_angle();
+                let num_points = (sweep / (PI / 16.0)).ceil().max(1.0) as usize;
+                let step = if arc.clockwise() { -sweep } else { sweep } / num_points as f64;
+                let center = arc.center().borrow().data();
+                (0..num_points)
+                    .map(|i| {
+                        let angle = arc.start_angle() + i as f64 * step;
+                        center + arc.radius() * Vector2::new(angle.cos(), angle.sin())
+                    })
+                    .collect()
+            }
+            Segment::EllipticalArc(arc) => {
+                // Sampled like arcs, by the parameter
+                let sweep = arc.sweep_parameter();
+                let num_points = (sweep / (PI / 16.0)).ceil().max(1.0) as usize;
+                let step = if arc.clockwise() { -sweep } else { sweep } / num_points as f64;
+                (0..num_points)
+                    .map(|i| arc.point_at(arc.start_parameter() + i as f64 * step))
+                    .collect()
+            }
+            Segment::BSpline(spline) => {
+                let num_points = 8 * (spline.knots().len() - 2 * spline.degree()) + 1;
+                let mut points = spline.tessellate(num_points);
+                points.pop();
+                points
+            }
+            Segment::Bezier(bezier) => {
+                let mut points = bezier.tessellate(17);
+                points.pop();
+                points
+            }
+            _ => vec![self.get_start()],
         }
     }
 
+    // Whether the segment ends at the point. Ends that are computed, like those of arcs or of the
+    // pieces of split segments, may be off by rounding.
+    pub fn ends_at(&self, point: Vector2<f64>) -> bool {
+        (self.get_end() - point).norm() <= ENDPOINT_TOLERANCE
+    }
+
     pub fn continues(&self, prior_segment: &Segment) -> bool {
         // determines if this segment continues the prior segment
-        prior_segment.get_end() == self.get_start()
+        prior_segment.ends_at(self.get_start())
     }
 
     pub fn connects(&self, prior_segment: &Segment) -> bool {
         // determines if this segment connects to the prior segment
-        prior_segment.get_end() == self.get_start() || prior_segment.get_end() == self.get_end()
+        prior_segment.ends_at(self.get_start()) || prior_segment.ends_at(self.get_end())
     }
 
+    // The direction the segment leaves its start in
     pub fn start_angle(&self) -> f64 {
         match self {
             Segment::Line(line) => {
@@ -50,14 +118,39 @@ impl Segment {
                 let end = line.end().borrow().data();
                 (end.y - start.y).atan2(end.x - start.x)
             }
-            Segment::Arc(arc) => arc.start_angle(),
+            Segment::Arc(arc) => (arc.start_angle() + arc_turn(arc)).rem_euclid(2.0 * PI),
+            Segment::EllipticalArc(arc) => {
+                let d = elliptical_tangent(arc, arc.start_parameter());
+                d.y.atan2(d.x)
+            }
+            Segment::BSpline(spline) => {
+                let d = spline.derivative_at(spline.start_parameter());
+                d.y.atan2(d.x)
+            }
+            Segment::Bezier(bezier) => {
+                let d = bezier.derivative_at(0.0);
+                d.y.atan2(d.x)
+            }
         }
     }
 
+    // The direction the segment arrives at its end in
     pub fn end_angle(&self) -> f64 {
         match self {
             Segment::Line(_line) => self.start_angle(),
-            Segment::Arc(arc) => arc.end_angle(),
+            Segment::Arc(arc) => (arc.end_angle() + arc_turn(arc)).rem_euclid(2.0 * PI),
+            Segment::EllipticalArc(arc) => {
+                let d = elliptical_tangent(arc, arc.end_parameter());
+                d.y.atan2(d.x)
+            }
+            Segment::BSpline(spline) => {
+                let d = spline.derivative_at(spline.end_parameter());
+                d.y.atan2(d.x)
+            }
+            Segment::Bezier(bezier) => {
+                let d = bezier.derivative_at(1.0);
+                d.y.atan2(d.x)
+            }
         }
     }
 
@@ -69,3 +162,21 @@ impl Segment {
         self == other || self.reverse_equals(other)
     }
 }
+
+// The direction of travel along the elliptical arc at the parameter
+fn elliptical_tangent(arc: &EllipticalArc, t: f64) -> Vector2<f64> {
+    if arc.clockwise() {
+        -arc.derivative_at(t)
+    } else {
+        arc.derivative_at(t)
+    }
+}
+
+// The angle from the radius of an arc to its direction of travel
+fn arc_turn(arc: &Arc) -> f64 {
+    if arc.clockwise() {
+        -PI / 2.0
+    } else {
+        PI / 2.0
+    }
+}
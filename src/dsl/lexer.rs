@@ -0,0 +1,158 @@
+use crate::error::ISOTopeError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    Number(f64),
+    LParen,
+    RParen,
+    Comma,
+    Equals,
+    GreaterEquals,
+    LessEquals,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+}
+
+// Splits a single line of the sketch language into tokens. Everything after a '#' is a comment.
+pub(crate) fn tokenize_line(line: &str, line_number: usize) -> Result<Vec<Token>, ISOTopeError> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '#' => break,
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Equals);
+                i += 1;
+            }
+            '>' | '<' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(if c == '>' {
+                    Token::GreaterEquals
+                } else {
+                    Token::LessEquals
+                });
+                i += 2;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            '*' | '/' | '^' => {
+                tokens.push(match c {
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    _ => Token::Caret,
+                });
+                i += 1;
+            }
+            // A sign belongs to the number unless it follows an operand, like in `width - 8`
+            '-' | '+' if !starts_number(&tokens, chars.get(i + 1)) => {
+                tokens.push(if c == '-' { Token::Minus } else { Token::Plus });
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() {
+                    let c = chars[i];
+                    let is_exponent_sign =
+                        (c == '-' || c == '+') && matches!(chars[i - 1], 'e' | 'E');
+                    if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || is_exponent_sign {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text.parse::<f64>().map_err(|_| {
+                    ISOTopeError::ParseError(line_number, format!("invalid number '{}'", text))
+                })?;
+                tokens.push(Token::Number(value));
+            }
+            c => {
+                return Err(ISOTopeError::ParseError(
+                    line_number,
+                    format!("unexpected character '{}'", c),
+                ))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn starts_number(tokens: &[Token], next: Option<&char>) -> bool {
+    let follows_operand = matches!(
+        tokens.last(),
+        Some(Token::Ident(_) | Token::Number(_) | Token::RParen)
+    );
+    let digit_follows = next.is_some_and(|c| c.is_ascii_digit() || *c == '.');
+    digit_follows && !follows_operand
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_line() {
+        let tokens = tokenize_line("distance(a, b) = -2.5e-1 # comment", 1).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("distance".to_string()),
+                Token::LParen,
+                Token::Ident("a".to_string()),
+                Token::Comma,
+                Token::Ident("b".to_string()),
+                Token::RParen,
+                Token::Equals,
+                Token::Number(-0.25),
+            ]
+        );
+
+        assert_eq!(
+            tokenize_line("radius(c) <= 5 # comment", 2).unwrap()[4..],
+            [Token::LessEquals, Token::Number(5.0)]
+        );
+
+        assert_eq!(
+            tokenize_line("w-1 * -x ^ 2", 3).unwrap(),
+            [
+                Token::Ident("w".to_string()),
+                Token::Minus,
+                Token::Number(1.0),
+                Token::Star,
+                Token::Minus,
+                Token::Ident("x".to_string()),
+                Token::Caret,
+                Token::Number(2.0),
+            ]
+        );
+
+        assert!(tokenize_line("a = point(1.0; 2.0)", 3).is_err());
+        assert!(tokenize_line("radius(c) > 5", 4).is_err());
+    }
+}
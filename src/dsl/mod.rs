@@ -0,0 +1,525 @@
+use std::collections::BTreeMap;
+
+use crate::error::ISOTopeError;
+use crate::primitives::PrimitiveCell;
+use crate::sketch::Sketch;
+use crate::units::LengthUnit;
+
+pub(crate) use self::parser::parse_expression;
+use self::parser::SketchBuilder;
+pub(crate) use self::printer::primitive_name;
+use self::printer::SketchPrinter;
+
+mod lexer;
+mod parser;
+mod printer;
+
+// A small text format for sketches. Every line is either a primitive definition or a constraint:
+//
+//   a = point(0.0, 0.0)
+//   b = point(1.0, 0.0)
+//   l = line(a, b)
+//   horizontal(l)
+//   distance(a, b) = 2.0
+//   distance(a, b) >= 1.0
+//
+// Primitives have to be defined before they are referenced. Everything after a '#' is a comment.
+// Definitions like `m = construction line(a, b)` mark construction geometry.
+// Printed sketches name primitives after their id, like `p0` or `l3`, and parsing keeps those ids.
+//
+// Values can be expressions over named parameters, which keep driving the dimension afterwards:
+//
+//   parameter width = 40.0
+//   parameter hole_d = width / 8.0
+//   distance(a, b) = width - hole_d
+//
+// Dimensions prefixed with `driven` don't constrain the sketch, their value is measured instead:
+//
+//   driven distance(a, b) = 2.0
+//
+// Lengths are in millimeters unless a `unit in` statement comes first. Angles are in radians.
+// Values can name their unit, and are converted to the unit of the sketch:
+//
+//   distance(a, b) = 1.0 in
+//   angle(a, b, c) = 45.0 deg
+//
+// Patterns list the number of copies, the seed and then the copies. The spacing of linear
+// patterns and the step angle of circular patterns are their value:
+//
+//   linear_pattern(2, l, m, n) = (10.0, 0.0)
+//   circular_pattern(a, 5, c1, c2, c3, c4, c5, c6) = 60.0 deg
+
+pub fn parse(source: &str) -> Result<Sketch, ISOTopeError> {
+    parse_with_names(source).map(|(sketch, _)| sketch)
+}
+
+// Like `parse`, but also returns the primitives by the names used in the source.
+pub fn parse_with_names(
+    source: &str,
+) -> Result<(Sketch, BTreeMap<String, PrimitiveCell>), ISOTopeError> {
+    let mut builder = SketchBuilder::default();
+    builder.parse(source)?;
+    // A freshly read sketch has nothing to undo
+    builder.sketch.clear_history();
+    Ok((builder.sketch, builder.names))
+}
+
+pub fn print(sketch: &Sketch) -> Result<String, ISOTopeError> {
+    SketchPrinter::new(sketch).print()
+}
+
+// Parses a sketch and converts it to the given length unit, whatever unit the source is in.
+pub fn parse_in_unit(source: &str, unit: LengthUnit) -> Result<Sketch, ISOTopeError> {
+    let mut sketch = parse(source)?;
+    sketch.convert_length_unit(unit)?;
+    sketch.clear_history();
+    Ok(sketch)
+}
+
+// Prints a sketch converted to the given length unit, the sketch itself is left untouched.
+pub fn print_in_unit(sketch: &Sketch, unit: LengthUnit) -> Result<String, ISOTopeError> {
+    // The text format is lossless, so parsing it back gives an independent copy to convert
+    print(&parse_in_unit(&print(sketch)?, unit)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{
+            coincident::{
+                arc_end_point_coincident::ArcEndPointCoincident,
+                elliptical_arc_start_point_coincident::EllipticalArcStartPointCoincident,
+                point_on_ellipse::PointOnEllipse,
+            },
+            continuity::{g2_continuity::G2Continuity, ContinuityCurve, CurveEnd},
+            inequality::Bound,
+            length::LengthCurve,
+            lines::equal_length::EqualLength,
+            midpoint::MidpointCurve,
+            pattern::PatternKind,
+            radius::RadialCurve,
+            tangent::{
+                bspline_end_tangent::BSplineEndTangent, line_ellipse_tangent::LineEllipseTangent,
+            },
+            ConstraintCell,
+        },
+        examples::test_rectangle_rotated::RotatedRectangleDemo,
+        parameters::expression::Expression,
+        primitives::PrimitiveCell,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+        units::{Angle, Length},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<(), Box<dyn Error>> {
+        let rectangle = RotatedRectangleDemo::new()?;
+        let mut sketch = rectangle.sketch;
+        let center = sketch.add_point2(0.1, -0.3)?;
+        let arc = sketch.add_arc(center.clone(), 0.7, true, 0.1, 2.0)?;
+        let circle = sketch.add_circle(center.clone(), 1.0 / 3.0)?;
+        circle.borrow_mut().set_construction(true);
+        sketch.add_constraint(ConstraintCell::ArcEndPointCoincident(Rc::new(
+            RefCell::new(ArcEndPointCoincident::new(
+                arc.clone(),
+                rectangle.point_c.clone(),
+            )),
+        )))?;
+        let line = sketch.add_line(rectangle.point_a.clone(), rectangle.point_c.clone())?;
+        let ellipse = sketch.add_ellipse(center.clone(), 2.0, 0.5, 0.25)?;
+        let elliptical_arc =
+            sketch.add_elliptical_arc(center.clone(), 1.5, 0.75, -0.5, true, 0.3, 1.9)?;
+        sketch.add_constraint(ConstraintCell::PointOnEllipse(Rc::new(RefCell::new(
+            PointOnEllipse::new(ellipse.clone(), rectangle.point_b.clone()),
+        ))))?;
+        sketch.add_constraint(ConstraintCell::EllipticalArcStartPointCoincident(Rc::new(
+            RefCell::new(EllipticalArcStartPointCoincident::new(
+                elliptical_arc,
+                rectangle.point_d.clone(),
+            )),
+        )))?;
+        let spline = sketch.add_bspline(
+            vec![
+                rectangle.point_a.clone(),
+                rectangle.point_reference.clone(),
+                rectangle.point_c.clone(),
+            ],
+            2,
+            vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            Some(vec![1.0, 0.5, 1.0]),
+        )?;
+        sketch.add_bspline(
+            vec![
+                rectangle.point_b.clone(),
+                center.clone(),
+                rectangle.point_d.clone(),
+            ],
+            1,
+            vec![0.0, 0.0, 0.5, 1.0, 1.0],
+            None,
+        )?;
+        let control = sketch.add_point2(0.25, 2.0)?;
+        let bezier = sketch.add_bezier(
+            rectangle.point_d.clone(),
+            control.clone(),
+            control,
+            rectangle.point_a.clone(),
+        )?;
+        sketch.add_constraint(ConstraintCell::G2Continuity(Rc::new(RefCell::new(
+            G2Continuity::new(
+                ContinuityCurve::Bezier(bezier, CurveEnd::End),
+                ContinuityCurve::Line(line.clone(), CurveEnd::Start),
+            ),
+        ))))?;
+        sketch.add_constraint(ConstraintCell::BSplineEndTangent(Rc::new(RefCell::new(
+            BSplineEndTangent::new(spline, line.clone()),
+        ))))?;
+        sketch.add_constraint(ConstraintCell::LineEllipseTangent(Rc::new(RefCell::new(
+            LineEllipseTangent::new(line.clone(), ellipse),
+        ))))?;
+        sketch.constrain_diameter(RadialCurve::Circle(circle.clone()), 0.75)?;
+        sketch.constrain_equal_radius(
+            RadialCurve::Arc(arc.clone()),
+            RadialCurve::Circle(circle.clone()),
+        )?;
+        sketch.constrain_radius_difference(
+            RadialCurve::Circle(circle.clone()),
+            RadialCurve::Arc(arc.clone()),
+            0.25,
+        )?;
+        let diagonal = sketch.add_line(rectangle.point_b.clone(), rectangle.point_d.clone())?;
+        sketch.constrain_angle_between_lines(
+            line.clone(),
+            diagonal.clone(),
+            Angle::radians(-1.25),
+            true,
+        )?;
+        sketch.constrain_distance_point_line(center.clone(), line.clone(), -0.5, true)?;
+        sketch.constrain_distance_line_circle(line.clone(), circle.clone(), 0.1, false)?;
+        sketch.constrain_collinear_points(vec![
+            rectangle.point_a.clone(),
+            center.clone(),
+            rectangle.point_c.clone(),
+        ])?;
+        sketch.constrain_collinear_lines(line.clone(), diagonal.clone())?;
+        sketch.constrain_equal_lengths(vec![
+            LengthCurve::Line(diagonal.clone()),
+            LengthCurve::Arc(arc.clone()),
+            LengthCurve::Circle(circle.clone()),
+        ])?;
+        sketch.constrain_arc_sweep_angle(arc.clone(), Angle::radians(1.5))?;
+        sketch.constrain_arc_length(arc.clone(), 2.0)?;
+        sketch.constrain_euclidean_distance_bound(
+            rectangle.point_a.clone(),
+            center.clone(),
+            0.25,
+            Bound::Min,
+        )?;
+        sketch.constrain_radius_bound(RadialCurve::Arc(arc.clone()), 3.0, Bound::Max)?;
+        sketch.constrain_midpoint(MidpointCurve::Arc(arc.clone()), center.clone())?;
+        let measured = sketch.constrain_distance_point_circle(
+            rectangle.point_c.clone(),
+            circle.clone(),
+            1.0,
+            false,
+        )?;
+        sketch.set_driven(&ConstraintCell::PointCircleDistance(measured), true)?;
+        sketch.add_pattern(
+            vec![PrimitiveCell::Line(diagonal.clone())],
+            PatternKind::Linear {
+                spacing: Vector2::new(0.5, -0.25),
+            },
+            2,
+        )?;
+        sketch.add_pattern(
+            vec![PrimitiveCell::Circle(circle.clone())],
+            PatternKind::Circular {
+                center: rectangle.point_a.clone(),
+                step_angle: 0.75,
+            },
+            3,
+        )?;
+        // Two lines of equal length print differently depending on the constraint used
+        sketch.constrain_equal_lengths(vec![
+            LengthCurve::Line(line.clone()),
+            LengthCurve::Line(diagonal.clone()),
+        ])?;
+        sketch.add_constraint(ConstraintCell::EqualLength(Rc::new(RefCell::new(
+            EqualLength::new(line.clone(), diagonal.clone()),
+        ))))?;
+        sketch.constrain_symmetric(rectangle.point_b.clone(), rectangle.point_d.clone(), line)?;
+
+        let text = print(&sketch)?;
+        let parsed = parse(&text)?;
+
+        assert_eq!(parsed.get_num_primitives(), sketch.get_num_primitives());
+        assert_eq!(parsed.get_num_constraints(), sketch.get_num_constraints());
+        assert_eq!(parsed.get_data(), sketch.get_data());
+        assert_eq!(print(&parsed)?, text);
+        assert!(parsed
+            .constraints()
+            .iter()
+            .zip(sketch.constraints().iter())
+            .all(|(a, b)| std::mem::discriminant(a) == std::mem::discriminant(b)));
+        assert!(text.contains("construction circle("));
+        assert!(text.contains("driven distance("));
+        assert_eq!(
+            parsed.get_loss_per_constraint(),
+            sketch.get_loss_per_constraint()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip_keeps_ids() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let c = sketch.add_point2(1.0, 1.0)?;
+        sketch.add_line(a.clone(), c.clone())?;
+        let b = sketch
+            .get_primitive_id(&PrimitiveCell::Point2(b))
+            .ok_or("b should be in the sketch")?;
+        sketch.delete_primitive(b)?;
+
+        let text = print(&sketch)?;
+        assert_eq!(
+            text,
+            "p0 = point(0.0, 0.0)\np2 = point(1.0, 1.0)\nl3 = line(p0, p2)\n"
+        );
+        let parsed = parse(&text)?;
+        assert_eq!(print(&parsed)?, text);
+
+        // Names that don't follow the printer still get the next free id
+        let parsed = parse("p5 = point(0.0, 0.0)\nq = point(1.0, 0.0)\np1 = point(2.0, 0.0)")?;
+        assert_eq!(
+            parsed.primitives().keys().copied().collect::<Vec<_>>(),
+            [5, 6, 7]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_and_solve() -> Result<(), Box<dyn Error>> {
+        let (mut sketch, names) = parse_with_names(
+            "
+            # A rectangle with one corner in the origin
+            a = point(0.0, 0.1)
+            b = point(0.9, 0.0)
+            c = point(1.1, 0.6)
+            d = point(0.0, 0.5)
+            bottom = line(a, b)
+            right = line(b, c)
+            top = line(c, d)
+            left = line(d, a)
+
+            fix(a) = (0.0, 0.0)
+            horizontal(bottom)
+            vertical(right)
+            horizontal(top)
+            vertical(left)
+            distance(a, b) = 2.0
+            vertical_distance(a, d) = 1.0
+            ",
+        )?;
+
+        assert_eq!(sketch.get_num_primitives(), 8);
+        assert_eq!(sketch.get_num_constraints(), 7);
+
+        BFGSSolver::new().solve(&mut sketch)?;
+
+        let PrimitiveCell::Point2(c) = &names["c"] else {
+            return Err("c should be a point".into());
+        };
+        assert!((c.borrow().x() - 2.0).abs() < 1e-5);
+        assert!((c.borrow().y() - 1.0).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameters() -> Result<(), Box<dyn Error>> {
+        let source = "
+            parameter width = 4.0
+            parameter height = width / 2.0
+            a = point(0.0, 0.0)
+            b = point(1.0, 0.0)
+            c = point(1.0, 1.0)
+            fix(a) = (-width / 2.0, 0.0)
+            horizontal_distance(a, b) = width
+            vertical_distance(b, c) = height + 0.5
+            ";
+        let (mut sketch, names) = parse_with_names(source)?;
+        assert_eq!(sketch.dimension_bindings().len(), 3);
+
+        sketch.set_parameter("width", Expression::parse("6")?)?;
+        BFGSSolver::new().solve(&mut sketch)?;
+
+        let (PrimitiveCell::Point2(a), PrimitiveCell::Point2(b), PrimitiveCell::Point2(c)) =
+            (&names["a"], &names["b"], &names["c"])
+        else {
+            return Err("a, b and c should be points".into());
+        };
+        assert!((a.borrow().x() + 3.0).abs() < 1e-5);
+        assert!((b.borrow().x() - a.borrow().x() - 6.0).abs() < 1e-5);
+        assert!((c.borrow().y() - b.borrow().y() - 3.5).abs() < 1e-5);
+
+        let text = print(&sketch)?;
+        assert!(text.starts_with("parameter width = 6.0\nparameter height = width / 2.0\n"));
+        assert!(text.contains("fix(p0) = (-width / 2.0, 0.0)"));
+        assert_eq!(print(&parse(&text)?)?, text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_parameters() -> Result<(), Box<dyn Error>> {
+        let source = "
+            parameter pitch = 2.0
+            parameter step = 30.0
+            a = point(0.0, 0.0)
+            b = point(2.0, 0.0)
+            c = point(4.0, 0.0)
+            d = point(1.0, 0.0)
+            e = point(1.0, 1.0)
+            linear_pattern(2, a, b, c) = (pitch, 0.0)
+            circular_pattern(a, 1, d, e) = step deg
+            ";
+        let mut sketch = parse(source)?;
+        assert_eq!(sketch.dimension_bindings().len(), 2);
+
+        let text = print(&sketch)?;
+        assert!(text.contains("linear_pattern(2, p0, p1, p2) = (pitch, 0.0)"));
+        assert!(text.contains("circular_pattern(p0, 1, p3, p4) = step * 0.017453292519943295\n"));
+        assert_eq!(print(&parse(&text)?)?, text);
+
+        // Copies keep following the parameters
+        let ids: Vec<u64> = sketch.primitives().keys().copied().collect();
+        sketch.duplicate(&ids)?;
+        assert_eq!(sketch.dimension_bindings().len(), 4);
+        sketch.set_parameter("pitch", Expression::parse("3")?)?;
+        let spacings = sketch
+            .constraints()
+            .iter()
+            .filter(|constraint| matches!(constraint, ConstraintCell::Pattern(_)))
+            .map(ConstraintCell::dimensions)
+            .collect::<Vec<_>>();
+        assert_eq!(spacings.len(), 4);
+        assert_eq!(spacings.iter().filter(|d| d[..] == [3.0, 0.0]).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_units() -> Result<(), Box<dyn Error>> {
+        let source = "
+            unit in
+            parameter gap = 12.7
+            a = point(0.0, 0.0)
+            b = point(1.0, 0.0)
+            c = point(0.0, 1.0)
+            distance(a, b) = 25.4 mm
+            vertical_distance(a, c) = gap mm
+            angle(b, c, a) = 90.0 deg
+            ";
+        let sketch = parse(source)?;
+        assert_eq!(sketch.length_unit(), LengthUnit::Inch);
+
+        let dimensions: Vec<Vec<f64>> = sketch
+            .constraints()
+            .iter()
+            .map(ConstraintCell::dimensions)
+            .collect();
+        assert!((dimensions[0][0] - 1.0).abs() < 1e-12);
+        assert!((dimensions[1][0] - 0.5).abs() < 1e-12);
+        assert!((dimensions[2][0] - 0.5 * std::f64::consts::PI).abs() < 1e-12);
+
+        let text = print(&sketch)?;
+        assert!(text.starts_with("unit in\nparameter gap = 12.7\n"));
+        assert_eq!(print(&parse(&text)?)?, text);
+
+        // Exporting in millimeters scales the lengths but leaves the sketch and the angles alone
+        let millimeters = parse(&print_in_unit(&sketch, LengthUnit::Millimeter)?)?;
+        assert_eq!(millimeters.length_unit(), LengthUnit::Millimeter);
+        assert_eq!(sketch.length_unit(), LengthUnit::Inch);
+        let dimensions: Vec<Vec<f64>> = millimeters
+            .constraints()
+            .iter()
+            .map(ConstraintCell::dimensions)
+            .collect();
+        assert!((dimensions[0][0] - 25.4).abs() < 1e-12);
+        assert!((dimensions[1][0] - 12.7).abs() < 1e-12);
+        assert!((dimensions[2][0] - 0.5 * std::f64::consts::PI).abs() < 1e-12);
+        assert!((millimeters.length(Length::inches(1.0)) - 25.4).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let expect_error_on_line = |source: &str, expected_line: usize| match parse(source) {
+            Err(ISOTopeError::ParseError(line, _)) => assert_eq!(line, expected_line),
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+
+        expect_error_on_line("a = point(0.0, 0.0)\nl = line(a, b)", 2);
+        expect_error_on_line("a = point(0.0, 0.0)\na = point(1.0, 0.0)", 2);
+        expect_error_on_line("a = point(0.0)", 1);
+        expect_error_on_line("a = point(0.0, 0.0)\nhorizontal(a)", 2);
+        expect_error_on_line("a = point(0.0, 0.0)\n\nfix(a) = 1.0", 3);
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\ndistance(a, b)",
+            3,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\nl = line(a, b)\nhorizontal(l) >= 1.0",
+            4,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\nlinear_pattern(2, a, b) = (1.0, 0.0)",
+            3,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\nl = line(a, b)\nm = line(b, a)\nlinear_pattern(1, l, m) = (1.0, 0.0)",
+            5,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\nc = point(0.0, 1.0)\nl = line(a, b)\nm = line(b, c)\nn = line(c, a)\nequal_length(l, m, n)",
+            7,
+        );
+        expect_error_on_line("parameter a = b", 1);
+        expect_error_on_line("parameter a = 1.0\nparameter b = a\nparameter a = b", 3);
+        expect_error_on_line("parameter a = sqrt(1.0, 2.0)", 1);
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\ndistance(a, b) = w",
+            3,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\nl = line(a, b)\ndriven horizontal(l)",
+            4,
+        );
+        expect_error_on_line(
+            "parameter w = 1.0\na = point(0.0, 0.0)\nb = point(1.0, 0.0)\ndriven distance(a, b) = w",
+            4,
+        );
+        expect_error_on_line("a = polygon(0.0, 0.0)", 1);
+        expect_error_on_line("a = construction point(0.0, 0.0)", 1);
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\ns = bspline(1, a, b, 0.0, 1.0)",
+            3,
+        );
+        expect_error_on_line("unit parsec", 1);
+        expect_error_on_line("a = point(0.0, 0.0)\nunit in", 2);
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\ndistance(a, b) = 1.0 deg",
+            3,
+        );
+        expect_error_on_line(
+            "a = point(0.0, 0.0)\nb = point(1.0, 0.0)\ndistance(a, b) = 1.0 furlong",
+            3,
+        );
+    }
+}
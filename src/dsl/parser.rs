@@ -0,0 +1,1401 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::Vector2;
+
+use crate::constraints::angle_between_points::AngleBetweenPoints;
+use crate::constraints::arcs::arc_length::ArcLength;
+use crate::constraints::arcs::arc_sweep_angle::ArcSweepAngle;
+use crate::constraints::coincident::arc_end_point_coincident::ArcEndPointCoincident;
+use crate::constraints::coincident::arc_start_point_coincident::ArcStartPointCoincident;
+use crate::constraints::coincident::bspline_end_point_coincident::BSplineEndPointCoincident;
+use crate::constraints::coincident::bspline_start_point_coincident::BSplineStartPointCoincident;
+use crate::constraints::coincident::elliptical_arc_end_point_coincident::EllipticalArcEndPointCoincident;
+use crate::constraints::coincident::elliptical_arc_start_point_coincident::EllipticalArcStartPointCoincident;
+use crate::constraints::coincident::point_on_ellipse::PointOnEllipse;
+use crate::constraints::continuity::g1_continuity::G1Continuity;
+use crate::constraints::continuity::g2_continuity::G2Continuity;
+use crate::constraints::continuity::{ContinuityCurve, CurveEnd};
+use crate::constraints::distance::euclidian_distance_between_points::EuclidianDistanceBetweenPoints;
+use crate::constraints::distance::horizontal_distance_between_points::HorizontalDistanceBetweenPoints;
+use crate::constraints::distance::line_circle_distance::LineCircleDistance;
+use crate::constraints::distance::line_line_distance::LineLineDistance;
+use crate::constraints::distance::point_circle_distance::PointCircleDistance;
+use crate::constraints::distance::point_line_distance::PointLineDistance;
+use crate::constraints::distance::vertical_distance_between_points::VerticalDistanceBetweenPoints;
+use crate::constraints::fix_point::FixPoint;
+use crate::constraints::inequality::angle_bound::AngleBound;
+use crate::constraints::inequality::euclidean_distance_bound::EuclideanDistanceBound;
+use crate::constraints::inequality::horizontal_distance_bound::HorizontalDistanceBound;
+use crate::constraints::inequality::radius_bound::RadiusBound;
+use crate::constraints::inequality::vertical_distance_bound::VerticalDistanceBound;
+use crate::constraints::inequality::Bound;
+use crate::constraints::length::equal_lengths::EqualLengths;
+use crate::constraints::length::LengthCurve;
+use crate::constraints::lines::angle_between_lines::AngleBetweenLines;
+use crate::constraints::lines::collinear_lines::CollinearLines;
+use crate::constraints::lines::collinear_points::CollinearPoints;
+use crate::constraints::lines::equal_length::EqualLength;
+use crate::constraints::lines::horizontal_line::HorizontalLine;
+use crate::constraints::lines::parallel_lines::ParallelLines;
+use crate::constraints::lines::perpendicular_lines::PerpendicularLines;
+use crate::constraints::lines::vertical_line::VerticalLine;
+use crate::constraints::midpoint::{Midpoint, MidpointCurve};
+use crate::constraints::pattern::{Pattern, PatternKind};
+use crate::constraints::radius::diameter_dimension::DiameterDimension;
+use crate::constraints::radius::equal_radius::EqualRadius;
+use crate::constraints::radius::radius_difference::RadiusDifference;
+use crate::constraints::radius::radius_dimension::RadiusDimension;
+use crate::constraints::radius::RadialCurve;
+use crate::constraints::symmetric::symmetric_arcs::SymmetricArcs;
+use crate::constraints::symmetric::symmetric_lines::SymmetricLines;
+use crate::constraints::symmetric::symmetric_points::Symmetric;
+use crate::constraints::tangent::bspline_end_tangent::BSplineEndTangent;
+use crate::constraints::tangent::bspline_start_tangent::BSplineStartTangent;
+use crate::constraints::tangent::line_ellipse_tangent::LineEllipseTangent;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::parameters::expression::{Expression, Function};
+use crate::primitives::arc::Arc;
+use crate::primitives::bezier::Bezier;
+use crate::primitives::bspline::BSpline;
+use crate::primitives::circle::Circle;
+use crate::primitives::ellipse::Ellipse;
+use crate::primitives::elliptical_arc::EllipticalArc;
+use crate::primitives::line::Line;
+use crate::primitives::point2::Point2;
+use crate::primitives::PrimitiveCell;
+use crate::sketch::Sketch;
+use crate::units::{Angle, AngleUnit, LengthUnit, Unit};
+
+use super::lexer::{tokenize_line, Token};
+use super::printer::primitive_name;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Name(String),
+    Number(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Scalar(Expression),
+    Vector(Expression, Expression),
+}
+
+impl Value {
+    fn expressions(&self) -> Vec<Expression> {
+        match self {
+            Value::Scalar(value) => vec![value.clone()],
+            Value::Vector(x, y) => vec![x.clone(), y.clone()],
+        }
+    }
+}
+
+// A single statement is either a primitive definition `name = kind(args)`, a parameter definition
+// `parameter name = value`, the length unit `unit mm` or a constraint `kind(args)` with an
+// optional value `kind(args) = value`. Inequalities use `>=` or `<=` instead of `=`. Values are
+// expressions that can use the parameters defined before and may end with a unit like `in` or
+// `deg`. Dimensions marked as `driven` only measure the geometry.
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    line_number: usize,
+    target: Option<String>,
+    construction: bool,
+    kind: String,
+    args: Vec<Arg>,
+    value: Option<Value>,
+    bound: Option<Bound>,
+    unit: Option<Unit>,
+    driven: bool,
+}
+
+struct TokenStream {
+    tokens: Vec<Token>,
+    position: usize,
+    line_number: usize,
+}
+
+impl TokenStream {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> ISOTopeError {
+        ISOTopeError::ParseError(self.line_number, message.into())
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ISOTopeError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(self.error(format!("expected {:?}, found {:?}", expected, token))),
+            None => Err(self.error(format!("expected {:?}, found end of line", expected))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ISOTopeError> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            Some(token) => Err(self.error(format!("expected a name, found {:?}", token))),
+            None => Err(self.error("expected a name, found end of line")),
+        }
+    }
+
+    fn args(&mut self) -> Result<Vec<Arg>, ISOTopeError> {
+        self.expect(Token::LParen)?;
+        let mut args = vec![];
+        if self.peek() == Some(&Token::RParen) {
+            self.next();
+            return Ok(args);
+        }
+        loop {
+            match self.next() {
+                Some(Token::Ident(name)) => args.push(Arg::Name(name)),
+                Some(Token::Number(value)) => args.push(Arg::Number(value)),
+                Some(token) => {
+                    return Err(self.error(format!("unexpected {:?} in argument list", token)))
+                }
+                None => return Err(self.error("unterminated argument list")),
+            }
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => break,
+                Some(token) => {
+                    return Err(self.error(format!("expected ',' or ')', found {:?}", token)))
+                }
+                None => return Err(self.error("unterminated argument list")),
+            }
+        }
+        Ok(args)
+    }
+
+    fn value(&mut self) -> Result<Value, ISOTopeError> {
+        // A position `(x, y)`, or a scalar expression that may start with parentheses
+        let start = self.position;
+        if self.peek() == Some(&Token::LParen) {
+            self.next();
+            let x = self.expression()?;
+            if self.peek() == Some(&Token::Comma) {
+                self.next();
+                let y = self.expression()?;
+                self.expect(Token::RParen)?;
+                return Ok(Value::Vector(x, y));
+            }
+            self.position = start;
+        }
+        Ok(Value::Scalar(self.expression()?))
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expression, ISOTopeError> {
+        let mut expression = self.term()?;
+        loop {
+            expression = match self.peek() {
+                Some(Token::Plus) => {
+                    self.next();
+                    Expression::Add(Box::new(expression), Box::new(self.term()?))
+                }
+                Some(Token::Minus) => {
+                    self.next();
+                    Expression::Subtract(Box::new(expression), Box::new(self.term()?))
+                }
+                _ => return Ok(expression),
+            };
+        }
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Expression, ISOTopeError> {
+        let mut expression = self.unary()?;
+        loop {
+            expression = match self.peek() {
+                Some(Token::Star) => {
+                    self.next();
+                    Expression::Multiply(Box::new(expression), Box::new(self.unary()?))
+                }
+                Some(Token::Slash) => {
+                    self.next();
+                    Expression::Divide(Box::new(expression), Box::new(self.unary()?))
+                }
+                _ => return Ok(expression),
+            };
+        }
+    }
+
+    // unary := ('-' | '+') unary | atom ('^' unary)?
+    fn unary(&mut self) -> Result<Expression, ISOTopeError> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.next();
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Plus) => {
+                self.next();
+                self.unary()
+            }
+            _ => {
+                let base = self.atom()?;
+                if self.peek() == Some(&Token::Caret) {
+                    self.next();
+                    return Ok(Expression::Power(Box::new(base), Box::new(self.unary()?)));
+                }
+                Ok(base)
+            }
+        }
+    }
+
+    // atom := number | name | function '(' expression (',' expression)* ')' | '(' expression ')'
+    fn atom(&mut self) -> Result<Expression, ISOTopeError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| self.error(format!("unknown function '{}'", name)))?;
+                self.next();
+                let mut args = vec![self.expression()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    args.push(self.expression()?);
+                }
+                self.expect(Token::RParen)?;
+                if args.len() != function.arity() {
+                    return Err(self.error(format!(
+                        "'{}' expects {} arguments, found {}",
+                        name,
+                        function.arity(),
+                        args.len()
+                    )));
+                }
+                Ok(Expression::Call(function, args))
+            }
+            Some(Token::Ident(name)) => Ok(Expression::Parameter(name)),
+            Some(Token::LParen) => {
+                let expression = self.expression()?;
+                self.expect(Token::RParen)?;
+                Ok(expression)
+            }
+            Some(token) => Err(self.error(format!("expected a value, found {:?}", token))),
+            None => Err(self.error("expected a value, found end of line")),
+        }
+    }
+}
+
+// Parses a standalone expression like `width / 8`
+pub(crate) fn parse_expression(source: &str) -> Result<Expression, ISOTopeError> {
+    let mut stream = TokenStream {
+        tokens: tokenize_line(source, 1)?,
+        position: 0,
+        line_number: 1,
+    };
+    let expression = stream.expression()?;
+    if let Some(token) = stream.next() {
+        return Err(stream.error(format!("unexpected trailing {:?}", token)));
+    }
+    Ok(expression)
+}
+
+fn parse_statement(
+    tokens: Vec<Token>,
+    line_number: usize,
+) -> Result<Option<Statement>, ISOTopeError> {
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let mut stream = TokenStream {
+        tokens,
+        position: 0,
+        line_number,
+    };
+
+    let first = stream.ident()?;
+    if first == "parameter" && matches!(stream.peek(), Some(Token::Ident(_))) {
+        let name = stream.ident()?;
+        stream.expect(Token::Equals)?;
+        let value = stream.expression()?;
+        if let Some(token) = stream.next() {
+            return Err(stream.error(format!("unexpected trailing {:?}", token)));
+        }
+        return Ok(Some(Statement {
+            line_number,
+            target: Some(name),
+            construction: false,
+            kind: first,
+            args: vec![],
+            value: Some(Value::Scalar(value)),
+            bound: None,
+            unit: None,
+            driven: false,
+        }));
+    }
+    // The length unit of the sketch: `unit in`
+    if first == "unit" && matches!(stream.peek(), Some(Token::Ident(_))) {
+        let symbol = stream.ident()?;
+        if let Some(token) = stream.next() {
+            return Err(stream.error(format!("unexpected trailing {:?}", token)));
+        }
+        return Ok(Some(Statement {
+            line_number,
+            target: None,
+            construction: false,
+            kind: first,
+            args: vec![Arg::Name(symbol)],
+            value: None,
+            bound: None,
+            unit: None,
+            driven: false,
+        }));
+    }
+    // Driven dimensions: `driven distance(a, b) = 2.0`
+    let (driven, first) = match stream.peek() {
+        Some(Token::Ident(_)) if first == "driven" => (true, stream.ident()?),
+        _ => (false, first),
+    };
+    let (target, kind) = if stream.peek() == Some(&Token::Equals) {
+        stream.next();
+        (Some(first), stream.ident()?)
+    } else {
+        (None, first)
+    };
+    // Primitive definitions can be marked as construction geometry: `l = construction line(a, b)`
+    let (construction, kind) = match stream.peek() {
+        Some(Token::Ident(_)) if target.is_some() && kind == "construction" => {
+            (true, stream.ident()?)
+        }
+        _ => (false, kind),
+    };
+    let args = stream.args()?;
+
+    let bound = match stream.peek() {
+        Some(Token::Equals) => None,
+        Some(Token::GreaterEquals) => Some(Bound::Min),
+        Some(Token::LessEquals) => Some(Bound::Max),
+        _ => None,
+    };
+    let value = match stream.peek() {
+        Some(Token::Equals | Token::GreaterEquals | Token::LessEquals) if target.is_none() => {
+            stream.next();
+            Some(stream.value()?)
+        }
+        _ => None,
+    };
+    let unit = match stream.peek() {
+        Some(Token::Ident(_)) if value.is_some() => {
+            let symbol = stream.ident()?;
+            Some(
+                Unit::from_symbol(&symbol)
+                    .ok_or_else(|| stream.error(format!("unknown unit '{}'", symbol)))?,
+            )
+        }
+        _ => None,
+    };
+
+    if let Some(token) = stream.next() {
+        return Err(stream.error(format!("unexpected trailing {:?}", token)));
+    }
+
+    Ok(Some(Statement {
+        line_number,
+        target,
+        construction,
+        kind,
+        args,
+        value,
+        bound,
+        unit,
+        driven,
+    }))
+}
+
+#[derive(Default)]
+pub(crate) struct SketchBuilder {
+    pub(crate) sketch: Sketch,
+    pub(crate) names: BTreeMap<String, PrimitiveCell>,
+}
+
+impl SketchBuilder {
+    pub(crate) fn parse(&mut self, source: &str) -> Result<(), ISOTopeError> {
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let tokens = tokenize_line(line, line_number)?;
+            if let Some(statement) = parse_statement(tokens, line_number)? {
+                match (statement.target.clone(), &statement.value) {
+                    (Some(name), Some(Value::Scalar(value))) => {
+                        self.sketch
+                            .set_parameter(&name, value.clone())
+                            .map_err(|e| error(&statement, e.to_string()))?;
+                    }
+                    (Some(name), _) => self.define_primitive(&name, &statement)?,
+                    (None, _) if statement.kind == "unit" => self.set_unit(&statement)?,
+                    (None, _) => self.add_constraint(&statement)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Values without a unit are in the length unit of the sketch, so it has to be set first
+    fn set_unit(&mut self, statement: &Statement) -> Result<(), ISOTopeError> {
+        let unit = match statement.args.as_slice() {
+            [Arg::Name(symbol)] => LengthUnit::from_symbol(symbol)
+                .ok_or_else(|| error(statement, format!("unknown length unit '{}'", symbol)))?,
+            _ => return Err(error(statement, "'unit' expects a length unit like 'mm'")),
+        };
+        if self.sketch.get_num_primitives() > 0 || !self.sketch.parameters().is_empty() {
+            return Err(error(
+                statement,
+                "'unit' has to come before all other statements",
+            ));
+        }
+        self.sketch.set_length_unit(unit);
+        Ok(())
+    }
+
+    fn define_primitive(&mut self, name: &str, statement: &Statement) -> Result<(), ISOTopeError> {
+        if self.names.contains_key(name) {
+            return Err(error(statement, format!("'{}' is already defined", name)));
+        }
+
+        let primitive = match statement.kind.as_str() {
+            "point" => {
+                expect_arity(statement, 2)?;
+                PrimitiveCell::Point2(Rc::new(RefCell::new(Point2::new(
+                    self.number(statement, 0)?,
+                    self.number(statement, 1)?,
+                ))))
+            }
+            "line" => {
+                expect_arity(statement, 2)?;
+                PrimitiveCell::Line(Rc::new(RefCell::new(Line::new(
+                    self.point(statement, 0)?,
+                    self.point(statement, 1)?,
+                ))))
+            }
+            "circle" => {
+                expect_arity(statement, 2)?;
+                PrimitiveCell::Circle(Rc::new(RefCell::new(Circle::new(
+                    self.point(statement, 0)?,
+                    self.number(statement, 1)?,
+                ))))
+            }
+            "arc" => {
+                if statement.args.len() != 4 && statement.args.len() != 5 {
+                    return Err(error(statement, "arc expects 4 or 5 arguments"));
+                }
+                PrimitiveCell::Arc(Rc::new(RefCell::new(Arc::new(
+                    self.point(statement, 0)?,
+                    self.number(statement, 1)?,
+                    direction(statement, 4)?,
+                    self.number(statement, 2)?,
+                    self.number(statement, 3)?,
+                ))))
+            }
+            "ellipse" => {
+                expect_arity(statement, 4)?;
+                PrimitiveCell::Ellipse(Rc::new(RefCell::new(Ellipse::new(
+                    self.point(statement, 0)?,
+                    self.number(statement, 1)?,
+                    self.number(statement, 2)?,
+                    self.number(statement, 3)?,
+                ))))
+            }
+            "elliptical_arc" => {
+                if statement.args.len() != 6 && statement.args.len() != 7 {
+                    return Err(error(statement, "elliptical_arc expects 6 or 7 arguments"));
+                }
+                PrimitiveCell::EllipticalArc(Rc::new(RefCell::new(EllipticalArc::new(
+                    self.point(statement, 0)?,
+                    self.number(statement, 1)?,
+                    self.number(statement, 2)?,
+                    self.number(statement, 3)?,
+                    direction(statement, 6)?,
+                    self.number(statement, 4)?,
+                    self.number(statement, 5)?,
+                ))))
+            }
+            "bezier" => {
+                expect_arity(statement, 4)?;
+                PrimitiveCell::Bezier(Rc::new(RefCell::new(Bezier::new(
+                    self.point(statement, 0)?,
+                    self.point(statement, 1)?,
+                    self.point(statement, 2)?,
+                    self.point(statement, 3)?,
+                ))))
+            }
+            "bspline" => {
+                PrimitiveCell::BSpline(Rc::new(RefCell::new(self.bspline_definition(statement)?)))
+            }
+            kind => return Err(error(statement, format!("unknown primitive '{}'", kind))),
+        };
+
+        if statement.value.is_some() {
+            return Err(error(statement, "primitives cannot be assigned a value"));
+        }
+        if statement.construction {
+            if let PrimitiveCell::Point2(_) = primitive {
+                return Err(error(statement, "points cannot be construction geometry"));
+            }
+            primitive.set_construction(true);
+        }
+
+        // Printed sketches name primitives after their id, keep it so that gaps survive
+        let printed_id = name
+            .trim_start_matches(char::is_alphabetic)
+            .parse()
+            .ok()
+            .filter(|id| primitive_name(*id, &primitive) == name);
+        if let Some(id) = printed_id {
+            self.sketch.skip_primitive_ids(id);
+        }
+        self.sketch.add_primitive(primitive.clone())?;
+        self.names.insert(name.to_string(), primitive);
+        Ok(())
+    }
+
+    fn add_constraint(&mut self, statement: &Statement) -> Result<(), ISOTopeError> {
+        if let Some(bound) = statement.bound {
+            return self.add_inequality(statement, bound);
+        }
+
+        let constraint = match statement.kind.as_str() {
+            "fix" => {
+                expect_arity(statement, 1)?;
+                let (x, y) = self.vector_value(statement)?;
+                ConstraintCell::FixPoint(Rc::new(RefCell::new(FixPoint::new(
+                    self.point(statement, 0)?,
+                    Vector2::new(x, y),
+                ))))
+            }
+            "distance" => {
+                let signed = signed_flag(statement, 2)?;
+                let desired_distance = self.scalar_value(statement)?;
+                match (self.primitive(statement, 0)?, self.primitive(statement, 1)?) {
+                    (PrimitiveCell::Point2(point1), PrimitiveCell::Point2(point2)) if !signed => {
+                        ConstraintCell::EuclideanDistance(Rc::new(RefCell::new(
+                            EuclidianDistanceBetweenPoints::new(point1, point2, desired_distance),
+                        )))
+                    }
+                    (PrimitiveCell::Point2(point), PrimitiveCell::Line(line)) => {
+                        ConstraintCell::PointLineDistance(Rc::new(RefCell::new(
+                            PointLineDistance::new(point, line, desired_distance, signed),
+                        )))
+                    }
+                    (PrimitiveCell::Line(line1), PrimitiveCell::Line(line2)) => {
+                        ConstraintCell::LineLineDistance(Rc::new(RefCell::new(
+                            LineLineDistance::new(line1, line2, desired_distance, signed),
+                        )))
+                    }
+                    (PrimitiveCell::Point2(point), PrimitiveCell::Circle(circle)) => {
+                        ConstraintCell::PointCircleDistance(Rc::new(RefCell::new(
+                            PointCircleDistance::new(point, circle, desired_distance, signed),
+                        )))
+                    }
+                    (PrimitiveCell::Line(line), PrimitiveCell::Circle(circle)) => {
+                        ConstraintCell::LineCircleDistance(Rc::new(RefCell::new(
+                            LineCircleDistance::new(line, circle, desired_distance, signed),
+                        )))
+                    }
+                    _ => {
+                        return Err(error(
+                            statement,
+                            "distance is not supported for these primitives",
+                        ))
+                    }
+                }
+            }
+            "horizontal_distance" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::HorizontalDistance(Rc::new(RefCell::new(
+                    HorizontalDistanceBetweenPoints::new(
+                        self.point(statement, 0)?,
+                        self.point(statement, 1)?,
+                        self.scalar_value(statement)?,
+                    ),
+                )))
+            }
+            "vertical_distance" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::VerticalDistance(Rc::new(RefCell::new(
+                    VerticalDistanceBetweenPoints::new(
+                        self.point(statement, 0)?,
+                        self.point(statement, 1)?,
+                        self.scalar_value(statement)?,
+                    ),
+                )))
+            }
+            "angle" => {
+                expect_arity(statement, 3)?;
+                ConstraintCell::AngleBetweenPoints(Rc::new(RefCell::new(AngleBetweenPoints::new(
+                    self.point(statement, 0)?,
+                    self.point(statement, 1)?,
+                    self.point(statement, 2)?,
+                    self.angle_value(statement)?,
+                ))))
+            }
+            "arc_start_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::ArcStartPointCoincident(Rc::new(RefCell::new(
+                    ArcStartPointCoincident::new(
+                        self.arc(statement, 0)?,
+                        self.point(statement, 1)?,
+                    ),
+                )))
+            }
+            "arc_end_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::ArcEndPointCoincident(Rc::new(RefCell::new(
+                    ArcEndPointCoincident::new(self.arc(statement, 0)?, self.point(statement, 1)?),
+                )))
+            }
+            "elliptical_arc_start_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::EllipticalArcStartPointCoincident(Rc::new(RefCell::new(
+                    EllipticalArcStartPointCoincident::new(
+                        self.elliptical_arc(statement, 0)?,
+                        self.point(statement, 1)?,
+                    ),
+                )))
+            }
+            "elliptical_arc_end_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::EllipticalArcEndPointCoincident(Rc::new(RefCell::new(
+                    EllipticalArcEndPointCoincident::new(
+                        self.elliptical_arc(statement, 0)?,
+                        self.point(statement, 1)?,
+                    ),
+                )))
+            }
+            "point_on_ellipse" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::PointOnEllipse(Rc::new(RefCell::new(PointOnEllipse::new(
+                    self.ellipse(statement, 0)?,
+                    self.point(statement, 1)?,
+                ))))
+            }
+            "tangent" => {
+                expect_arity(statement, 2)?;
+                match (self.primitive(statement, 0)?, self.primitive(statement, 1)?) {
+                    (PrimitiveCell::Line(line), PrimitiveCell::Ellipse(ellipse))
+                    | (PrimitiveCell::Ellipse(ellipse), PrimitiveCell::Line(line)) => {
+                        ConstraintCell::LineEllipseTangent(Rc::new(RefCell::new(
+                            LineEllipseTangent::new(line, ellipse),
+                        )))
+                    }
+                    _ => {
+                        return Err(error(
+                            statement,
+                            "tangent is not supported for these primitives",
+                        ))
+                    }
+                }
+            }
+            "bspline_start_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::BSplineStartPointCoincident(Rc::new(RefCell::new(
+                    BSplineStartPointCoincident::new(
+                        self.bspline(statement, 0)?,
+                        self.point(statement, 1)?,
+                    ),
+                )))
+            }
+            "bspline_end_coincident" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::BSplineEndPointCoincident(Rc::new(RefCell::new(
+                    BSplineEndPointCoincident::new(
+                        self.bspline(statement, 0)?,
+                        self.point(statement, 1)?,
+                    ),
+                )))
+            }
+            "bspline_start_tangent" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::BSplineStartTangent(Rc::new(RefCell::new(
+                    BSplineStartTangent::new(self.bspline(statement, 0)?, self.line(statement, 1)?),
+                )))
+            }
+            "bspline_end_tangent" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::BSplineEndTangent(Rc::new(RefCell::new(BSplineEndTangent::new(
+                    self.bspline(statement, 0)?,
+                    self.line(statement, 1)?,
+                ))))
+            }
+            "g1" => {
+                expect_arity(statement, 4)?;
+                ConstraintCell::G1Continuity(Rc::new(RefCell::new(G1Continuity::new(
+                    self.continuity_curve(statement, 0)?,
+                    self.continuity_curve(statement, 2)?,
+                ))))
+            }
+            "g2" => {
+                expect_arity(statement, 4)?;
+                ConstraintCell::G2Continuity(Rc::new(RefCell::new(G2Continuity::new(
+                    self.continuity_curve(statement, 0)?,
+                    self.continuity_curve(statement, 2)?,
+                ))))
+            }
+            "horizontal" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::HorizontalLine(Rc::new(RefCell::new(HorizontalLine::new(
+                    self.line(statement, 0)?,
+                ))))
+            }
+            "vertical" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::VerticalLine(Rc::new(RefCell::new(VerticalLine::new(
+                    self.line(statement, 0)?,
+                ))))
+            }
+            "parallel" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::ParallelLines(Rc::new(RefCell::new(ParallelLines::new(
+                    self.line(statement, 0)?,
+                    self.line(statement, 1)?,
+                ))))
+            }
+            "perpendicular" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::PerpendicularLines(Rc::new(RefCell::new(PerpendicularLines::new(
+                    self.line(statement, 0)?,
+                    self.line(statement, 1)?,
+                ))))
+            }
+            "equal_length" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::EqualLength(Rc::new(RefCell::new(EqualLength::new(
+                    self.line(statement, 0)?,
+                    self.line(statement, 1)?,
+                ))))
+            }
+            "equal_lengths" => {
+                if statement.args.len() < 2 {
+                    return Err(error(
+                        statement,
+                        "'equal_lengths' expects at least two lines, arcs or circles",
+                    ));
+                }
+                let curves = (0..statement.args.len())
+                    .map(|index| self.length_curve(statement, index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                ConstraintCell::EqualLengths(Rc::new(RefCell::new(EqualLengths::new(curves))))
+            }
+            "line_angle" => {
+                let signed = signed_flag(statement, 2)?;
+                ConstraintCell::AngleBetweenLines(Rc::new(RefCell::new(AngleBetweenLines::new(
+                    self.line(statement, 0)?,
+                    self.line(statement, 1)?,
+                    self.angle_value(statement)?,
+                    signed,
+                ))))
+            }
+            "collinear" => match self.primitive(statement, 0)? {
+                PrimitiveCell::Line(_) => {
+                    expect_arity(statement, 2)?;
+                    ConstraintCell::CollinearLines(Rc::new(RefCell::new(CollinearLines::new(
+                        self.line(statement, 0)?,
+                        self.line(statement, 1)?,
+                    ))))
+                }
+                _ => {
+                    if statement.args.len() < 3 {
+                        return Err(error(
+                            statement,
+                            "'collinear' expects two lines or at least three points",
+                        ));
+                    }
+                    let points = (0..statement.args.len())
+                        .map(|index| self.point(statement, index))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    ConstraintCell::CollinearPoints(Rc::new(RefCell::new(CollinearPoints::new(
+                        points,
+                    ))))
+                }
+            },
+            "linear_pattern" | "circular_pattern" => {
+                let (kind, first) = if statement.kind == "linear_pattern" {
+                    let (x, y) = self.vector_value(statement)?;
+                    let spacing = Vector2::new(x, y);
+                    (PatternKind::Linear { spacing }, 0)
+                } else {
+                    let center = self.point(statement, 0)?;
+                    let step_angle = self.scalar_value(statement)?;
+                    (PatternKind::Circular { center, step_angle }, 1)
+                };
+                let count = self.number(statement, first)?;
+                if count < 0.0 || count.fract() != 0.0 {
+                    return Err(error(
+                        statement,
+                        format!("argument {} must be the number of copies", first + 1),
+                    ));
+                }
+                let primitives = (first + 1..statement.args.len())
+                    .map(|index| self.primitive(statement, index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let groups = count as usize + 1;
+                if primitives.is_empty() || primitives.len() % groups != 0 {
+                    return Err(error(
+                        statement,
+                        format!(
+                            "'{}' expects the seed followed by {} copies of the same size",
+                            statement.kind,
+                            groups - 1
+                        ),
+                    ));
+                }
+                let mut chunks = primitives.chunks(primitives.len() / groups);
+                let seed = chunks.next().unwrap_or_default().to_vec();
+                let copies: Vec<_> = chunks.map(<[_]>::to_vec).collect();
+                if !Pattern::is_valid(&seed, &copies) {
+                    return Err(error(
+                        statement,
+                        "the seed has to list points before their users, and the copies have to match it",
+                    ));
+                }
+                ConstraintCell::Pattern(Rc::new(RefCell::new(Pattern::new(seed, copies, kind))))
+            }
+            "midpoint" => {
+                expect_arity(statement, 2)?;
+                let curve = match self.primitive(statement, 0)? {
+                    PrimitiveCell::Line(line) => MidpointCurve::Line(line),
+                    PrimitiveCell::Arc(arc) => MidpointCurve::Arc(arc),
+                    _ => {
+                        return Err(error(
+                            statement,
+                            "midpoint is only supported for lines and arcs",
+                        ))
+                    }
+                };
+                ConstraintCell::Midpoint(Rc::new(RefCell::new(Midpoint::new(
+                    curve,
+                    self.point(statement, 1)?,
+                ))))
+            }
+            "arc_angle" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::ArcSweepAngle(Rc::new(RefCell::new(ArcSweepAngle::new(
+                    self.arc(statement, 0)?,
+                    self.angle_value(statement)?,
+                ))))
+            }
+            "arc_length" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::ArcLength(Rc::new(RefCell::new(ArcLength::new(
+                    self.arc(statement, 0)?,
+                    self.scalar_value(statement)?,
+                ))))
+            }
+            "radius" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::RadiusDimension(Rc::new(RefCell::new(RadiusDimension::new(
+                    self.radial_curve(statement, 0)?,
+                    self.scalar_value(statement)?,
+                ))))
+            }
+            "diameter" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::DiameterDimension(Rc::new(RefCell::new(DiameterDimension::new(
+                    self.radial_curve(statement, 0)?,
+                    self.scalar_value(statement)?,
+                ))))
+            }
+            "equal_radius" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::EqualRadius(Rc::new(RefCell::new(EqualRadius::new(
+                    self.radial_curve(statement, 0)?,
+                    self.radial_curve(statement, 1)?,
+                ))))
+            }
+            "radius_difference" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::RadiusDifference(Rc::new(RefCell::new(RadiusDifference::new(
+                    self.radial_curve(statement, 0)?,
+                    self.radial_curve(statement, 1)?,
+                    self.scalar_value(statement)?,
+                ))))
+            }
+            "symmetric" => {
+                expect_arity(statement, 3)?;
+                let axis = self.line(statement, 2)?;
+                match (self.primitive(statement, 0)?, self.primitive(statement, 1)?) {
+                    (PrimitiveCell::Point2(point1), PrimitiveCell::Point2(point2)) => {
+                        ConstraintCell::Symmetric(Rc::new(RefCell::new(Symmetric::new(
+                            point1, point2, axis,
+                        ))))
+                    }
+                    (PrimitiveCell::Line(line1), PrimitiveCell::Line(line2)) => {
+                        ConstraintCell::SymmetricLines(Rc::new(RefCell::new(SymmetricLines::new(
+                            line1, line2, axis,
+                        ))))
+                    }
+                    (PrimitiveCell::Arc(arc1), PrimitiveCell::Arc(arc2)) => {
+                        ConstraintCell::SymmetricArcs(Rc::new(RefCell::new(SymmetricArcs::new(
+                            arc1, arc2, axis,
+                        ))))
+                    }
+                    _ => {
+                        return Err(error(
+                            statement,
+                            "symmetric is not supported for these primitives",
+                        ))
+                    }
+                }
+            }
+            kind => return Err(error(statement, format!("unknown constraint '{}'", kind))),
+        };
+
+        let takes_value = matches!(
+            statement.kind.as_str(),
+            "fix"
+                | "distance"
+                | "horizontal_distance"
+                | "vertical_distance"
+                | "angle"
+                | "line_angle"
+                | "arc_angle"
+                | "arc_length"
+                | "radius"
+                | "diameter"
+                | "radius_difference"
+                | "linear_pattern"
+                | "circular_pattern"
+        );
+        if !takes_value && statement.value.is_some() {
+            return Err(error(
+                statement,
+                format!("'{}' does not take a value", statement.kind),
+            ));
+        }
+
+        self.sketch.add_constraint(constraint.clone())?;
+        self.configure_dimensions(statement, constraint)
+    }
+
+    fn add_inequality(&mut self, statement: &Statement, bound: Bound) -> Result<(), ISOTopeError> {
+        let limit = self.scalar_value(statement)?;
+        let constraint = match statement.kind.as_str() {
+            "distance" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::EuclideanDistanceBound(Rc::new(RefCell::new(
+                    EuclideanDistanceBound::new(
+                        self.point(statement, 0)?,
+                        self.point(statement, 1)?,
+                        limit,
+                        bound,
+                    ),
+                )))
+            }
+            "horizontal_distance" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::HorizontalDistanceBound(Rc::new(RefCell::new(
+                    HorizontalDistanceBound::new(
+                        self.point(statement, 0)?,
+                        self.point(statement, 1)?,
+                        limit,
+                        bound,
+                    ),
+                )))
+            }
+            "vertical_distance" => {
+                expect_arity(statement, 2)?;
+                ConstraintCell::VerticalDistanceBound(Rc::new(RefCell::new(
+                    VerticalDistanceBound::new(
+                        self.point(statement, 0)?,
+                        self.point(statement, 1)?,
+                        limit,
+                        bound,
+                    ),
+                )))
+            }
+            "angle" => {
+                expect_arity(statement, 3)?;
+                ConstraintCell::AngleBound(Rc::new(RefCell::new(AngleBound::new(
+                    self.point(statement, 0)?,
+                    self.point(statement, 1)?,
+                    self.point(statement, 2)?,
+                    Angle::radians(limit),
+                    bound,
+                ))))
+            }
+            "radius" => {
+                expect_arity(statement, 1)?;
+                ConstraintCell::RadiusBound(Rc::new(RefCell::new(RadiusBound::new(
+                    self.radial_curve(statement, 0)?,
+                    limit,
+                    bound,
+                ))))
+            }
+            kind => {
+                return Err(error(
+                    statement,
+                    format!("'{}' can not be an inequality", kind),
+                ))
+            }
+        };
+
+        self.sketch.add_constraint(constraint.clone())?;
+        self.configure_dimensions(statement, constraint)
+    }
+
+    // Values that use parameters keep following them, driven dimensions keep measuring
+    fn configure_dimensions(
+        &mut self,
+        statement: &Statement,
+        constraint: ConstraintCell,
+    ) -> Result<(), ISOTopeError> {
+        if let Some(unit) = statement.unit {
+            if constraint
+                .dimension_quantities()
+                .iter()
+                .any(|quantity| *quantity != unit.quantity())
+            {
+                return Err(error(
+                    statement,
+                    format!("'{}' does not take a value in this unit", statement.kind),
+                ));
+            }
+        }
+        for (index, expression) in self.expressions(statement).into_iter().enumerate() {
+            if expression.constant_value().is_none() {
+                if statement.driven {
+                    return Err(error(statement, "driven dimensions can not use parameters"));
+                }
+                self.sketch
+                    .bind_dimension(constraint.clone(), index, expression)
+                    .map_err(|e| error(statement, e.to_string()))?;
+            }
+        }
+        if statement.driven {
+            self.sketch
+                .set_driven(&constraint, true)
+                .map_err(|e| error(statement, e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn primitive(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<PrimitiveCell, ISOTopeError> {
+        match statement.args.get(index) {
+            Some(Arg::Name(name)) => self
+                .names
+                .get(name)
+                .cloned()
+                .ok_or_else(|| error(statement, format!("'{}' is not defined", name))),
+            Some(Arg::Number(_)) => Err(error(
+                statement,
+                format!("argument {} must be a name", index + 1),
+            )),
+            None => Err(error(statement, format!("missing argument {}", index + 1))),
+        }
+    }
+
+    fn point(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<Rc<RefCell<Point2>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Point2(point) => Ok(point),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a point", index + 1),
+            )),
+        }
+    }
+
+    fn line(&self, statement: &Statement, index: usize) -> Result<Rc<RefCell<Line>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Line(line) => Ok(line),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a line", index + 1),
+            )),
+        }
+    }
+
+    fn arc(&self, statement: &Statement, index: usize) -> Result<Rc<RefCell<Arc>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Arc(arc) => Ok(arc),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be an arc", index + 1),
+            )),
+        }
+    }
+
+    fn ellipse(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<Rc<RefCell<Ellipse>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Ellipse(ellipse) => Ok(ellipse),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be an ellipse", index + 1),
+            )),
+        }
+    }
+
+    fn elliptical_arc(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<Rc<RefCell<EllipticalArc>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::EllipticalArc(arc) => Ok(arc),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be an elliptical arc", index + 1),
+            )),
+        }
+    }
+
+    fn bspline(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<Rc<RefCell<BSpline>>, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::BSpline(spline) => Ok(spline),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a B-spline", index + 1),
+            )),
+        }
+    }
+
+    // `bspline(degree, points..., [knots..., [weights...]])`. Without knots the spline is clamped
+    // with uniform knots.
+    fn bspline_definition(&self, statement: &Statement) -> Result<BSpline, ISOTopeError> {
+        let degree = self.number(statement, 0)?;
+        if degree < 1.0 || degree.fract() != 0.0 {
+            return Err(error(statement, "the degree must be a positive integer"));
+        }
+        let degree = degree as usize;
+
+        let num_points = statement.args[1..]
+            .iter()
+            .take_while(|arg| matches!(arg, Arg::Name(_)))
+            .count();
+        let control_points = (1..=num_points)
+            .map(|index| self.point(statement, index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let numbers = (num_points + 1..statement.args.len())
+            .map(|index| self.number(statement, index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let num_knots = num_points + degree + 1;
+        let spline = if numbers.is_empty() {
+            BSpline::clamped(control_points, degree)
+        } else if numbers.len() == num_knots {
+            BSpline::new(control_points, degree, numbers, None)
+        } else if numbers.len() == num_knots + num_points {
+            let weights = numbers[num_knots..].to_vec();
+            BSpline::new(
+                control_points,
+                degree,
+                numbers[..num_knots].to_vec(),
+                Some(weights),
+            )
+        } else {
+            return Err(error(
+                statement,
+                format!(
+                    "bspline expects {} knots and optionally {} weights, found {} numbers",
+                    num_knots,
+                    num_points,
+                    numbers.len()
+                ),
+            ));
+        };
+        spline.map_err(|e| error(statement, e.to_string()))
+    }
+
+    // A line, arc or Bézier followed by 'start' or 'end' for the end at the joint
+    fn continuity_curve(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<ContinuityCurve, ISOTopeError> {
+        let end = match statement.args.get(index + 1) {
+            Some(Arg::Name(flag)) if flag == "start" => CurveEnd::Start,
+            Some(Arg::Name(flag)) if flag == "end" => CurveEnd::End,
+            _ => {
+                return Err(error(
+                    statement,
+                    format!("argument {} must be 'start' or 'end'", index + 2),
+                ))
+            }
+        };
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Line(line) => Ok(ContinuityCurve::Line(line, end)),
+            PrimitiveCell::Arc(arc) => Ok(ContinuityCurve::Arc(arc, end)),
+            PrimitiveCell::Bezier(bezier) => Ok(ContinuityCurve::Bezier(bezier, end)),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a line, arc or bezier", index + 1),
+            )),
+        }
+    }
+
+    fn length_curve(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<LengthCurve, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Line(line) => Ok(LengthCurve::Line(line)),
+            PrimitiveCell::Arc(arc) => Ok(LengthCurve::Arc(arc)),
+            PrimitiveCell::Circle(circle) => Ok(LengthCurve::Circle(circle)),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a line, arc or circle", index + 1),
+            )),
+        }
+    }
+
+    fn radial_curve(
+        &self,
+        statement: &Statement,
+        index: usize,
+    ) -> Result<RadialCurve, ISOTopeError> {
+        match self.primitive(statement, index)? {
+            PrimitiveCell::Circle(circle) => Ok(RadialCurve::Circle(circle)),
+            PrimitiveCell::Arc(arc) => Ok(RadialCurve::Arc(arc)),
+            _ => Err(error(
+                statement,
+                format!("argument {} must be a circle or arc", index + 1),
+            )),
+        }
+    }
+
+    fn number(&self, statement: &Statement, index: usize) -> Result<f64, ISOTopeError> {
+        match statement.args.get(index) {
+            Some(Arg::Number(value)) if value.is_finite() => Ok(*value),
+            Some(_) => Err(error(
+                statement,
+                format!("argument {} must be a finite number", index + 1),
+            )),
+            None => Err(error(statement, format!("missing argument {}", index + 1))),
+        }
+    }
+
+    fn evaluate(&self, statement: &Statement, value: &Expression) -> Result<f64, ISOTopeError> {
+        self.sketch
+            .parameters()
+            .evaluate(value)
+            .map_err(|e| error(statement, e.to_string()))
+    }
+
+    // The value expressions converted from their unit to the units of the sketch
+    fn expressions(&self, statement: &Statement) -> Vec<Expression> {
+        let factor = match statement.unit {
+            Some(Unit::Length(unit)) => unit.factor_to(self.sketch.length_unit()),
+            Some(Unit::Angle(AngleUnit::Degree)) => PI / 180.0,
+            Some(Unit::Angle(AngleUnit::Radian)) | None => 1.0,
+        };
+        let expressions = statement
+            .value
+            .as_ref()
+            .map(Value::expressions)
+            .unwrap_or_default();
+        if factor == 1.0 {
+            return expressions;
+        }
+        expressions
+            .into_iter()
+            .map(|expression| match expression.constant_value() {
+                Some(value) => Expression::Number(value * factor),
+                None => {
+                    Expression::Multiply(Box::new(expression), Box::new(Expression::Number(factor)))
+                }
+            })
+            .collect()
+    }
+
+    fn scalar_value(&self, statement: &Statement) -> Result<f64, ISOTopeError> {
+        match (&statement.value, self.expressions(statement).as_slice()) {
+            (Some(Value::Scalar(_)), [value]) => self.evaluate(statement, value),
+            _ => Err(error(
+                statement,
+                format!("'{}' expects a finite number as value", statement.kind),
+            )),
+        }
+    }
+
+    fn angle_value(&self, statement: &Statement) -> Result<Angle, ISOTopeError> {
+        Ok(Angle::radians(self.scalar_value(statement)?))
+    }
+
+    fn vector_value(&self, statement: &Statement) -> Result<(f64, f64), ISOTopeError> {
+        match (&statement.value, self.expressions(statement).as_slice()) {
+            (Some(Value::Vector(..)), [x, y]) => {
+                Ok((self.evaluate(statement, x)?, self.evaluate(statement, y)?))
+            }
+            _ => Err(error(
+                statement,
+                format!("'{}' expects a position (x, y) as value", statement.kind),
+            )),
+        }
+    }
+}
+
+// Reads the optional 'cw' / 'ccw' flag of arcs, counter-clockwise is the default.
+fn direction(statement: &Statement, index: usize) -> Result<bool, ISOTopeError> {
+    match statement.args.get(index) {
+        None => Ok(false),
+        Some(Arg::Name(flag)) if flag == "ccw" => Ok(false),
+        Some(Arg::Name(flag)) if flag == "cw" => Ok(true),
+        Some(_) => Err(error(statement, "arc direction must be 'cw' or 'ccw'")),
+    }
+}
+
+// An optional 'signed' flag after the given number of arguments
+fn signed_flag(statement: &Statement, index: usize) -> Result<bool, ISOTopeError> {
+    match statement.args.get(index) {
+        None => Ok(false),
+        Some(Arg::Name(flag)) if flag == "signed" && statement.args.len() == index + 1 => Ok(true),
+        Some(Arg::Name(flag)) if flag == "signed" => Err(error(
+            statement,
+            format!(
+                "'{}' expects {} arguments, found {}",
+                statement.kind,
+                index + 1,
+                statement.args.len()
+            ),
+        )),
+        Some(_) => Err(error(
+            statement,
+            format!("argument {} must be 'signed'", index + 1),
+        )),
+    }
+}
+
+fn expect_arity(statement: &Statement, arity: usize) -> Result<(), ISOTopeError> {
+    if statement.args.len() != arity {
+        return Err(error(
+            statement,
+            format!(
+                "'{}' expects {} arguments, found {}",
+                statement.kind,
+                arity,
+                statement.args.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn error(statement: &Statement, message: impl Into<String>) -> ISOTopeError {
+    ISOTopeError::ParseError(statement.line_number, message.into())
+}
@@ -0,0 +1,578 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::constraints::continuity::{ContinuityCurve, CurveEnd};
+use crate::constraints::inequality::Bound;
+use crate::constraints::pattern::PatternKind;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::primitives::PrimitiveCell;
+use crate::sketch::Sketch;
+use crate::units::LengthUnit;
+
+// Floats are printed with `{:?}`, which yields the shortest representation that parses back to the
+// exact same value. This is what makes the text format lossless.
+fn number(value: f64) -> String {
+    format!("{:?}", value)
+}
+
+pub(crate) fn primitive_name(id: u64, primitive: &PrimitiveCell) -> String {
+    let prefix = match primitive {
+        PrimitiveCell::Point2(_) => "p",
+        PrimitiveCell::Line(_) => "l",
+        PrimitiveCell::Arc(_) => "a",
+        PrimitiveCell::Circle(_) => "c",
+        PrimitiveCell::Ellipse(_) => "e",
+        PrimitiveCell::EllipticalArc(_) => "ea",
+        PrimitiveCell::BSpline(_) => "s",
+        PrimitiveCell::Bezier(_) => "b",
+    };
+    format!("{}{}", prefix, id)
+}
+
+pub(crate) struct SketchPrinter<'a> {
+    sketch: &'a Sketch,
+    names: BTreeMap<u64, String>,
+}
+
+impl<'a> SketchPrinter<'a> {
+    pub(crate) fn new(sketch: &'a Sketch) -> Self {
+        let names = sketch
+            .primitives()
+            .iter()
+            .map(|(id, primitive)| (*id, primitive_name(*id, primitive)))
+            .collect();
+        Self { sketch, names }
+    }
+
+    fn name(&self, primitive: PrimitiveCell) -> Result<&str, ISOTopeError> {
+        self.sketch
+            .get_primitive_id(&primitive)
+            .and_then(|id| self.names.get(&id))
+            .map(|name| name.as_str())
+            .ok_or(ISOTopeError::MissingSketchReferences)
+    }
+
+    pub(crate) fn print(&self) -> Result<String, ISOTopeError> {
+        let mut output = String::new();
+
+        // Millimeters are the default and need no statement
+        let unit = self.sketch.length_unit();
+        if unit != LengthUnit::default() {
+            let _ = writeln!(output, "unit {}", unit.symbol());
+        }
+
+        let parameters = self.sketch.parameters();
+        for name in parameters.names() {
+            if let Some(expression) = parameters.expression(&name) {
+                let _ = writeln!(output, "parameter {} = {}", name, expression);
+            }
+        }
+
+        for (id, primitive) in self.sketch.primitives().iter() {
+            let name = &self.names[id];
+            let definition = match primitive {
+                PrimitiveCell::Point2(point) => {
+                    let point = point.borrow();
+                    format!("point({}, {})", number(point.x()), number(point.y()))
+                }
+                PrimitiveCell::Line(line) => {
+                    let line = line.borrow();
+                    format!(
+                        "line({}, {})",
+                        self.name(PrimitiveCell::Point2(line.start()))?,
+                        self.name(PrimitiveCell::Point2(line.end()))?
+                    )
+                }
+                PrimitiveCell::Circle(circle) => {
+                    let circle = circle.borrow();
+                    format!(
+                        "circle({}, {})",
+                        self.name(PrimitiveCell::Point2(circle.center()))?,
+                        number(circle.radius())
+                    )
+                }
+                PrimitiveCell::Arc(arc) => {
+                    let arc = arc.borrow();
+                    format!(
+                        "arc({}, {}, {}, {}, {})",
+                        self.name(PrimitiveCell::Point2(arc.center()))?,
+                        number(arc.radius()),
+                        number(arc.start_angle()),
+                        number(arc.end_angle()),
+                        if arc.clockwise() { "cw" } else { "ccw" }
+                    )
+                }
+                PrimitiveCell::Ellipse(ellipse) => {
+                    let ellipse = ellipse.borrow();
+                    format!(
+                        "ellipse({}, {}, {}, {})",
+                        self.name(PrimitiveCell::Point2(ellipse.center()))?,
+                        number(ellipse.radius_x()),
+                        number(ellipse.radius_y()),
+                        number(ellipse.rotation())
+                    )
+                }
+                PrimitiveCell::EllipticalArc(arc) => {
+                    let arc = arc.borrow();
+                    format!(
+                        "elliptical_arc({}, {}, {}, {}, {}, {}, {})",
+                        self.name(PrimitiveCell::Point2(arc.center()))?,
+                        number(arc.radius_x()),
+                        number(arc.radius_y()),
+                        number(arc.rotation()),
+                        number(arc.start_parameter()),
+                        number(arc.end_parameter()),
+                        if arc.clockwise() { "cw" } else { "ccw" }
+                    )
+                }
+                PrimitiveCell::BSpline(spline) => {
+                    let spline = spline.borrow();
+                    let mut args = vec![spline.degree().to_string()];
+                    for point in spline.control_points() {
+                        args.push(self.name(PrimitiveCell::Point2(point))?.to_string());
+                    }
+                    args.extend(spline.knots().into_iter().map(number));
+                    if let Some(weights) = spline.weights() {
+                        args.extend(weights.into_iter().map(number));
+                    }
+                    format!("bspline({})", args.join(", "))
+                }
+                PrimitiveCell::Bezier(bezier) => {
+                    let bezier = bezier.borrow();
+                    format!(
+                        "bezier({}, {}, {}, {})",
+                        self.name(PrimitiveCell::Point2(bezier.start()))?,
+                        self.name(PrimitiveCell::Point2(bezier.control1()))?,
+                        self.name(PrimitiveCell::Point2(bezier.control2()))?,
+                        self.name(PrimitiveCell::Point2(bezier.end()))?
+                    )
+                }
+            };
+            let modifier = if primitive.construction() {
+                "construction "
+            } else {
+                ""
+            };
+            let _ = writeln!(output, "{} = {}{}", name, modifier, definition);
+        }
+
+        for constraint in self.sketch.constraints().iter() {
+            let modifier = if self.sketch.is_driven(constraint) {
+                "driven "
+            } else {
+                ""
+            };
+            let _ = writeln!(output, "{}{}", modifier, self.constraint(constraint)?);
+        }
+
+        Ok(output)
+    }
+
+    fn constraint(&self, constraint: &ConstraintCell) -> Result<String, ISOTopeError> {
+        // Dimensions driven by parameters print their expression
+        let value = |index, value| match self.sketch.dimension_expression(constraint, index) {
+            Some(expression) => expression.to_string(),
+            None => number(value),
+        };
+        let point = |p| self.name(PrimitiveCell::Point2(p));
+        let line = |l| self.name(PrimitiveCell::Line(l));
+        let arc = |a| self.name(PrimitiveCell::Arc(a));
+        let ellipse = |e| self.name(PrimitiveCell::Ellipse(e));
+        let elliptical_arc = |a| self.name(PrimitiveCell::EllipticalArc(a));
+        let bspline = |s| self.name(PrimitiveCell::BSpline(s));
+        let circle = |c| self.name(PrimitiveCell::Circle(c));
+        let signed = |signed| if signed { ", signed" } else { "" };
+        let relation = |bound| match bound {
+            Bound::Min => ">=",
+            Bound::Max => "<=",
+        };
+        let curve = |c: ContinuityCurve| -> Result<String, ISOTopeError> {
+            let end = match c.end() {
+                CurveEnd::Start => "start",
+                CurveEnd::End => "end",
+            };
+            Ok(format!("{}, {}", self.name(c.primitive())?, end))
+        };
+
+        Ok(match constraint {
+            ConstraintCell::FixPoint(c) => {
+                let c = c.borrow();
+                format!(
+                    "fix({}) = ({}, {})",
+                    point(c.point())?,
+                    value(0, c.desired_pos().x),
+                    value(1, c.desired_pos().y)
+                )
+            }
+            ConstraintCell::EuclideanDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}) = {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::PointLineDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}{}) = {}",
+                    point(c.point())?,
+                    line(c.line())?,
+                    signed(c.signed()),
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::LineLineDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}{}) = {}",
+                    line(c.line1())?,
+                    line(c.line2())?,
+                    signed(c.signed()),
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::PointCircleDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}{}) = {}",
+                    point(c.point())?,
+                    circle(c.circle())?,
+                    signed(c.signed()),
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::LineCircleDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}{}) = {}",
+                    line(c.line())?,
+                    circle(c.circle())?,
+                    signed(c.signed()),
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::EuclideanDistanceBound(c) => {
+                let c = c.borrow();
+                format!(
+                    "distance({}, {}) {} {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    relation(c.bound()),
+                    value(0, c.limit())
+                )
+            }
+            ConstraintCell::HorizontalDistanceBound(c) => {
+                let c = c.borrow();
+                format!(
+                    "horizontal_distance({}, {}) {} {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    relation(c.bound()),
+                    value(0, c.limit())
+                )
+            }
+            ConstraintCell::VerticalDistanceBound(c) => {
+                let c = c.borrow();
+                format!(
+                    "vertical_distance({}, {}) {} {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    relation(c.bound()),
+                    value(0, c.limit())
+                )
+            }
+            ConstraintCell::AngleBound(c) => {
+                let c = c.borrow();
+                format!(
+                    "angle({}, {}, {}) {} {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    point(c.middle_point())?,
+                    relation(c.bound()),
+                    value(0, c.limit())
+                )
+            }
+            ConstraintCell::RadiusBound(c) => {
+                let c = c.borrow();
+                format!(
+                    "radius({}) {} {}",
+                    self.name(c.curve().primitive())?,
+                    relation(c.bound()),
+                    value(0, c.limit())
+                )
+            }
+            ConstraintCell::HorizontalDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "horizontal_distance({}, {}) = {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::VerticalDistance(c) => {
+                let c = c.borrow();
+                format!(
+                    "vertical_distance({}, {}) = {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    value(0, c.desired_distance())
+                )
+            }
+            ConstraintCell::AngleBetweenPoints(c) => {
+                let c = c.borrow();
+                format!(
+                    "angle({}, {}, {}) = {}",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    point(c.middle_point())?,
+                    value(0, c.desired_angle())
+                )
+            }
+            ConstraintCell::ArcStartPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "arc_start_coincident({}, {})",
+                    arc(c.arc())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::ArcEndPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "arc_end_coincident({}, {})",
+                    arc(c.arc())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::EllipticalArcStartPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "elliptical_arc_start_coincident({}, {})",
+                    elliptical_arc(c.arc())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::EllipticalArcEndPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "elliptical_arc_end_coincident({}, {})",
+                    elliptical_arc(c.arc())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::PointOnEllipse(c) => {
+                let c = c.borrow();
+                format!(
+                    "point_on_ellipse({}, {})",
+                    ellipse(c.ellipse())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::BSplineStartPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "bspline_start_coincident({}, {})",
+                    bspline(c.spline())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::BSplineEndPointCoincident(c) => {
+                let c = c.borrow();
+                format!(
+                    "bspline_end_coincident({}, {})",
+                    bspline(c.spline())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::G1Continuity(c) => {
+                let c = c.borrow();
+                format!("g1({}, {})", curve(c.curve1())?, curve(c.curve2())?)
+            }
+            ConstraintCell::G2Continuity(c) => {
+                let c = c.borrow();
+                format!("g2({}, {})", curve(c.curve1())?, curve(c.curve2())?)
+            }
+            ConstraintCell::HorizontalLine(c) => {
+                format!("horizontal({})", line(c.borrow().line())?)
+            }
+            ConstraintCell::VerticalLine(c) => format!("vertical({})", line(c.borrow().line())?),
+            ConstraintCell::ParallelLines(c) => {
+                let c = c.borrow();
+                format!("parallel({}, {})", line(c.line1())?, line(c.line2())?)
+            }
+            ConstraintCell::PerpendicularLines(c) => {
+                let c = c.borrow();
+                format!("perpendicular({}, {})", line(c.line1())?, line(c.line2())?)
+            }
+            ConstraintCell::EqualLength(c) => {
+                let c = c.borrow();
+                format!("equal_length({}, {})", line(c.line1())?, line(c.line2())?)
+            }
+            ConstraintCell::EqualLengths(c) => {
+                let curves = c
+                    .borrow()
+                    .curves()
+                    .into_iter()
+                    .map(|curve| self.name(curve.primitive()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("equal_lengths({})", curves.join(", "))
+            }
+            ConstraintCell::BSplineStartTangent(c) => {
+                let c = c.borrow();
+                format!(
+                    "bspline_start_tangent({}, {})",
+                    bspline(c.spline())?,
+                    line(c.line())?
+                )
+            }
+            ConstraintCell::BSplineEndTangent(c) => {
+                let c = c.borrow();
+                format!(
+                    "bspline_end_tangent({}, {})",
+                    bspline(c.spline())?,
+                    line(c.line())?
+                )
+            }
+            ConstraintCell::LineEllipseTangent(c) => {
+                let c = c.borrow();
+                format!("tangent({}, {})", line(c.line())?, ellipse(c.ellipse())?)
+            }
+            ConstraintCell::AngleBetweenLines(c) => {
+                let c = c.borrow();
+                format!(
+                    "line_angle({}, {}{}) = {}",
+                    line(c.line1())?,
+                    line(c.line2())?,
+                    signed(c.signed()),
+                    value(0, c.desired_angle())
+                )
+            }
+            ConstraintCell::CollinearLines(c) => {
+                let c = c.borrow();
+                format!("collinear({}, {})", line(c.line1())?, line(c.line2())?)
+            }
+            ConstraintCell::CollinearPoints(c) => {
+                let points = c
+                    .borrow()
+                    .points()
+                    .into_iter()
+                    .map(point)
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("collinear({})", points.join(", "))
+            }
+            ConstraintCell::Pattern(c) => {
+                let c = c.borrow();
+                let primitives = c
+                    .seed()
+                    .into_iter()
+                    .chain(c.copies().into_iter().flatten())
+                    .map(|p| self.name(p))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match c.kind() {
+                    PatternKind::Linear { spacing } => format!(
+                        "linear_pattern({}, {}) = ({}, {})",
+                        c.count(),
+                        primitives.join(", "),
+                        value(0, spacing.x),
+                        value(1, spacing.y)
+                    ),
+                    PatternKind::Circular { center, step_angle } => format!(
+                        "circular_pattern({}, {}, {}) = {}",
+                        point(center)?,
+                        c.count(),
+                        primitives.join(", "),
+                        value(0, step_angle)
+                    ),
+                }
+            }
+            ConstraintCell::Midpoint(c) => {
+                let c = c.borrow();
+                format!(
+                    "midpoint({}, {})",
+                    self.name(c.curve().primitive())?,
+                    point(c.point())?
+                )
+            }
+            ConstraintCell::ArcSweepAngle(c) => {
+                let c = c.borrow();
+                format!(
+                    "arc_angle({}) = {}",
+                    arc(c.arc())?,
+                    value(0, c.desired_angle())
+                )
+            }
+            ConstraintCell::ArcLength(c) => {
+                let c = c.borrow();
+                format!(
+                    "arc_length({}) = {}",
+                    arc(c.arc())?,
+                    value(0, c.desired_length())
+                )
+            }
+            ConstraintCell::RadiusDimension(c) => {
+                let c = c.borrow();
+                format!(
+                    "radius({}) = {}",
+                    self.name(c.curve().primitive())?,
+                    value(0, c.desired_radius())
+                )
+            }
+            ConstraintCell::DiameterDimension(c) => {
+                let c = c.borrow();
+                format!(
+                    "diameter({}) = {}",
+                    self.name(c.curve().primitive())?,
+                    value(0, c.desired_diameter())
+                )
+            }
+            ConstraintCell::EqualRadius(c) => {
+                let c = c.borrow();
+                format!(
+                    "equal_radius({}, {})",
+                    self.name(c.curve1().primitive())?,
+                    self.name(c.curve2().primitive())?
+                )
+            }
+            ConstraintCell::RadiusDifference(c) => {
+                let c = c.borrow();
+                format!(
+                    "radius_difference({}, {}) = {}",
+                    self.name(c.curve1().primitive())?,
+                    self.name(c.curve2().primitive())?,
+                    value(0, c.desired_difference())
+                )
+            }
+            ConstraintCell::Symmetric(c) => {
+                let c = c.borrow();
+                format!(
+                    "symmetric({}, {}, {})",
+                    point(c.point1())?,
+                    point(c.point2())?,
+                    line(c.line())?
+                )
+            }
+            ConstraintCell::SymmetricLines(c) => {
+                let c = c.borrow();
+                format!(
+                    "symmetric({}, {}, {})",
+                    line(c.line1())?,
+                    line(c.line2())?,
+                    line(c.axis())?
+                )
+            }
+            ConstraintCell::SymmetricArcs(c) => {
+                let c = c.borrow();
+                format!(
+                    "symmetric({}, {}, {})",
+                    arc(c.arc1())?,
+                    arc(c.arc2())?,
+                    line(c.axis())?
+                )
+            }
+        })
+    }
+}
@@ -13,4 +13,50 @@ pub enum ISOTopeError {
     ConstraintAlreadyInSketch,
     #[error("No such constraint in the sketch")]
     ConstraintNotFound,
+    #[error("The constraint has no dimension {0}")]
+    NotADimension(usize),
+
+    // History errors
+    #[error("No transaction is open")]
+    NoTransaction,
+    #[error("Finish the open transaction first")]
+    TransactionInProgress,
+
+    // Transform errors
+    #[error("Invalid transform: {0}")]
+    InvalidTransform(String),
+
+    // Trim, extend, split, fillet, chamfer and offset errors
+    #[error("The primitive with ID {0} can't be trimmed, extended or split this way")]
+    UnsupportedCurve(u64),
+    #[error("None of the points splits the primitive with ID {0}")]
+    InvalidSplit(u64),
+    #[error("The primitive with ID {0} doesn't reach anything when extended")]
+    NothingToExtendTo(u64),
+    #[error("The point is not the corner of two lines or of a line and an arc")]
+    InvalidCorner,
+    #[error("No fillet or chamfer of size {0} fits into the corner")]
+    CornerTooSmall(f64),
+    #[error("The segments are not a connected chain of lines and arcs")]
+    InvalidOffsetChain,
+    #[error("Nothing of the profile is left when offset by {0}")]
+    OffsetVanishes(f64),
+
+    // Primitive errors
+    #[error("Invalid B-spline: {0}")]
+    InvalidBSpline(String),
+
+    // Parameter errors
+    #[error("Unknown parameter '{0}'")]
+    UnknownParameter(String),
+    #[error("The parameter '{0}' is still in use")]
+    ParameterInUse(String),
+    #[error("Cyclic parameter dependency: {0}")]
+    CyclicParameters(String),
+    #[error("Invalid expression: {0}")]
+    InvalidExpression(String),
+
+    // DSL errors
+    #[error("Parse error on line {0}: {1}")]
+    ParseError(usize, String),
 }
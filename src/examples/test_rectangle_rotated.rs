@@ -4,6 +4,7 @@ use std::{cell::RefCell, rc::Rc};
 use nalgebra::Vector2;
 
 use crate::error::ISOTopeError;
+use crate::units::Angle;
 use crate::{primitives::point2::Point2, sketch::Sketch};
 
 pub struct RotatedRectangleDemo {
@@ -68,7 +69,7 @@ impl RotatedRectangleDemo {
             point_reference.clone(),
             point_b.clone(),
             point_a.clone(),
-            f64::to_radians(45.0),
+            Angle::degrees(45.0),
         )?;
 
         Ok(Self {
@@ -1,10 +1,308 @@
+use std::f64::consts::PI;
+
 use nalgebra::Vector2;
 
 use crate::primitives::Primitive;
 
-pub fn intersections(_a: Primitive, _b: Primitive) -> Vec<Vector2<f64>> {
-    // We only deal with point intersections for now
-    // In case two lines do overlap in parallel, we just treat the whole sketch as invalid
-    // In case of two arcs or circles with same radius in same position, we treat the whole sketch as invalid, etc.
-    todo!("Find all intersections between two primitives")
+// Two intersections closer than this are the same point
+const TOLERANCE: f64 = 1e-9;
+
+// The intersections of two lines, arcs or circles. Other primitives don't intersect anything yet.
+pub fn intersections(a: Primitive, b: Primitive) -> Vec<Vector2<f64>> {
+    // We only deal with point intersections
+    // Lines on top of each other, or arcs and circles on the same circle, overlap instead and have
+    // none. Callers that care, like the planar arrangement of `decompose`, handle overlaps themselves.
+    match (Curve::new(&a), Curve::new(&b)) {
+        (Some(a), Some(b)) => a.intersections(&b),
+        _ => Vec::new(),
+    }
+}
+
+// The intersections of the extension of `a` with `b`, where a line is extended to an infinite
+// line and an arc to its full circle. This is where `a` could be extended to.
+pub fn extension_intersections(a: Primitive, b: Primitive) -> Vec<Vector2<f64>> {
+    match (Curve::new(&a), Curve::new(&b)) {
+        (Some(a), Some(b)) => a.extended().intersections(&b),
+        _ => Vec::new(),
+    }
+}
+
+// The position of a point along a line, with 0 at the start and 1 at the end
+pub fn line_parameter(start: Vector2<f64>, end: Vector2<f64>, point: Vector2<f64>) -> f64 {
+    let direction = end - start;
+    (point - start).dot(&direction) / direction.norm_squared()
+}
+
+// How far a point around the center is from the start angle when going in the given direction, in
+// [0, 2 * PI)
+pub fn angle_from(
+    center: Vector2<f64>,
+    start_angle: f64,
+    clockwise: bool,
+    point: Vector2<f64>,
+) -> f64 {
+    let offset = point - center;
+    let angle = offset.y.atan2(offset.x) - start_angle;
+    if clockwise { -angle } else { angle }.rem_euclid(2.0 * PI)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Curve {
+    // A segment from start to end, or the infinite line through them
+    Line {
+        start: Vector2<f64>,
+        end: Vector2<f64>,
+        bounded: bool,
+    },
+    // A circle, or an arc of it if there is a range of start angle, sweep and direction
+    Circle {
+        center: Vector2<f64>,
+        radius: f64,
+        range: Option<(f64, f64, bool)>,
+    },
+}
+
+impl Curve {
+    fn new(primitive: &Primitive) -> Option<Self> {
+        match primitive {
+            Primitive::Line(line) => Some(Curve::Line {
+                start: line.start().borrow().data(),
+                end: line.end().borrow().data(),
+                bounded: true,
+            }),
+            Primitive::Arc(arc) => Some(Curve::Circle {
+                center: arc.center().borrow().data(),
+                radius: arc.radius(),
+                range: Some((arc.start_angle(), arc.sweep_angle(), arc.clockwise())),
+            }),
+            Primitive::Circle(circle) => Some(Curve::Circle {
+                center: circle.center().borrow().data(),
+                radius: circle.radius(),
+                range: None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn extended(self) -> Self {
+        match self {
+            Curve::Line { start, end, .. } => Curve::Line {
+                start,
+                end,
+                bounded: false,
+            },
+            Curve::Circle { center, radius, .. } => Curve::Circle {
+                center,
+                radius,
+                range: None,
+            },
+        }
+    }
+
+    fn contains(&self, point: Vector2<f64>) -> bool {
+        match *self {
+            Curve::Line {
+                start,
+                end,
+                bounded,
+            } => {
+                let t = line_parameter(start, end, point);
+                let tolerance = TOLERANCE / (end - start).norm();
+                !bounded || (-tolerance..=1.0 + tolerance).contains(&t)
+            }
+            Curve::Circle {
+                center,
+                radius,
+                range,
+            } => match range {
+                None => true,
+                Some((start_angle, sweep, clockwise)) => {
+                    let angle = angle_from(center, start_angle, clockwise, point);
+                    let tolerance = TOLERANCE / radius;
+                    angle <= sweep + tolerance || angle >= 2.0 * PI - tolerance
+                }
+            },
+        }
+    }
+
+    fn intersections(&self, other: &Curve) -> Vec<Vector2<f64>> {
+        let candidates = match (*self, *other) {
+            (
+                Curve::Line { start, end, .. },
+                Curve::Line {
+                    start: s, end: e, ..
+                },
+            ) => line_line(start, end, s, e),
+            (Curve::Line { start, end, .. }, Curve::Circle { center, radius, .. })
+            | (Curve::Circle { center, radius, .. }, Curve::Line { start, end, .. }) => {
+                line_circle(start, end, center, radius)
+            }
+            (
+                Curve::Circle { center, radius, .. },
+                Curve::Circle {
+                    center: c,
+                    radius: r,
+                    ..
+                },
+            ) => circle_circle(center, radius, c, r),
+        };
+        candidates
+            .into_iter()
+            .filter(|p| self.contains(*p) && other.contains(*p))
+            .collect()
+    }
+}
+
+// The intersection of the infinite lines through the two pairs of points. Parallel lines have none.
+pub(crate) fn line_line(
+    start1: Vector2<f64>,
+    end1: Vector2<f64>,
+    start2: Vector2<f64>,
+    end2: Vector2<f64>,
+) -> Vec<Vector2<f64>> {
+    let d1 = end1 - start1;
+    let d2 = end2 - start2;
+    let cross = d1.perp(&d2);
+    if cross.abs() <= TOLERANCE * d1.norm() * d2.norm() {
+        return Vec::new();
+    }
+    let t = (start2 - start1).perp(&d2) / cross;
+    vec![start1 + t * d1]
+}
+
+// The intersections of the infinite line through the two points with a circle
+pub(crate) fn line_circle(
+    start: Vector2<f64>,
+    end: Vector2<f64>,
+    center: Vector2<f64>,
+    radius: f64,
+) -> Vec<Vector2<f64>> {
+    let direction = (end - start).normalize();
+    let closest = start + (center - start).dot(&direction) * direction;
+    let distance = (closest - center).norm();
+    if distance > radius + TOLERANCE {
+        Vec::new()
+    } else if distance >= radius - TOLERANCE {
+        // Tangent
+        vec![closest]
+    } else {
+        let half_chord = (radius * radius - distance * distance).sqrt();
+        vec![
+            closest - half_chord * direction,
+            closest + half_chord * direction,
+        ]
+    }
+}
+
+// The intersections of two circles. Concentric circles have none.
+pub(crate) fn circle_circle(
+    center1: Vector2<f64>,
+    radius1: f64,
+    center2: Vector2<f64>,
+    radius2: f64,
+) -> Vec<Vector2<f64>> {
+    let offset = center2 - center1;
+    let distance = offset.norm();
+    if distance <= TOLERANCE
+        || distance > radius1 + radius2 + TOLERANCE
+        || distance < (radius1 - radius2).abs() - TOLERANCE
+    {
+        return Vec::new();
+    }
+    let direction = offset / distance;
+    // Distance from the first center to the chord through the intersections
+    let along = (distance * distance + radius1 * radius1 - radius2 * radius2) / (2.0 * distance);
+    let foot = center1 + along * direction;
+    let half_chord_squared = radius1 * radius1 - along * along;
+    if half_chord_squared <= (TOLERANCE * radius1).powi(2) {
+        // Touching
+        return vec![foot];
+    }
+    let half_chord = half_chord_squared.sqrt();
+    let normal = Vector2::new(-direction.y, direction.x);
+    vec![foot - half_chord * normal, foot + half_chord * normal]
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::primitives::{arc::Arc, circle::Circle, line::Line, point2::Point2, Primitive};
+
+    use super::{extension_intersections, intersections};
+
+    fn point(x: f64, y: f64) -> Rc<RefCell<Point2>> {
+        Rc::new(RefCell::new(Point2::new(x, y)))
+    }
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Primitive {
+        Primitive::Line(Line::new(point(x1, y1), point(x2, y2)))
+    }
+
+    fn assert_points(mut actual: Vec<Vector2<f64>>, expected: &[(f64, f64)]) {
+        actual.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, (x, y)) in actual.iter().zip(expected.iter()) {
+            assert!((a - Vector2::new(*x, *y)).norm() < 1e-9, "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn test_intersections() {
+        let circle = Primitive::Circle(Circle::new(point(0.0, 0.0), 1.0));
+        // Upper half of the circle
+        let arc = Primitive::Arc(Arc::new(point(0.0, 0.0), 1.0, false, 0.0, PI));
+
+        assert_points(
+            intersections(line(-1.0, -1.0, 1.0, 1.0), line(-1.0, 1.0, 1.0, -1.0)),
+            &[(0.0, 0.0)],
+        );
+        assert_points(
+            intersections(line(0.0, 0.0, 1.0, 0.0), line(0.0, 1.0, 1.0, 1.0)),
+            &[],
+        );
+        assert_points(
+            intersections(line(-2.0, 0.5, 2.0, 0.5), circle.clone()),
+            &[(-(0.75f64.sqrt()), 0.5), (0.75f64.sqrt(), 0.5)],
+        );
+        assert_points(intersections(line(-2.0, -0.5, 2.0, -0.5), arc.clone()), &[]);
+        assert_points(
+            intersections(line(-2.0, 1.0, 2.0, 1.0), circle.clone()),
+            &[(0.0, 1.0)],
+        );
+        assert_points(
+            intersections(
+                Primitive::Circle(Circle::new(point(1.0, 0.0), 1.0)),
+                arc.clone(),
+            ),
+            &[(0.5, 0.75f64.sqrt())],
+        );
+
+        // Circles that barely cross still cross in two points
+        let half_chord: f64 = 1e-5;
+        let distance = 2.0 * (1.0 - half_chord * half_chord).sqrt();
+        assert_points(
+            intersections(
+                Primitive::Circle(Circle::new(point(distance, 0.0), 1.0)),
+                circle.clone(),
+            ),
+            &[(0.5 * distance, -half_chord), (0.5 * distance, half_chord)],
+        );
+
+        // The segment ends before the circle, its extension doesn't
+        assert_points(intersections(line(2.0, 0.0, 3.0, 0.0), circle.clone()), &[]);
+        assert_points(
+            extension_intersections(line(2.0, 0.0, 3.0, 0.0), circle.clone()),
+            &[(-1.0, 0.0), (1.0, 0.0)],
+        );
+        assert_points(
+            extension_intersections(arc, line(-2.0, -0.5, 2.0, -0.5)),
+            &[(-(0.75f64.sqrt()), -0.5), (0.75f64.sqrt(), -0.5)],
+        );
+    }
 }
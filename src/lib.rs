@@ -4,11 +4,14 @@
 
 pub mod constraints;
 pub mod decompose;
+pub mod dsl;
 pub mod error;
 pub mod intersections;
+pub mod parameters;
 pub mod primitives;
 pub mod sketch;
 pub mod solvers;
+pub mod units;
 
 #[cfg(test)]
 pub mod examples;
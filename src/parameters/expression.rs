@@ -0,0 +1,254 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::error::ISOTopeError;
+
+// The functions that can be called in expressions
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Function {
+    Sqrt,
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Atan2,
+    Min,
+    Max,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sqrt" => Some(Function::Sqrt),
+            "abs" => Some(Function::Abs),
+            "sin" => Some(Function::Sin),
+            "cos" => Some(Function::Cos),
+            "tan" => Some(Function::Tan),
+            "atan2" => Some(Function::Atan2),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Sqrt => "sqrt",
+            Function::Abs => "abs",
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Tan => "tan",
+            Function::Atan2 => "atan2",
+            Function::Min => "min",
+            Function::Max => "max",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Function::Atan2 | Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(&self, args: &[f64]) -> f64 {
+        match self {
+            Function::Sqrt => args[0].sqrt(),
+            Function::Abs => args[0].abs(),
+            Function::Sin => args[0].sin(),
+            Function::Cos => args[0].cos(),
+            Function::Tan => args[0].tan(),
+            Function::Atan2 => args[0].atan2(args[1]),
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+        }
+    }
+}
+
+// An arithmetic expression over numbers and named parameters, like `width / 8`
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Expression {
+    Number(f64),
+    Parameter(String),
+    Negate(Box<Expression>),
+    Add(Box<Expression>, Box<Expression>),
+    Subtract(Box<Expression>, Box<Expression>),
+    Multiply(Box<Expression>, Box<Expression>),
+    Divide(Box<Expression>, Box<Expression>),
+    Power(Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    // Parses an expression in the syntax of the sketch language
+    pub fn parse(source: &str) -> Result<Self, ISOTopeError> {
+        crate::dsl::parse_expression(source)
+    }
+
+    // The names of all parameters the expression depends on
+    pub fn parameters(&self) -> BTreeSet<String> {
+        let mut parameters = BTreeSet::new();
+        self.collect_parameters(&mut parameters);
+        parameters
+    }
+
+    fn collect_parameters(&self, parameters: &mut BTreeSet<String>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Parameter(name) => {
+                parameters.insert(name.clone());
+            }
+            Expression::Negate(a) => a.collect_parameters(parameters),
+            Expression::Add(a, b)
+            | Expression::Subtract(a, b)
+            | Expression::Multiply(a, b)
+            | Expression::Divide(a, b)
+            | Expression::Power(a, b) => {
+                a.collect_parameters(parameters);
+                b.collect_parameters(parameters);
+            }
+            Expression::Call(_, args) => {
+                for arg in args.iter() {
+                    arg.collect_parameters(parameters);
+                }
+            }
+        }
+    }
+
+    // Evaluates the expression, looking up parameter values by name
+    pub fn evaluate(&self, lookup: &impl Fn(&str) -> Option<f64>) -> Result<f64, ISOTopeError> {
+        let value = self.evaluate_unchecked(lookup)?;
+        if !value.is_finite() {
+            return Err(ISOTopeError::InvalidExpression(format!(
+                "'{}' does not evaluate to a finite number",
+                self
+            )));
+        }
+        Ok(value)
+    }
+
+    fn evaluate_unchecked(
+        &self,
+        lookup: &impl Fn(&str) -> Option<f64>,
+    ) -> Result<f64, ISOTopeError> {
+        Ok(match self {
+            Expression::Number(value) => *value,
+            Expression::Parameter(name) => {
+                lookup(name).ok_or_else(|| ISOTopeError::UnknownParameter(name.clone()))?
+            }
+            Expression::Negate(a) => -a.evaluate_unchecked(lookup)?,
+            Expression::Add(a, b) => {
+                a.evaluate_unchecked(lookup)? + b.evaluate_unchecked(lookup)?
+            }
+            Expression::Subtract(a, b) => {
+                a.evaluate_unchecked(lookup)? - b.evaluate_unchecked(lookup)?
+            }
+            Expression::Multiply(a, b) => {
+                a.evaluate_unchecked(lookup)? * b.evaluate_unchecked(lookup)?
+            }
+            Expression::Divide(a, b) => {
+                a.evaluate_unchecked(lookup)? / b.evaluate_unchecked(lookup)?
+            }
+            Expression::Power(a, b) => a
+                .evaluate_unchecked(lookup)?
+                .powf(b.evaluate_unchecked(lookup)?),
+            Expression::Call(function, args) => {
+                if args.len() != function.arity() {
+                    return Err(ISOTopeError::InvalidExpression(format!(
+                        "'{}' expects {} arguments",
+                        function.name(),
+                        function.arity()
+                    )));
+                }
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate_unchecked(lookup))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.apply(&args)
+            }
+        })
+    }
+
+    // The value of an expression without parameters
+    pub fn constant_value(&self) -> Option<f64> {
+        if self.parameters().is_empty() {
+            self.evaluate(&|_| None).ok()
+        } else {
+            None
+        }
+    }
+
+    // Binding strength when printing, higher binds tighter
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Add(..) | Expression::Subtract(..) => 1,
+            Expression::Multiply(..) | Expression::Divide(..) => 2,
+            Expression::Negate(_) => 3,
+            Expression::Power(..) => 4,
+            Expression::Number(_) | Expression::Parameter(_) | Expression::Call(..) => 5,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+
+    fn fmt_binary(
+        f: &mut fmt::Formatter<'_>,
+        a: &Expression,
+        operator: &str,
+        b: &Expression,
+        precedence: u8,
+    ) -> fmt::Result {
+        a.fmt_operand(f, precedence)?;
+        write!(f, " {} ", operator)?;
+        b.fmt_operand(f, precedence + 1)
+    }
+}
+
+// Expressions print in the syntax they are parsed from. Numbers use `{:?}` to stay lossless.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{:?}", value),
+            Expression::Parameter(name) => write!(f, "{}", name),
+            Expression::Negate(a) => {
+                write!(f, "-")?;
+                a.fmt_operand(f, 3)
+            }
+            Expression::Add(a, b) => Self::fmt_binary(f, a, "+", b, 1),
+            Expression::Subtract(a, b) => Self::fmt_binary(f, a, "-", b, 1),
+            Expression::Multiply(a, b) => Self::fmt_binary(f, a, "*", b, 2),
+            Expression::Divide(a, b) => Self::fmt_binary(f, a, "/", b, 2),
+            // Powers are right associative
+            Expression::Power(a, b) => {
+                a.fmt_operand(f, 5)?;
+                write!(f, " ^ ")?;
+                b.fmt_operand(f, 3)
+            }
+            Expression::Call(function, args) => {
+                write!(f, "{}(", function.name())?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
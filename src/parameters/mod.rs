@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+
+use self::expression::Expression;
+
+pub mod expression;
+
+// A table of named parameters. Every parameter is defined by an expression that can use other
+// parameters, the values are kept up to date whenever a definition changes.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Parameters {
+    expressions: BTreeMap<String, Expression>,
+    values: BTreeMap<String, f64>,
+    // Parameter names ordered such that every parameter comes after the ones it uses
+    order: Vec<String>,
+}
+
+impl Parameters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+
+    pub fn expression(&self, name: &str) -> Option<Expression> {
+        self.expressions.get(name).cloned()
+    }
+
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn values(&self) -> BTreeMap<String, f64> {
+        self.values.clone()
+    }
+
+    // The names of all parameters, each after the parameters its expression uses
+    pub fn names(&self) -> Vec<String> {
+        self.order.clone()
+    }
+
+    // Defines or redefines a parameter and returns its new value. The table is left unchanged if
+    // the definition can not be evaluated.
+    pub fn set(&mut self, name: &str, expression: Expression) -> Result<f64, ISOTopeError> {
+        let is_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_name {
+            return Err(ISOTopeError::InvalidExpression(format!(
+                "'{}' is not a valid parameter name",
+                name
+            )));
+        }
+
+        let mut expressions = self.expressions.clone();
+        expressions.insert(name.to_string(), expression);
+        let (values, order) = evaluate(&expressions)?;
+
+        self.expressions = expressions;
+        self.values = values;
+        self.order = order;
+        Ok(self.values[name])
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), ISOTopeError> {
+        if !self.expressions.contains_key(name) {
+            return Err(ISOTopeError::UnknownParameter(name.to_string()));
+        }
+        if self
+            .expressions
+            .values()
+            .any(|expression| expression.parameters().contains(name))
+        {
+            return Err(ISOTopeError::ParameterInUse(name.to_string()));
+        }
+
+        self.expressions.remove(name);
+        self.values.remove(name);
+        self.order.retain(|n| n != name);
+        Ok(())
+    }
+
+    pub fn evaluate(&self, expression: &Expression) -> Result<f64, ISOTopeError> {
+        expression.evaluate(&|name| self.value(name))
+    }
+}
+
+type Evaluated = (BTreeMap<String, f64>, Vec<String>);
+
+// Evaluates all parameters in dependency order
+fn evaluate(expressions: &BTreeMap<String, Expression>) -> Result<Evaluated, ISOTopeError> {
+    let mut values = BTreeMap::new();
+    let mut order = vec![];
+    for name in expressions.keys() {
+        visit(name, expressions, &mut values, &mut order, &mut vec![])?;
+    }
+    Ok((values, order))
+}
+
+fn visit(
+    name: &str,
+    expressions: &BTreeMap<String, Expression>,
+    values: &mut BTreeMap<String, f64>,
+    order: &mut Vec<String>,
+    stack: &mut Vec<String>,
+) -> Result<(), ISOTopeError> {
+    if values.contains_key(name) {
+        return Ok(());
+    }
+    if let Some(position) = stack.iter().position(|n| n == name) {
+        let mut cycle = stack[position..].to_vec();
+        cycle.push(name.to_string());
+        return Err(ISOTopeError::CyclicParameters(cycle.join(" -> ")));
+    }
+    let expression = expressions
+        .get(name)
+        .ok_or_else(|| ISOTopeError::UnknownParameter(name.to_string()))?;
+
+    stack.push(name.to_string());
+    for dependency in expression.parameters() {
+        visit(&dependency, expressions, values, order, stack)?;
+    }
+    stack.pop();
+
+    let value = expression.evaluate(&|n| values.get(n).copied())?;
+    values.insert(name.to_string(), value);
+    order.push(name.to_string());
+    Ok(())
+}
+
+// Drives one dimension of a constraint by an expression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DimensionBinding {
+    constraint: ConstraintCell,
+    index: usize,
+    expression: Expression,
+}
+
+impl DimensionBinding {
+    pub fn new(constraint: ConstraintCell, index: usize, expression: Expression) -> Self {
+        Self {
+            constraint,
+            index,
+            expression,
+        }
+    }
+
+    pub fn constraint(&self) -> ConstraintCell {
+        self.constraint.clone()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn expression(&self) -> Expression {
+        self.expression.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::error::ISOTopeError;
+
+    use super::{expression::Expression, Parameters};
+
+    #[test]
+    fn test_parameters() -> Result<(), Box<dyn Error>> {
+        let mut parameters = Parameters::new();
+        parameters
+            .set("hole_d", Expression::parse("width / 8")?)
+            .unwrap_err();
+        parameters.set("width", Expression::parse("40")?)?;
+        parameters.set("hole_d", Expression::parse("width / 8")?)?;
+        parameters.set("margin", Expression::parse("-(hole_d + 1) * 2 ^ 2")?)?;
+        assert_eq!(parameters.value("hole_d"), Some(5.0));
+        assert_eq!(parameters.value("margin"), Some(-24.0));
+
+        // Changing a parameter updates everything that depends on it
+        parameters.set("width", Expression::parse("max(16, sqrt(64))")?)?;
+        assert_eq!(parameters.value("hole_d"), Some(2.0));
+        assert_eq!(parameters.value("margin"), Some(-12.0));
+        assert_eq!(parameters.names(), vec!["width", "hole_d", "margin"]);
+
+        assert!(matches!(
+            parameters.remove("width"),
+            Err(ISOTopeError::ParameterInUse(_))
+        ));
+        parameters.remove("margin")?;
+        assert_eq!(parameters.value("margin"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cyclic_parameters() -> Result<(), Box<dyn Error>> {
+        let mut parameters = Parameters::new();
+        parameters.set("a", Expression::parse("1")?)?;
+        parameters.set("b", Expression::parse("a + 1")?)?;
+
+        match parameters.set("a", Expression::parse("b * 2")?) {
+            Err(ISOTopeError::CyclicParameters(cycle)) => assert_eq!(cycle, "a -> b -> a"),
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        // The table is unchanged
+        assert_eq!(parameters.value("a"), Some(1.0));
+        assert_eq!(parameters.value("b"), Some(2.0));
+
+        assert!(parameters
+            .set("c", Expression::parse("1 / (a - 1)")?)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_print_expression() -> Result<(), Box<dyn Error>> {
+        for source in [
+            "width / 8.0",
+            "-(a + b) * c",
+            "a - (b - c)",
+            "a ^ b ^ c",
+            "(a ^ b) ^ c",
+            "atan2(a, -b) + -2.5",
+        ] {
+            assert_eq!(Expression::parse(source)?.to_string(), source);
+        }
+        Ok(())
+    }
+}
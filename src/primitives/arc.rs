@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::f64::consts::PI;
 use std::rc::Rc;
 
 use nalgebra::{DVectorView, SMatrix, SMatrixView, SVector, Vector2};
@@ -18,6 +19,9 @@ pub struct Arc {
     pub gradient: SVector<f64, 3>,
 
     pub clockwise: bool,
+
+    #[serde(default)]
+    pub construction: bool,
 }
 
 impl Arc {
@@ -34,6 +38,7 @@ impl Arc {
             gradient: SVector::<f64, 3>::zeros(),
 
             clockwise,
+            construction: false,
         }
     }
 
@@ -158,6 +163,73 @@ impl Arc {
         ])
     }
 
+    // The angle swept from the start to the end in the direction of the arc, in [0, 2 * PI)
+    pub fn sweep_angle(&self) -> f64 {
+        let sweep = if self.clockwise {
+            self.start_angle() - self.end_angle()
+        } else {
+            self.end_angle() - self.start_angle()
+        };
+        sweep.rem_euclid(2.0 * PI)
+    }
+
+    pub fn sweep_angle_gradient(&self) -> SMatrix<f64, 1, 5> {
+        if self.clockwise {
+            self.start_angle_gradient() - self.end_angle_gradient()
+        } else {
+            self.end_angle_gradient() - self.start_angle_gradient()
+        }
+    }
+
+    pub fn arc_length(&self) -> f64 {
+        self.radius() * self.sweep_angle()
+    }
+
+    pub fn arc_length_gradient(&self) -> SMatrix<f64, 1, 5> {
+        self.sweep_angle() * self.radius_gradient() + self.radius() * self.sweep_angle_gradient()
+    }
+
+    // The angle halfway along the arc. Its gradient by both the start and the end angle is 1/2.
+    pub fn mid_angle(&self) -> f64 {
+        if self.clockwise {
+            self.start_angle() - 0.5 * self.sweep_angle()
+        } else {
+            self.start_angle() + 0.5 * self.sweep_angle()
+        }
+    }
+
+    pub fn mid_point(&self) -> Vector2<f64> {
+        let center = self.center.borrow().data();
+        let angle = self.mid_angle();
+        center + self.radius() * Vector2::new(angle.cos(), angle.sin())
+    }
+
+    pub fn mid_point_gradient(&self) -> SMatrix<f64, 2, 5> {
+        let radius = self.radius();
+        let angle = self.mid_angle();
+
+        SMatrix::<f64, 2, 5>::from_row_slice(&[
+            1.0,
+            0.0,
+            angle.cos(),
+            -0.5 * radius * angle.sin(),
+            -0.5 * radius * angle.sin(),
+            0.0,
+            1.0,
+            angle.sin(),
+            0.5 * radius * angle.cos(),
+            0.5 * radius * angle.cos(),
+        ])
+    }
+
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
     pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 5>) {
         // Panic if nan or inf is encountered
         assert!(gradient.iter().all(|x| x.is_finite()));
@@ -177,15 +249,15 @@ impl PrimitiveLike for Arc {
         self.gradient = SVector::<f64, 3>::zeros();
     }
 
-    fn get_data(&self) -> DVectorView<f64> {
+    fn get_data(&self) -> DVectorView<'_, f64> {
         self.data.as_view()
     }
 
-    fn get_gradient(&self) -> DVectorView<f64> {
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
         self.gradient.as_view()
     }
 
-    fn set_data(&mut self, data: DVectorView<f64>) {
+    fn set_data(&mut self, data: DVectorView<'_, f64>) {
         assert!(data.iter().all(|x| x.is_finite()));
         self.data = SVector::from_row_slice(data.as_slice());
     }
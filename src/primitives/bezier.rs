@@ -0,0 +1,400 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nalgebra::{DVectorView, SMatrix, SMatrixView, SVector, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use super::point2::Point2;
+use super::{PrimitiveCell, PrimitiveLike};
+
+// Nodes and weights of the 8 point Gauss-Legendre quadrature on [-1, 1], used for the arc length.
+const GAUSS_LEGENDRE: [(f64, f64); 8] = [
+    (-0.9602898564975363, 0.10122853629037618),
+    (-0.7966664774136268, 0.22238103445337445),
+    (-0.525532409916329, 0.3137066458778874),
+    (-0.1834346424956498, 0.362683783378362),
+    (0.1834346424956498, 0.362683783378362),
+    (0.525532409916329, 0.3137066458778874),
+    (0.7966664774136268, 0.22238103445337445),
+    (0.9602898564975363, 0.10122853629037618),
+];
+
+// A cubic Bézier curve from `start` to `end` with the two inner control points `control1` and
+// `control2`. Like a line it only references points, the gradients are ordered
+// [start, control1, control2, end].
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Bezier {
+    start: Rc<RefCell<Point2>>,
+    control1: Rc<RefCell<Point2>>,
+    control2: Rc<RefCell<Point2>>,
+    end: Rc<RefCell<Point2>>,
+    empty: SVector<f64, 0>,
+
+    #[serde(default)]
+    construction: bool,
+}
+
+impl Bezier {
+    pub fn new(
+        start: Rc<RefCell<Point2>>,
+        control1: Rc<RefCell<Point2>>,
+        control2: Rc<RefCell<Point2>>,
+        end: Rc<RefCell<Point2>>,
+    ) -> Self {
+        Self {
+            start,
+            control1,
+            control2,
+            end,
+            empty: SVector::<f64, 0>::zeros(),
+            construction: false,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        Bezier::new(
+            self.end.clone(),
+            self.control2.clone(),
+            self.control1.clone(),
+            self.start.clone(),
+        )
+    }
+
+    pub fn start(&self) -> Rc<RefCell<Point2>> {
+        self.start.clone()
+    }
+
+    pub fn set_start(&mut self, start: Rc<RefCell<Point2>>) {
+        self.start = start;
+    }
+
+    pub fn control1(&self) -> Rc<RefCell<Point2>> {
+        self.control1.clone()
+    }
+
+    pub fn set_control1(&mut self, control1: Rc<RefCell<Point2>>) {
+        self.control1 = control1;
+    }
+
+    pub fn control2(&self) -> Rc<RefCell<Point2>> {
+        self.control2.clone()
+    }
+
+    pub fn set_control2(&mut self, control2: Rc<RefCell<Point2>>) {
+        self.control2 = control2;
+    }
+
+    pub fn end(&self) -> Rc<RefCell<Point2>> {
+        self.end.clone()
+    }
+
+    pub fn set_end(&mut self, end: Rc<RefCell<Point2>>) {
+        self.end = end;
+    }
+
+    pub fn start_gradient(&self) -> SMatrix<f64, 2, 8> {
+        Self::expand([1.0, 0.0, 0.0, 0.0])
+    }
+
+    pub fn end_gradient(&self) -> SMatrix<f64, 2, 8> {
+        Self::expand([0.0, 0.0, 0.0, 1.0])
+    }
+
+    fn points(&self) -> [Vector2<f64>; 4] {
+        [
+            self.start.borrow().data(),
+            self.control1.borrow().data(),
+            self.control2.borrow().data(),
+            self.end.borrow().data(),
+        ]
+    }
+
+    fn combine(&self, coefficients: [f64; 4]) -> Vector2<f64> {
+        self.points()
+            .iter()
+            .zip(coefficients.iter())
+            .map(|(point, c)| *c * point)
+            .sum()
+    }
+
+    // Expands the coefficients c_i to the gradient [c_0 * I, c_1 * I, c_2 * I, c_3 * I] of a point
+    // by the control points
+    fn expand(coefficients: [f64; 4]) -> SMatrix<f64, 2, 8> {
+        let mut gradient = SMatrix::<f64, 2, 8>::zeros();
+        for (i, c) in coefficients.iter().enumerate() {
+            gradient[(0, 2 * i)] = *c;
+            gradient[(1, 2 * i + 1)] = *c;
+        }
+        gradient
+    }
+
+    // Bernstein polynomials of degree 3 and their first and second derivatives
+    fn basis(t: f64) -> [f64; 4] {
+        let s = 1.0 - t;
+        [s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t]
+    }
+
+    fn basis_derivative(t: f64) -> [f64; 4] {
+        let s = 1.0 - t;
+        [
+            -3.0 * s * s,
+            3.0 * s * s - 6.0 * s * t,
+            6.0 * s * t - 3.0 * t * t,
+            3.0 * t * t,
+        ]
+    }
+
+    fn basis_second_derivative(t: f64) -> [f64; 4] {
+        let s = 1.0 - t;
+        [6.0 * s, 6.0 * t - 12.0 * s, 6.0 * s - 12.0 * t, 6.0 * t]
+    }
+
+    pub fn point_at(&self, t: f64) -> Vector2<f64> {
+        self.combine(Self::basis(t))
+    }
+
+    pub fn point_at_gradient(&self, t: f64) -> SMatrix<f64, 2, 8> {
+        Self::expand(Self::basis(t))
+    }
+
+    pub fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        self.combine(Self::basis_derivative(t))
+    }
+
+    pub fn derivative_at_gradient(&self, t: f64) -> SMatrix<f64, 2, 8> {
+        Self::expand(Self::basis_derivative(t))
+    }
+
+    pub fn second_derivative_at(&self, t: f64) -> Vector2<f64> {
+        self.combine(Self::basis_second_derivative(t))
+    }
+
+    pub fn second_derivative_at_gradient(&self, t: f64) -> SMatrix<f64, 2, 8> {
+        Self::expand(Self::basis_second_derivative(t))
+    }
+
+    // The length of the curve between the parameters t0 and t1
+    pub fn arc_length_between(&self, t0: f64, t1: f64) -> f64 {
+        let half = 0.5 * (t1 - t0);
+        let mid = 0.5 * (t0 + t1);
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|(x, w)| w * half * self.derivative_at(mid + half * x).norm())
+            .sum()
+    }
+
+    pub fn arc_length(&self) -> f64 {
+        self.arc_length_between(0.0, 1.0)
+    }
+
+    pub fn arc_length_gradient(&self) -> SMatrix<f64, 1, 8> {
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|(x, w)| {
+                let t = 0.5 + 0.5 * x;
+                let derivative = self.derivative_at(t);
+                let norm = derivative.norm();
+                if norm < 1e-12 {
+                    return SMatrix::<f64, 1, 8>::zeros();
+                }
+                0.5 * w / norm * derivative.transpose() * self.derivative_at_gradient(t)
+            })
+            .sum()
+    }
+
+    // The parameter at which the curve has the given length from its start, found with Newton's
+    // method and clamped to [0, 1].
+    pub fn parameter_at_length(&self, length: f64) -> f64 {
+        let total = self.arc_length();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let mut t = (length / total).clamp(0.0, 1.0);
+        for _ in 0..16 {
+            let error = self.arc_length_between(0.0, t) - length;
+            let speed = self.derivative_at(t).norm();
+            if error.abs() < 1e-12 * total || speed < 1e-12 {
+                break;
+            }
+            t = (t - error / speed).clamp(0.0, 1.0);
+        }
+        t
+    }
+
+    // Points along the curve, evenly spaced in the parameter. Includes both end points.
+    pub fn tessellate(&self, num_points: usize) -> Vec<Vector2<f64>> {
+        let num_points = num_points.max(2);
+        (0..num_points)
+            .map(|i| self.point_at(i as f64 / (num_points - 1) as f64))
+            .collect()
+    }
+
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
+    pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 8>) {
+        // Panic if nan or inf is encountered
+        assert!(gradient.iter().all(|x| x.is_finite()));
+        self.start
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 0));
+        self.control1
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 2));
+        self.control2
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 4));
+        self.end
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 6));
+    }
+}
+
+impl PrimitiveLike for Bezier {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![
+            PrimitiveCell::Point2(self.start.clone()),
+            PrimitiveCell::Point2(self.control1.clone()),
+            PrimitiveCell::Point2(self.control2.clone()),
+            PrimitiveCell::Point2(self.end.clone()),
+        ]
+    }
+
+    fn zero_gradient(&mut self) {
+        // Referenced points will zero their gradients automatically as they are part of the sketch
+    }
+
+    fn get_data(&self) -> DVectorView<'_, f64> {
+        // empty vector
+        self.empty.as_view()
+    }
+
+    fn set_data(&mut self, _data: DVectorView<'_, f64>) {
+        // Do nothing
+    }
+
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
+        // empty vector
+        self.empty.as_view()
+    }
+
+    fn to_primitive(&self) -> super::Primitive {
+        super::Primitive::Bezier(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use nalgebra::{SMatrix, SVector, Vector2};
+
+    use crate::primitives::{bezier::Bezier, point2::Point2};
+
+    fn bezier(coordinates: [(f64, f64); 4]) -> Bezier {
+        let [start, control1, control2, end] =
+            coordinates.map(|(x, y)| Rc::new(RefCell::new(Point2::new(x, y))));
+        Bezier::new(start, control1, control2, end)
+    }
+
+    // Central differences of a value of the curve by the control points, in the order
+    // [start, control1, control2, end]
+    fn numerical_gradient<const R: usize>(
+        bezier: &Bezier,
+        value: impl Fn(&Bezier) -> SVector<f64, R>,
+    ) -> SMatrix<f64, R, 8> {
+        let eps = 1e-6;
+        let points = [
+            bezier.start(),
+            bezier.control1(),
+            bezier.control2(),
+            bezier.end(),
+        ];
+        SMatrix::<f64, R, 8>::from_columns(&std::array::from_fn::<_, 8, _>(|column| {
+            let point = &points[column / 2];
+            let shift = |delta: f64| {
+                let moved = point.borrow().data()[column % 2] + delta;
+                if column % 2 == 0 {
+                    point.borrow_mut().set_x(moved);
+                } else {
+                    point.borrow_mut().set_y(moved);
+                }
+            };
+            shift(eps);
+            let plus = value(bezier);
+            shift(-2.0 * eps);
+            let minus = value(bezier);
+            shift(eps);
+            (plus - minus) / (2.0 * eps)
+        }))
+    }
+
+    #[test]
+    fn test_point_at() {
+        let bezier = bezier([(0.0, 0.0), (1.0, 2.0), (3.0, 2.0), (4.0, 0.0)]);
+        assert!((bezier.point_at(0.0) - Vector2::new(0.0, 0.0)).norm() < 1e-12);
+        assert!((bezier.point_at(1.0) - Vector2::new(4.0, 0.0)).norm() < 1e-12);
+        // (P0 + 3 P1 + 3 P2 + P3) / 8
+        assert!((bezier.point_at(0.5) - Vector2::new(2.0, 1.5)).norm() < 1e-12);
+
+        let reversed = bezier.reverse();
+        for t in [0.0, 0.2, 0.5, 0.9] {
+            assert!((reversed.point_at(1.0 - t) - bezier.point_at(t)).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_straight_arc_length() {
+        // Unevenly spaced control points on a line, so the speed along the curve varies
+        let bezier = bezier([(0.0, 0.0), (0.3, 0.4), (2.4, 3.2), (3.0, 4.0)]);
+        assert!((bezier.arc_length() - 5.0).abs() < 1e-12);
+        assert!((bezier.arc_length_between(0.0, 0.5) - bezier.point_at(0.5).norm()).abs() < 1e-12);
+
+        assert_eq!(bezier.parameter_at_length(0.0), 0.0);
+        assert!((bezier.parameter_at_length(5.0) - 1.0).abs() < 1e-12);
+        for length in [0.5, 1.0, 2.5, 4.5] {
+            let t = bezier.parameter_at_length(length);
+            assert!((bezier.point_at(t).norm() - length).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_derivatives() {
+        let bezier = bezier([(0.0, 0.0), (1.0, 2.0), (3.5, 2.5), (4.0, -1.0)]);
+        let eps = 1e-6;
+        for t in [0.0, 0.3, 0.5, 1.0] {
+            let numerical = (bezier.point_at(t + eps) - bezier.point_at(t - eps)) / (2.0 * eps);
+            assert!((bezier.derivative_at(t) - numerical).norm() < 1e-6);
+            let numerical =
+                (bezier.derivative_at(t + eps) - bezier.derivative_at(t - eps)) / (2.0 * eps);
+            assert!((bezier.second_derivative_at(t) - numerical).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_control_point_gradients() {
+        let bezier = bezier([(0.0, 0.0), (1.0, 2.0), (3.5, 2.5), (4.0, -1.0)]);
+        for t in [0.0, 0.3, 1.0] {
+            let numerical = numerical_gradient(&bezier, |b| b.point_at(t));
+            assert!((bezier.point_at_gradient(t) - numerical).norm() < 1e-6);
+            let numerical = numerical_gradient(&bezier, |b| b.derivative_at(t));
+            assert!((bezier.derivative_at_gradient(t) - numerical).norm() < 1e-6);
+            let numerical = numerical_gradient(&bezier, |b| b.second_derivative_at(t));
+            assert!((bezier.second_derivative_at_gradient(t) - numerical).norm() < 1e-6);
+        }
+        let numerical = numerical_gradient(&bezier, |b| SVector::<f64, 1>::new(b.arc_length()));
+        assert!((bezier.arc_length_gradient() - numerical).norm() < 1e-6);
+    }
+}
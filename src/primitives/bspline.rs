@@ -0,0 +1,489 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nalgebra::{DVector, DVectorView, Matrix2xX, MatrixView1xX, SVector, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use crate::error::ISOTopeError;
+
+use super::{point2::Point2, PrimitiveCell, PrimitiveLike};
+
+// A (rational) B-spline curve. The control points are references like the end points of a line, so
+// the solver moves the curve by moving its control points. Degree, knots and weights are fixed.
+// Without weights this is a plain B-spline, with weights it is a NURBS curve.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct BSpline {
+    control_points: Vec<Rc<RefCell<Point2>>>,
+    degree: usize,
+    knots: Vec<f64>,
+    weights: Option<Vec<f64>>,
+    empty: SVector<f64, 0>,
+
+    #[serde(default)]
+    construction: bool,
+}
+
+impl BSpline {
+    pub fn new(
+        control_points: Vec<Rc<RefCell<Point2>>>,
+        degree: usize,
+        knots: Vec<f64>,
+        weights: Option<Vec<f64>>,
+    ) -> Result<Self, ISOTopeError> {
+        let n = control_points.len();
+        if degree == 0 {
+            return Err(ISOTopeError::InvalidBSpline(
+                "the degree must be at least 1".to_string(),
+            ));
+        }
+        if n < degree + 1 {
+            return Err(ISOTopeError::InvalidBSpline(format!(
+                "a curve of degree {} needs at least {} control points",
+                degree,
+                degree + 1
+            )));
+        }
+        if knots.len() != n + degree + 1 {
+            return Err(ISOTopeError::InvalidBSpline(format!(
+                "expected {} knots, got {}",
+                n + degree + 1,
+                knots.len()
+            )));
+        }
+        if knots.iter().any(|k| !k.is_finite()) || knots.windows(2).any(|w| w[0] > w[1]) {
+            return Err(ISOTopeError::InvalidBSpline(
+                "the knots must be finite and non-decreasing".to_string(),
+            ));
+        }
+        if knots[degree] >= knots[n] {
+            return Err(ISOTopeError::InvalidBSpline(
+                "the knots must span a non-empty domain".to_string(),
+            ));
+        }
+        if let Some(weights) = &weights {
+            if weights.len() != n {
+                return Err(ISOTopeError::InvalidBSpline(format!(
+                    "expected {} weights, got {}",
+                    n,
+                    weights.len()
+                )));
+            }
+            if weights.iter().any(|w| !w.is_finite() || *w <= 0.0) {
+                return Err(ISOTopeError::InvalidBSpline(
+                    "the weights must be positive".to_string(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            control_points,
+            degree,
+            knots,
+            weights,
+            empty: SVector::<f64, 0>::zeros(),
+            construction: false,
+        })
+    }
+
+    // A clamped B-spline with uniform knots on [0, 1]. It starts in the first and ends in the last
+    // control point.
+    pub fn clamped(
+        control_points: Vec<Rc<RefCell<Point2>>>,
+        degree: usize,
+    ) -> Result<Self, ISOTopeError> {
+        let spans = control_points.len().saturating_sub(degree).max(1);
+        let mut knots = vec![0.0; degree + 1];
+        knots.extend((1..spans).map(|i| i as f64 / spans as f64));
+        knots.extend(vec![1.0; degree + 1]);
+        Self::new(control_points, degree, knots, None)
+    }
+
+    pub fn reverse(&self) -> Self {
+        let first = self.start_parameter();
+        let last = self.end_parameter();
+        let mut control_points = self.control_points.clone();
+        control_points.reverse();
+        let knots = self.knots.iter().rev().map(|k| first + last - k).collect();
+        let weights = self
+            .weights
+            .as_ref()
+            .map(|w| w.iter().rev().copied().collect());
+        Self {
+            control_points,
+            degree: self.degree,
+            knots,
+            weights,
+            empty: SVector::<f64, 0>::zeros(),
+            construction: self.construction,
+        }
+    }
+
+    pub fn control_points(&self) -> Vec<Rc<RefCell<Point2>>> {
+        self.control_points.clone()
+    }
+
+    pub fn set_control_points(&mut self, control_points: Vec<Rc<RefCell<Point2>>>) {
+        assert!(control_points.len() == self.control_points.len());
+        self.control_points = control_points;
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn knots(&self) -> Vec<f64> {
+        self.knots.clone()
+    }
+
+    pub fn weights(&self) -> Option<Vec<f64>> {
+        self.weights.clone()
+    }
+
+    pub fn start_parameter(&self) -> f64 {
+        self.knots[self.degree]
+    }
+
+    pub fn end_parameter(&self) -> f64 {
+        self.knots[self.control_points.len()]
+    }
+
+    // The basis functions N_i(t) of the curve and their derivatives, computed with the Cox-de Boor
+    // recursion. The parameter is clamped to the domain of the curve.
+    fn basis(&self, t: f64) -> (DVector<f64>, DVector<f64>) {
+        let n = self.control_points.len();
+        let p = self.degree;
+        let u = &self.knots;
+        let t = t.clamp(self.start_parameter(), self.end_parameter());
+
+        // The last non-empty knot span containing t, so the end of the domain is included
+        let mut span = p;
+        for k in p..n {
+            if u[k] <= t && u[k] < u[k + 1] {
+                span = k;
+            }
+        }
+
+        let ratio = |a: f64, b: f64| if b == 0.0 { 0.0 } else { a / b };
+
+        let mut values = DVector::<f64>::zeros(n + p);
+        values[span] = 1.0;
+        let mut derivatives = DVector::<f64>::zeros(n);
+        for d in 1..=p {
+            let previous = values.clone();
+            values = DVector::<f64>::zeros(n + p - d);
+            for i in 0..n + p - d {
+                values[i] = ratio(t - u[i], u[i + d] - u[i]) * previous[i]
+                    + ratio(u[i + d + 1] - t, u[i + d + 1] - u[i + 1]) * previous[i + 1];
+            }
+            if d == p {
+                for i in 0..n {
+                    derivatives[i] = ratio(p as f64, u[i + p] - u[i]) * previous[i]
+                        - ratio(p as f64, u[i + p + 1] - u[i + 1]) * previous[i + 1];
+                }
+            }
+        }
+
+        (values, derivatives)
+    }
+
+    // The rational basis functions R_i(t) = w_i N_i(t) / W(t) and their derivatives. The curve is
+    // C(t) = sum R_i(t) P_i, so these are also the gradients of the curve by the control points.
+    fn rational_basis(&self, t: f64) -> (DVector<f64>, DVector<f64>) {
+        let (values, derivatives) = self.basis(t);
+        let Some(weights) = &self.weights else {
+            return (values, derivatives);
+        };
+
+        let weights = DVector::from_column_slice(weights);
+        let weighted_values = values.component_mul(&weights);
+        let weighted_derivatives = derivatives.component_mul(&weights);
+        let w = weighted_values.sum();
+        let dw = weighted_derivatives.sum();
+
+        let rational_values = weighted_values / w;
+        let rational_derivatives = (weighted_derivatives - &rational_values * dw) / w;
+        (rational_values, rational_derivatives)
+    }
+
+    fn combine(&self, coefficients: &DVector<f64>) -> Vector2<f64> {
+        self.control_points
+            .iter()
+            .zip(coefficients.iter())
+            .map(|(point, c)| *c * point.borrow().data())
+            .sum()
+    }
+
+    // Expands the coefficients c_i to the gradient [c_0 * I, c_1 * I, ...] of a point by the
+    // control points
+    fn expand(coefficients: &DVector<f64>) -> Matrix2xX<f64> {
+        let mut gradient = Matrix2xX::<f64>::zeros(2 * coefficients.len());
+        for (i, c) in coefficients.iter().enumerate() {
+            gradient[(0, 2 * i)] = *c;
+            gradient[(1, 2 * i + 1)] = *c;
+        }
+        gradient
+    }
+
+    pub fn point_at(&self, t: f64) -> Vector2<f64> {
+        self.combine(&self.rational_basis(t).0)
+    }
+
+    pub fn point_at_gradient(&self, t: f64) -> Matrix2xX<f64> {
+        Self::expand(&self.rational_basis(t).0)
+    }
+
+    // The first derivative dC/dt of the curve
+    pub fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        self.combine(&self.rational_basis(t).1)
+    }
+
+    pub fn derivative_at_gradient(&self, t: f64) -> Matrix2xX<f64> {
+        Self::expand(&self.rational_basis(t).1)
+    }
+
+    pub fn start_point(&self) -> Vector2<f64> {
+        self.point_at(self.start_parameter())
+    }
+
+    pub fn start_point_gradient(&self) -> Matrix2xX<f64> {
+        self.point_at_gradient(self.start_parameter())
+    }
+
+    pub fn end_point(&self) -> Vector2<f64> {
+        self.point_at(self.end_parameter())
+    }
+
+    pub fn end_point_gradient(&self) -> Matrix2xX<f64> {
+        self.point_at_gradient(self.end_parameter())
+    }
+
+    // Points along the curve, evenly spaced in the parameter. Includes both end points.
+    pub fn tessellate(&self, num_points: usize) -> Vec<Vector2<f64>> {
+        let num_points = num_points.max(2);
+        let first = self.start_parameter();
+        let last = self.end_parameter();
+        (0..num_points)
+            .map(|i| self.point_at(first + (last - first) * i as f64 / (num_points - 1) as f64))
+            .collect()
+    }
+
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
+    pub fn add_to_gradient(&mut self, gradient: MatrixView1xX<f64>) {
+        // Panic if nan or inf is encountered
+        assert!(gradient.iter().all(|x| x.is_finite()));
+        for (i, point) in self.control_points.iter().enumerate() {
+            point
+                .borrow_mut()
+                .add_to_gradient(gradient.fixed_view::<1, 2>(0, 2 * i));
+        }
+    }
+}
+
+impl PrimitiveLike for BSpline {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        self.control_points
+            .iter()
+            .map(|point| PrimitiveCell::Point2(point.clone()))
+            .collect()
+    }
+
+    fn zero_gradient(&mut self) {
+        // Referenced points will zero their gradients automatically as they are part of the sketch
+    }
+
+    fn get_data(&self) -> DVectorView<'_, f64> {
+        // empty vector
+        self.empty.as_view()
+    }
+
+    fn set_data(&mut self, _data: DVectorView<'_, f64>) {
+        // Do nothing
+    }
+
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
+        // empty vector
+        self.empty.as_view()
+    }
+
+    fn to_primitive(&self) -> super::Primitive {
+        super::Primitive::BSpline(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::FRAC_1_SQRT_2;
+    use std::rc::Rc;
+
+    use nalgebra::{Matrix2xX, Vector2};
+
+    use crate::{
+        error::ISOTopeError,
+        primitives::{bspline::BSpline, point2::Point2},
+    };
+
+    fn points(coordinates: &[(f64, f64)]) -> Vec<Rc<RefCell<Point2>>> {
+        coordinates
+            .iter()
+            .map(|(x, y)| Rc::new(RefCell::new(Point2::new(*x, *y))))
+            .collect()
+    }
+
+    // A rational cubic with interior knots that are neither uniform nor simple
+    fn cubic() -> BSpline {
+        BSpline::new(
+            points(&[
+                (0.0, 0.0),
+                (1.0, 2.0),
+                (2.5, 2.5),
+                (4.0, 0.5),
+                (5.0, 1.0),
+                (6.0, 3.0),
+            ]),
+            3,
+            vec![0.0, 0.0, 0.0, 0.0, 0.3, 0.3, 1.0, 1.0, 1.0, 1.0],
+            Some(vec![1.0, 0.5, 2.0, 1.0, 0.8, 1.0]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_invalid_knots_and_degrees() {
+        let invalid = |num_points: usize, degree, knots: &[f64], weights: Option<Vec<f64>>| {
+            let control_points = points(&vec![(0.0, 0.0); num_points]);
+            matches!(
+                BSpline::new(control_points, degree, knots.to_vec(), weights),
+                Err(ISOTopeError::InvalidBSpline(_))
+            )
+        };
+        let knots = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+
+        assert!(!invalid(3, 2, &knots, None));
+        assert!(!invalid(3, 2, &knots, Some(vec![1.0, 2.0, 1.0])));
+        // Degree zero and too few control points for the degree
+        assert!(invalid(6, 0, &knots, None));
+        assert!(invalid(2, 3, &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0], None));
+        // Wrong number of knots
+        assert!(invalid(3, 2, &knots[1..], None));
+        // Decreasing and non-finite knots
+        assert!(invalid(3, 2, &[0.0, 0.0, 1.0, 0.5, 1.0, 1.0], None));
+        assert!(invalid(3, 2, &[0.0, 0.0, 0.0, f64::NAN, 1.0, 1.0], None));
+        // An empty domain
+        assert!(invalid(3, 2, &[0.0, 1.0, 1.0, 1.0, 1.0, 2.0], None));
+        // Wrong number of weights and weights that aren't positive
+        assert!(invalid(3, 2, &knots, Some(vec![1.0, 1.0])));
+        assert!(invalid(3, 2, &knots, Some(vec![1.0, 0.0, 1.0])));
+        assert!(invalid(3, 2, &knots, Some(vec![1.0, f64::INFINITY, 1.0])));
+    }
+
+    #[test]
+    fn test_clamped_evaluation() {
+        let spline = BSpline::clamped(points(&[(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)]), 2).unwrap();
+        assert_eq!(spline.knots(), vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        assert!((spline.start_point() - Vector2::new(0.0, 0.0)).norm() < 1e-12);
+        assert!((spline.end_point() - Vector2::new(2.0, 0.0)).norm() < 1e-12);
+        // A single span of a quadratic is the Bezier curve 0.25 P0 + 0.5 P1 + 0.25 P2 at t = 0.5
+        assert!((spline.point_at(0.5) - Vector2::new(1.0, 1.0)).norm() < 1e-12);
+
+        // With more control points the curve still starts and ends on them
+        let spline = BSpline::clamped(
+            points(&[(0.0, 0.0), (1.0, 2.0), (2.0, 0.0), (3.0, 2.0), (4.0, 0.0)]),
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            spline.knots(),
+            vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0]
+        );
+        assert!((spline.start_point() - Vector2::new(0.0, 0.0)).norm() < 1e-12);
+        assert!((spline.end_point() - Vector2::new(4.0, 0.0)).norm() < 1e-12);
+        // At the interior knot only the middle three basis functions are non-zero: 1/4, 1/2, 1/4
+        assert!((spline.point_at(0.5) - Vector2::new(2.0, 1.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_nurbs_quarter_circle() {
+        let spline = BSpline::new(
+            points(&[(1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]),
+            2,
+            vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            Some(vec![1.0, FRAC_1_SQRT_2, 1.0]),
+        )
+        .unwrap();
+        for point in spline.tessellate(17) {
+            assert!((point.norm() - 1.0).abs() < 1e-12);
+        }
+        assert!((spline.point_at(0.5) - Vector2::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let spline = cubic();
+        let reversed = spline.reverse();
+        for t in [0.0, 0.1, 0.3, 0.6, 1.0] {
+            assert!((reversed.point_at(1.0 - t) - spline.point_at(t)).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_derivative_at() {
+        let spline = cubic();
+        let eps = 1e-6;
+        for t in [0.05, 0.2, 0.5, 0.9] {
+            let numerical = (spline.point_at(t + eps) - spline.point_at(t - eps)) / (2.0 * eps);
+            assert!((spline.derivative_at(t) - numerical).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_control_point_gradients() {
+        let spline = cubic();
+        let eps = 1e-6;
+        for t in [0.0, 0.2, 0.5, 1.0] {
+            // Central differences by moving one coordinate of one control point at a time
+            let mut point_gradient = Matrix2xX::<f64>::zeros(12);
+            let mut derivative_gradient = Matrix2xX::<f64>::zeros(12);
+            for (i, point) in spline.control_points().iter().enumerate() {
+                for axis in 0..2 {
+                    let shift = |delta: f64| {
+                        let moved = point.borrow().data()[axis] + delta;
+                        if axis == 0 {
+                            point.borrow_mut().set_x(moved);
+                        } else {
+                            point.borrow_mut().set_y(moved);
+                        }
+                    };
+                    shift(eps);
+                    let (point_plus, derivative_plus) =
+                        (spline.point_at(t), spline.derivative_at(t));
+                    shift(-2.0 * eps);
+                    let (point_minus, derivative_minus) =
+                        (spline.point_at(t), spline.derivative_at(t));
+                    shift(eps);
+                    point_gradient
+                        .set_column(2 * i + axis, &((point_plus - point_minus) / (2.0 * eps)));
+                    derivative_gradient.set_column(
+                        2 * i + axis,
+                        &((derivative_plus - derivative_minus) / (2.0 * eps)),
+                    );
+                }
+            }
+            assert!((spline.point_at_gradient(t) - point_gradient).norm() < 1e-6);
+            assert!((spline.derivative_at_gradient(t) - derivative_gradient).norm() < 1e-6);
+        }
+    }
+}
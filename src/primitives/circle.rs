@@ -16,6 +16,9 @@ pub struct Circle {
     center: Rc<RefCell<Point2>>,
     data: SVector<f64, 1>,
     gradient: SVector<f64, 1>,
+
+    #[serde(default)]
+    construction: bool,
 }
 
 impl Circle {
@@ -24,6 +27,7 @@ impl Circle {
             center,
             data: SVector::<f64, 1>::from_row_slice(&[radius]),
             gradient: SVector::<f64, 1>::zeros(),
+            construction: false,
         }
     }
 
@@ -51,6 +55,14 @@ impl Circle {
         SMatrix::<f64, 1, 3>::from_row_slice(&[0.0, 0.0, 1.0])
     }
 
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
     pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 3>) {
         // Panic if nan or inf is encountered
         assert!(gradient.iter().all(|x| x.is_finite()));
@@ -70,16 +82,16 @@ impl PrimitiveLike for Circle {
         self.gradient = SVector::<f64, 1>::zeros();
     }
 
-    fn get_data(&self) -> DVectorView<f64> {
+    fn get_data(&self) -> DVectorView<'_, f64> {
         self.data.as_view()
     }
 
-    fn set_data(&mut self, data: DVectorView<f64>) {
+    fn set_data(&mut self, data: DVectorView<'_, f64>) {
         assert!(data.iter().all(|x| x.is_finite()));
         self.data.copy_from(&data);
     }
 
-    fn get_gradient(&self) -> DVectorView<f64> {
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
         self.gradient.as_view()
     }
 
@@ -0,0 +1,217 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nalgebra::{DVectorView, SMatrix, SMatrixView, SVector, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use super::{point2::Point2, PrimitiveCell, PrimitiveLike};
+
+// An ellipse with its own axes rotated by `rotation` against the sketch x-axis. Points on the
+// ellipse are parameterized as center + R(rotation) * [radius_x * cos(t), radius_y * sin(t)].
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Ellipse {
+    center: Rc<RefCell<Point2>>,
+    data: SVector<f64, 3>,
+    gradient: SVector<f64, 3>,
+
+    #[serde(default)]
+    construction: bool,
+}
+
+impl Ellipse {
+    pub fn new(center: Rc<RefCell<Point2>>, radius_x: f64, radius_y: f64, rotation: f64) -> Self {
+        Self {
+            center,
+            data: SVector::<f64, 3>::from_row_slice(&[radius_x, radius_y, rotation]),
+            gradient: SVector::<f64, 3>::zeros(),
+            construction: false,
+        }
+    }
+
+    pub fn center(&self) -> Rc<RefCell<Point2>> {
+        self.center.clone()
+    }
+
+    pub fn set_center(&mut self, center: Rc<RefCell<Point2>>) {
+        self.center = center;
+    }
+
+    pub fn center_gradient(&self) -> SMatrix<f64, 2, 5> {
+        SMatrix::<f64, 2, 5>::from_row_slice(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0])
+    }
+
+    pub fn radius_x(&self) -> f64 {
+        self.data[0]
+    }
+
+    pub fn set_radius_x(&mut self, radius_x: f64) {
+        self.data[0] = radius_x;
+    }
+
+    pub fn radius_x_gradient(&self) -> SMatrix<f64, 1, 5> {
+        SMatrix::<f64, 1, 5>::from_row_slice(&[0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    pub fn radius_y(&self) -> f64 {
+        self.data[1]
+    }
+
+    pub fn set_radius_y(&mut self, radius_y: f64) {
+        self.data[1] = radius_y;
+    }
+
+    pub fn radius_y_gradient(&self) -> SMatrix<f64, 1, 5> {
+        SMatrix::<f64, 1, 5>::from_row_slice(&[0.0, 0.0, 0.0, 1.0, 0.0])
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.data[2]
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.data[2] = rotation;
+    }
+
+    pub fn rotation_gradient(&self) -> SMatrix<f64, 1, 5> {
+        SMatrix::<f64, 1, 5>::from_row_slice(&[0.0, 0.0, 0.0, 0.0, 1.0])
+    }
+
+    pub fn point_at(&self, t: f64) -> Vector2<f64> {
+        let center = self.center.borrow().data();
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let x = self.radius_x() * t.cos();
+        let y = self.radius_y() * t.sin();
+        center + Vector2::new(cos_r * x - sin_r * y, sin_r * x + cos_r * y)
+    }
+
+    pub fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let dx = -self.radius_x() * t.sin();
+        let dy = self.radius_y() * t.cos();
+        Vector2::new(cos_r * dx - sin_r * dy, sin_r * dx + cos_r * dy)
+    }
+
+    // Gradient of `point_at(t)` with respect to [center_x, center_y, radius_x, radius_y, rotation]
+    pub fn point_at_gradient(&self, t: f64) -> SMatrix<f64, 2, 5> {
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let (sin_t, cos_t) = t.sin_cos();
+        let x = self.radius_x() * cos_t;
+        let y = self.radius_y() * sin_t;
+
+        SMatrix::<f64, 2, 5>::from_row_slice(&[
+            1.0,
+            0.0,
+            cos_r * cos_t,
+            -sin_r * sin_t,
+            -sin_r * x - cos_r * y,
+            0.0,
+            1.0,
+            sin_r * cos_t,
+            cos_r * sin_t,
+            cos_r * x - sin_r * y,
+        ])
+    }
+
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
+    pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 5>) {
+        // Panic if nan or inf is encountered
+        assert!(gradient.iter().all(|x| x.is_finite()));
+        self.center
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 0));
+        self.gradient += gradient.fixed_view::<1, 3>(0, 2).transpose();
+    }
+}
+
+impl PrimitiveLike for Ellipse {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![PrimitiveCell::Point2(self.center.clone())]
+    }
+
+    fn zero_gradient(&mut self) {
+        self.gradient = SVector::<f64, 3>::zeros();
+    }
+
+    fn get_data(&self) -> DVectorView<'_, f64> {
+        self.data.as_view()
+    }
+
+    fn set_data(&mut self, data: DVectorView<'_, f64>) {
+        assert!(data.iter().all(|x| x.is_finite()));
+        self.data.copy_from(&data);
+    }
+
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
+        self.gradient.as_view()
+    }
+
+    fn to_primitive(&self) -> super::Primitive {
+        super::Primitive::Ellipse(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::primitives::{ellipse::Ellipse, point2::Point2};
+
+    // An ellipse from [center_x, center_y, radius_x, radius_y, rotation]
+    fn ellipse(data: [f64; 5]) -> Ellipse {
+        let center = Rc::new(RefCell::new(Point2::new(data[0], data[1])));
+        Ellipse::new(center, data[2], data[3], data[4])
+    }
+
+    #[test]
+    fn test_point_at() {
+        let ellipse = ellipse([1.0, 2.0, 2.0, 1.0, 0.5 * PI]);
+        assert!((ellipse.point_at(0.0) - Vector2::new(1.0, 4.0)).norm() < 1e-12);
+        assert!((ellipse.point_at(0.5 * PI) - Vector2::new(0.0, 2.0)).norm() < 1e-12);
+        assert!((ellipse.point_at(PI) - Vector2::new(1.0, 0.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_derivative_at() {
+        let ellipse = ellipse([1.0, 2.0, 3.0, 1.5, 0.3]);
+        let eps = 1e-6;
+        for t in [0.0, 0.7, 2.0, -2.5] {
+            let numerical = (ellipse.point_at(t + eps) - ellipse.point_at(t - eps)) / (2.0 * eps);
+            assert!((ellipse.derivative_at(t) - numerical).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_point_at_gradient() {
+        let data = [1.0, 2.0, 3.0, 1.5, 0.3];
+        let eps = 1e-6;
+        for t in [0.0, 0.7, 2.0, -2.5] {
+            let gradient = ellipse(data).point_at_gradient(t);
+            for i in 0..5 {
+                let mut plus = data;
+                plus[i] += eps;
+                let mut minus = data;
+                minus[i] -= eps;
+                let numerical =
+                    (ellipse(plus).point_at(t) - ellipse(minus).point_at(t)) / (2.0 * eps);
+                assert!((gradient.column(i) - numerical).norm() < 1e-6);
+            }
+        }
+    }
+}
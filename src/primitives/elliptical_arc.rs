@@ -0,0 +1,335 @@
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::{DVectorView, SMatrix, SMatrixView, SVector, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+use super::{point2::Point2, PrimitiveCell, PrimitiveLike};
+
+// A part of an ellipse between the parameters `start_parameter` and `end_parameter`. The
+// parameterization is the same as for `Ellipse`, so for a circle the parameters are the angles.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EllipticalArc {
+    center: Rc<RefCell<Point2>>,
+    data: SVector<f64, 5>,
+    gradient: SVector<f64, 5>,
+
+    clockwise: bool,
+
+    #[serde(default)]
+    construction: bool,
+}
+
+impl EllipticalArc {
+    pub fn new(
+        center: Rc<RefCell<Point2>>,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        clockwise: bool,
+        start_parameter: f64,
+        end_parameter: f64,
+    ) -> Self {
+        Self {
+            center,
+            data: SVector::<f64, 5>::from_row_slice(&[
+                radius_x,
+                radius_y,
+                rotation,
+                start_parameter,
+                end_parameter,
+            ]),
+            gradient: SVector::<f64, 5>::zeros(),
+            clockwise,
+            construction: false,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        EllipticalArc::new(
+            self.center.clone(),
+            self.radius_x(),
+            self.radius_y(),
+            self.rotation(),
+            !self.clockwise,
+            self.end_parameter(),
+            self.start_parameter(),
+        )
+    }
+
+    pub fn center(&self) -> Rc<RefCell<Point2>> {
+        self.center.clone()
+    }
+
+    pub fn set_center(&mut self, center: Rc<RefCell<Point2>>) {
+        self.center = center;
+    }
+
+    pub fn center_gradient(&self) -> SMatrix<f64, 2, 7> {
+        SMatrix::<f64, 2, 7>::from_row_slice(&[
+            1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        ])
+    }
+
+    pub fn radius_x(&self) -> f64 {
+        self.data[0]
+    }
+
+    pub fn set_radius_x(&mut self, radius_x: f64) {
+        self.data[0] = radius_x;
+    }
+
+    pub fn radius_y(&self) -> f64 {
+        self.data[1]
+    }
+
+    pub fn set_radius_y(&mut self, radius_y: f64) {
+        self.data[1] = radius_y;
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.data[2]
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.data[2] = rotation;
+    }
+
+    pub fn start_parameter(&self) -> f64 {
+        self.data[3]
+    }
+
+    pub fn set_start_parameter(&mut self, start_parameter: f64) {
+        self.data[3] = start_parameter;
+    }
+
+    pub fn end_parameter(&self) -> f64 {
+        self.data[4]
+    }
+
+    pub fn set_end_parameter(&mut self, end_parameter: f64) {
+        self.data[4] = end_parameter;
+    }
+
+    pub fn clockwise(&self) -> bool {
+        self.clockwise
+    }
+
+    pub fn set_clockwise(&mut self, clockwise: bool) {
+        self.clockwise = clockwise;
+    }
+
+    pub fn point_at(&self, t: f64) -> Vector2<f64> {
+        let center = self.center.borrow().data();
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let x = self.radius_x() * t.cos();
+        let y = self.radius_y() * t.sin();
+        center + Vector2::new(cos_r * x - sin_r * y, sin_r * x + cos_r * y)
+    }
+
+    pub fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let dx = -self.radius_x() * t.sin();
+        let dy = self.radius_y() * t.cos();
+        Vector2::new(cos_r * dx - sin_r * dy, sin_r * dx + cos_r * dy)
+    }
+
+    // How far the parameter runs from the start to the end, in the direction of the arc
+    pub fn sweep_parameter(&self) -> f64 {
+        let sweep = if self.clockwise {
+            self.start_parameter() - self.end_parameter()
+        } else {
+            self.end_parameter() - self.start_parameter()
+        };
+        sweep.rem_euclid(2.0 * PI)
+    }
+
+    // Gradient of `point_at(t)` with respect to [center_x, center_y, radius_x, radius_y, rotation]
+    // and the parameter t itself in the last column.
+    fn point_at_gradient(&self, t: f64) -> SMatrix<f64, 2, 6> {
+        let (sin_r, cos_r) = self.rotation().sin_cos();
+        let (sin_t, cos_t) = t.sin_cos();
+        let x = self.radius_x() * cos_t;
+        let y = self.radius_y() * sin_t;
+        let dx = -self.radius_x() * sin_t;
+        let dy = self.radius_y() * cos_t;
+
+        SMatrix::<f64, 2, 6>::from_row_slice(&[
+            1.0,
+            0.0,
+            cos_r * cos_t,
+            -sin_r * sin_t,
+            -sin_r * x - cos_r * y,
+            cos_r * dx - sin_r * dy,
+            0.0,
+            1.0,
+            sin_r * cos_t,
+            cos_r * sin_t,
+            cos_r * x - sin_r * y,
+            sin_r * dx + cos_r * dy,
+        ])
+    }
+
+    pub fn start_point(&self) -> Vector2<f64> {
+        self.point_at(self.start_parameter())
+    }
+
+    pub fn start_point_gradient(&self) -> SMatrix<f64, 2, 7> {
+        let gradient = self.point_at_gradient(self.start_parameter());
+        let mut result = SMatrix::<f64, 2, 7>::zeros();
+        result
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&gradient.fixed_view::<2, 6>(0, 0));
+        result
+    }
+
+    pub fn end_point(&self) -> Vector2<f64> {
+        self.point_at(self.end_parameter())
+    }
+
+    pub fn end_point_gradient(&self) -> SMatrix<f64, 2, 7> {
+        let gradient = self.point_at_gradient(self.end_parameter());
+        let mut result = SMatrix::<f64, 2, 7>::zeros();
+        result
+            .fixed_view_mut::<2, 5>(0, 0)
+            .copy_from(&gradient.fixed_view::<2, 5>(0, 0));
+        result
+            .fixed_view_mut::<2, 1>(0, 6)
+            .copy_from(&gradient.fixed_view::<2, 1>(0, 5));
+        result
+    }
+
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
+    pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 7>) {
+        // Panic if nan or inf is encountered
+        assert!(gradient.iter().all(|x| x.is_finite()));
+        self.center
+            .borrow_mut()
+            .add_to_gradient(gradient.fixed_view::<1, 2>(0, 0));
+        self.gradient += gradient.fixed_view::<1, 5>(0, 2).transpose();
+    }
+}
+
+impl PrimitiveLike for EllipticalArc {
+    fn references(&self) -> Vec<PrimitiveCell> {
+        vec![PrimitiveCell::Point2(self.center.clone())]
+    }
+
+    fn zero_gradient(&mut self) {
+        self.gradient = SVector::<f64, 5>::zeros();
+    }
+
+    fn get_data(&self) -> DVectorView<'_, f64> {
+        self.data.as_view()
+    }
+
+    fn set_data(&mut self, data: DVectorView<'_, f64>) {
+        assert!(data.iter().all(|x| x.is_finite()));
+        self.data.copy_from(&data);
+    }
+
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
+        self.gradient.as_view()
+    }
+
+    fn to_primitive(&self) -> super::Primitive {
+        super::Primitive::EllipticalArc(self.clone())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::{SMatrix, Vector2};
+
+    use crate::primitives::{elliptical_arc::EllipticalArc, point2::Point2};
+
+    // An elliptical arc from [center_x, center_y, radius_x, radius_y, rotation, start, end]
+    fn arc(data: [f64; 7], clockwise: bool) -> EllipticalArc {
+        let center = Rc::new(RefCell::new(Point2::new(data[0], data[1])));
+        EllipticalArc::new(
+            center, data[2], data[3], data[4], clockwise, data[5], data[6],
+        )
+    }
+
+    // Central differences of the given point over all seven parameters of the arc
+    fn numerical_gradient(
+        data: [f64; 7],
+        point: impl Fn(&EllipticalArc) -> Vector2<f64>,
+    ) -> SMatrix<f64, 2, 7> {
+        let eps = 1e-6;
+        SMatrix::<f64, 2, 7>::from_fn(|row, i| {
+            let mut plus = data;
+            plus[i] += eps;
+            let mut minus = data;
+            minus[i] -= eps;
+            (point(&arc(plus, false))[row] - point(&arc(minus, false))[row]) / (2.0 * eps)
+        })
+    }
+
+    #[test]
+    fn test_start_and_end_point() {
+        let arc = arc([1.0, 2.0, 2.0, 1.0, 0.5 * PI, 0.0, 0.5 * PI], false);
+        assert!((arc.start_point() - Vector2::new(1.0, 4.0)).norm() < 1e-12);
+        assert!((arc.end_point() - Vector2::new(0.0, 2.0)).norm() < 1e-12);
+        assert!((arc.point_at(PI) - Vector2::new(1.0, 0.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_sweep_parameter() {
+        let data = [0.0, 0.0, 2.0, 1.0, 0.0, 0.25 * PI, 0.75 * PI];
+        assert!((arc(data, false).sweep_parameter() - 0.5 * PI).abs() < 1e-12);
+        assert!((arc(data, true).sweep_parameter() - 1.5 * PI).abs() < 1e-12);
+
+        // Parameters beyond a full turn wrap around
+        let data = [0.0, 0.0, 2.0, 1.0, 0.0, 1.75 * PI, 4.25 * PI];
+        assert!((arc(data, false).sweep_parameter() - 0.5 * PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let arc = arc([1.0, 2.0, 3.0, 1.5, 0.3, 0.2, 2.0], false);
+        let reversed = arc.reverse();
+        assert!(reversed.clockwise());
+        assert!((reversed.start_point() - arc.end_point()).norm() < 1e-12);
+        assert!((reversed.end_point() - arc.start_point()).norm() < 1e-12);
+        assert!((reversed.sweep_parameter() - arc.sweep_parameter()).abs() < 1e-12);
+        assert!((reversed.reverse().start_point() - arc.start_point()).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_derivative_at() {
+        let arc = arc([1.0, 2.0, 3.0, 1.5, 0.3, 0.2, 2.0], false);
+        let eps = 1e-6;
+        for t in [0.0, 0.7, 2.0, -2.5] {
+            let numerical = (arc.point_at(t + eps) - arc.point_at(t - eps)) / (2.0 * eps);
+            assert!((arc.derivative_at(t) - numerical).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_point_gradients() {
+        let data = [1.0, 2.0, 3.0, 1.5, 0.3, 0.2, 2.0];
+        let start = numerical_gradient(data, EllipticalArc::start_point);
+        assert!((arc(data, false).start_point_gradient() - start).norm() < 1e-6);
+        let end = numerical_gradient(data, EllipticalArc::end_point);
+        assert!((arc(data, false).end_point_gradient() - end).norm() < 1e-6);
+    }
+}
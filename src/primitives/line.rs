@@ -17,6 +17,9 @@ pub struct Line {
     start: Rc<RefCell<Point2>>,
     end: Rc<RefCell<Point2>>,
     empty: SVector<f64, 0>,
+
+    #[serde(default)]
+    construction: bool,
 }
 
 impl Line {
@@ -25,6 +28,7 @@ impl Line {
             start,
             end,
             empty: SVector::<f64, 0>::zeros(),
+            construction: false,
         }
     }
 
@@ -52,6 +56,14 @@ impl Line {
         SMatrix::<f64, 2, 4>::from_row_slice(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0])
     }
 
+    pub fn construction(&self) -> bool {
+        self.construction
+    }
+
+    pub fn set_construction(&mut self, construction: bool) {
+        self.construction = construction;
+    }
+
     pub fn add_to_gradient(&mut self, gradient: SMatrixView<f64, 1, 4>) {
         // Panic if nan or inf is encountered
         assert!(gradient.iter().all(|x| x.is_finite()));
@@ -76,16 +88,16 @@ impl PrimitiveLike for Line {
         // Referenced points will zero their gradients automatically as they are part of the sketch
     }
 
-    fn get_data(&self) -> DVectorView<f64> {
+    fn get_data(&self) -> DVectorView<'_, f64> {
         // empty vector
         self.empty.as_view()
     }
 
-    fn set_data(&mut self, _data: DVectorView<f64>) {
+    fn set_data(&mut self, _data: DVectorView<'_, f64>) {
         // Do nothing
     }
 
-    fn get_gradient(&self) -> DVectorView<f64> {
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
         // empty vector
         self.empty.as_view()
     }
@@ -10,7 +10,11 @@ use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
 pub mod arc;
+pub mod bezier;
+pub mod bspline;
 pub mod circle;
+pub mod ellipse;
+pub mod elliptical_arc;
 pub mod line;
 pub mod point2;
 
@@ -18,9 +22,9 @@ pub mod point2;
 pub trait PrimitiveLike: Debug {
     fn references(&self) -> Vec<PrimitiveCell>;
     fn zero_gradient(&mut self);
-    fn get_data(&self) -> DVectorView<f64>;
-    fn set_data(&mut self, data: DVectorView<f64>);
-    fn get_gradient(&self) -> DVectorView<f64>;
+    fn get_data(&self) -> DVectorView<'_, f64>;
+    fn set_data(&mut self, data: DVectorView<'_, f64>);
+    fn get_gradient(&self) -> DVectorView<'_, f64>;
     fn to_primitive(&self) -> Primitive;
 }
 
@@ -32,6 +36,10 @@ pub enum Primitive {
     Line(line::Line),
     Arc(arc::Arc),
     Circle(circle::Circle),
+    Ellipse(ellipse::Ellipse),
+    EllipticalArc(elliptical_arc::EllipticalArc),
+    BSpline(bspline::BSpline),
+    Bezier(bezier::Bezier),
 }
 
 impl Primitive {
@@ -41,6 +49,10 @@ impl Primitive {
             Primitive::Line(l) => l,
             Primitive::Arc(a) => a,
             Primitive::Circle(c) => c,
+            Primitive::Ellipse(e) => e,
+            Primitive::EllipticalArc(e) => e,
+            Primitive::BSpline(s) => s,
+            Primitive::Bezier(b) => b,
         }
     }
 }
@@ -53,33 +65,145 @@ pub enum PrimitiveCell {
     Line(Rc<RefCell<line::Line>>),
     Arc(Rc<RefCell<arc::Arc>>),
     Circle(Rc<RefCell<circle::Circle>>),
+    Ellipse(Rc<RefCell<ellipse::Ellipse>>),
+    EllipticalArc(Rc<RefCell<elliptical_arc::EllipticalArc>>),
+    BSpline(Rc<RefCell<bspline::BSpline>>),
+    Bezier(Rc<RefCell<bezier::Bezier>>),
 }
 
 impl PrimitiveCell {
-    pub fn borrow(&self) -> Ref<dyn PrimitiveLike> {
+    pub fn borrow(&self) -> Ref<'_, dyn PrimitiveLike> {
         match self {
             PrimitiveCell::Point2(p) => p.borrow(),
             PrimitiveCell::Line(l) => l.borrow(),
             PrimitiveCell::Arc(a) => a.borrow(),
             PrimitiveCell::Circle(c) => c.borrow(),
+            PrimitiveCell::Ellipse(e) => e.borrow(),
+            PrimitiveCell::EllipticalArc(e) => e.borrow(),
+            PrimitiveCell::BSpline(s) => s.borrow(),
+            PrimitiveCell::Bezier(b) => b.borrow(),
         }
     }
 
-    pub fn borrow_mut(&self) -> RefMut<dyn PrimitiveLike> {
+    pub fn borrow_mut(&self) -> RefMut<'_, dyn PrimitiveLike> {
         match self {
             PrimitiveCell::Point2(p) => p.borrow_mut(),
             PrimitiveCell::Line(l) => l.borrow_mut(),
             PrimitiveCell::Arc(a) => a.borrow_mut(),
             PrimitiveCell::Circle(c) => c.borrow_mut(),
+            PrimitiveCell::Ellipse(e) => e.borrow_mut(),
+            PrimitiveCell::EllipticalArc(e) => e.borrow_mut(),
+            PrimitiveCell::BSpline(s) => s.borrow_mut(),
+            PrimitiveCell::Bezier(b) => b.borrow_mut(),
         }
     }
 
+    // Construction geometry is solved like any other geometry, but is not part of the profile
+    pub fn construction(&self) -> bool {
+        match self {
+            PrimitiveCell::Point2(_) => false,
+            PrimitiveCell::Line(l) => l.borrow().construction(),
+            PrimitiveCell::Arc(a) => a.borrow().construction(),
+            PrimitiveCell::Circle(c) => c.borrow().construction(),
+            PrimitiveCell::Ellipse(e) => e.borrow().construction(),
+            PrimitiveCell::EllipticalArc(e) => e.borrow().construction(),
+            PrimitiveCell::BSpline(s) => s.borrow().construction(),
+            PrimitiveCell::Bezier(b) => b.borrow().construction(),
+        }
+    }
+
+    pub fn set_construction(&self, construction: bool) {
+        match self {
+            PrimitiveCell::Point2(_) => {}
+            PrimitiveCell::Line(l) => l.borrow_mut().set_construction(construction),
+            PrimitiveCell::Arc(a) => a.borrow_mut().set_construction(construction),
+            PrimitiveCell::Circle(c) => c.borrow_mut().set_construction(construction),
+            PrimitiveCell::Ellipse(e) => e.borrow_mut().set_construction(construction),
+            PrimitiveCell::EllipticalArc(e) => e.borrow_mut().set_construction(construction),
+            PrimitiveCell::BSpline(s) => s.borrow_mut().set_construction(construction),
+            PrimitiveCell::Bezier(b) => b.borrow_mut().set_construction(construction),
+        }
+    }
+
+    // A copy of the primitive that references the points returned by `point` instead of its own.
+    // Points are copied as new, unreferenced points.
+    pub fn duplicate(
+        &self,
+        point: &mut impl FnMut(&Rc<RefCell<point2::Point2>>) -> Rc<RefCell<point2::Point2>>,
+    ) -> PrimitiveCell {
+        match self {
+            PrimitiveCell::Point2(p) => {
+                PrimitiveCell::Point2(Rc::new(RefCell::new(p.borrow().clone())))
+            }
+            PrimitiveCell::Line(l) => {
+                let mut line = l.borrow().clone();
+                line.set_start(point(&line.start()));
+                line.set_end(point(&line.end()));
+                PrimitiveCell::Line(Rc::new(RefCell::new(line)))
+            }
+            PrimitiveCell::Arc(a) => {
+                let mut arc = a.borrow().clone();
+                arc.set_center(point(&arc.center()));
+                PrimitiveCell::Arc(Rc::new(RefCell::new(arc)))
+            }
+            PrimitiveCell::Circle(c) => {
+                let mut circle = c.borrow().clone();
+                circle.set_center(point(&circle.center()));
+                PrimitiveCell::Circle(Rc::new(RefCell::new(circle)))
+            }
+            PrimitiveCell::Ellipse(e) => {
+                let mut ellipse = e.borrow().clone();
+                ellipse.set_center(point(&ellipse.center()));
+                PrimitiveCell::Ellipse(Rc::new(RefCell::new(ellipse)))
+            }
+            PrimitiveCell::EllipticalArc(e) => {
+                let mut arc = e.borrow().clone();
+                arc.set_center(point(&arc.center()));
+                PrimitiveCell::EllipticalArc(Rc::new(RefCell::new(arc)))
+            }
+            PrimitiveCell::BSpline(s) => {
+                let mut spline = s.borrow().clone();
+                spline
+                    .set_control_points(spline.control_points().iter().map(&mut *point).collect());
+                PrimitiveCell::BSpline(Rc::new(RefCell::new(spline)))
+            }
+            PrimitiveCell::Bezier(b) => {
+                let mut bezier = b.borrow().clone();
+                bezier.set_start(point(&bezier.start()));
+                bezier.set_control1(point(&bezier.control1()));
+                bezier.set_control2(point(&bezier.control2()));
+                bezier.set_end(point(&bezier.end()));
+                PrimitiveCell::Bezier(Rc::new(RefCell::new(bezier)))
+            }
+        }
+    }
+
+    // Scales the coordinates and radii the primitive owns, angles and curve parameters stay the
+    // same
+    pub fn scale_lengths(&self, factor: f64) {
+        let lengths: &[usize] = match self {
+            PrimitiveCell::Point2(_) => &[0, 1],
+            PrimitiveCell::Circle(_) | PrimitiveCell::Arc(_) => &[0],
+            PrimitiveCell::Ellipse(_) | PrimitiveCell::EllipticalArc(_) => &[0, 1],
+            _ => &[],
+        };
+        let mut data = self.borrow().get_data().clone_owned();
+        for &index in lengths {
+            data[index] *= factor;
+        }
+        self.borrow_mut().set_data(data.as_view());
+    }
+
     pub fn as_ptr(&self) -> *const dyn PrimitiveLike {
         match self {
             PrimitiveCell::Point2(p) => p.as_ptr(),
             PrimitiveCell::Line(l) => l.as_ptr(),
             PrimitiveCell::Arc(a) => a.as_ptr(),
             PrimitiveCell::Circle(c) => c.as_ptr(),
+            PrimitiveCell::Ellipse(e) => e.as_ptr(),
+            PrimitiveCell::EllipticalArc(e) => e.as_ptr(),
+            PrimitiveCell::BSpline(s) => s.as_ptr(),
+            PrimitiveCell::Bezier(b) => b.as_ptr(),
         }
     }
 }
@@ -64,16 +64,16 @@ impl PrimitiveLike for Point2 {
         self.gradient = Vector2::zeros();
     }
 
-    fn get_data(&self) -> DVectorView<f64> {
+    fn get_data(&self) -> DVectorView<'_, f64> {
         self.data.as_view()
     }
 
-    fn set_data(&mut self, data: DVectorView<f64>) {
+    fn set_data(&mut self, data: DVectorView<'_, f64>) {
         assert!(data.iter().all(|x| x.is_finite()));
         self.data = Vector2::from_row_slice(data.as_slice());
     }
 
-    fn get_gradient(&self) -> DVectorView<f64> {
+    fn get_gradient(&self) -> DVectorView<'_, f64> {
         self.gradient.as_view()
     }
 
@@ -0,0 +1,542 @@
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::Vector2;
+
+use crate::constraints::coincident::arc_end_point_coincident::ArcEndPointCoincident;
+use crate::constraints::coincident::arc_start_point_coincident::ArcStartPointCoincident;
+use crate::constraints::continuity::g1_continuity::G1Continuity;
+use crate::constraints::continuity::{ContinuityCurve, CurveEnd};
+use crate::constraints::radius::RadialCurve;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::intersections::{angle_from, circle_circle, line_circle, line_line, line_parameter};
+use crate::primitives::arc::Arc;
+use crate::primitives::line::Line;
+use crate::primitives::point2::Point2;
+use crate::primitives::PrimitiveCell;
+
+use super::trim::CurveEdit;
+use super::Sketch;
+
+// Tangent points closer than this to the end of a curve don't leave anything of it
+const TOLERANCE: f64 = 1e-9;
+
+impl Sketch {
+    // Rounds the corner at the point, where two lines or a line and an arc meet, with an arc of
+    // the given radius. The neighbors are trimmed back to the arc, which is held tangent to them
+    // and at its radius by constraints. The corner point stays as a virtual sharp on the lines if
+    // anything else uses it.
+    pub fn fillet(
+        &mut self,
+        point: &Rc<RefCell<Point2>>,
+        radius: f64,
+    ) -> Result<CurveEdit, ISOTopeError> {
+        let [first, second] = self.corner(point)?;
+        let corner = point.borrow().data();
+
+        // The center is at the radius from both neighbors, on the side where it touches both
+        let mut best: Option<(f64, Placement)> = None;
+        for offset1 in first.offsets(radius) {
+            for offset2 in second.offsets(radius) {
+                for center in offset1.intersections(&offset2) {
+                    let (Some(tangent1), Some(tangent2)) = (
+                        first.tangent_point(center, radius),
+                        second.tangent_point(center, radius),
+                    ) else {
+                        continue;
+                    };
+                    if !first.contains(tangent1) || !second.contains(tangent2) {
+                        continue;
+                    }
+                    let size = (tangent1 - corner).norm() + (tangent2 - corner).norm();
+                    if best.as_ref().is_none_or(|(best_size, _)| size < *best_size) {
+                        best = Some((
+                            size,
+                            Placement {
+                                center,
+                                tangent1,
+                                tangent2,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        let (_, placement) = best.ok_or(ISOTopeError::CornerTooSmall(radius))?;
+        let Placement {
+            center,
+            tangent1,
+            tangent2,
+        } = placement;
+
+        self.curve_edit(|sketch, _| {
+            let (angle1, angle2) = (angle_of(tangent1 - center), angle_of(tangent2 - center));
+            // The fillet takes the short way around, it turns by less than half a circle
+            let clockwise = (angle2 - angle1).rem_euclid(2.0 * PI) > PI;
+            let center = sketch.add_point2(center.x, center.y)?;
+            let fillet = sketch.add_arc(center, radius, clockwise, angle1, angle2)?;
+
+            for (neighbor, tangent, end) in [
+                (&first, tangent1, CurveEnd::Start),
+                (&second, tangent2, CurveEnd::End),
+            ] {
+                let joint = sketch.cut_back(neighbor, tangent)?;
+                sketch.add_constraint(arc_end_coincident(&fillet, end, joint))?;
+                sketch.add_constraint(ConstraintCell::G1Continuity(Rc::new(RefCell::new(
+                    G1Continuity::new(
+                        neighbor.continuity(),
+                        ContinuityCurve::Arc(fillet.clone(), end),
+                    ),
+                ))))?;
+            }
+            sketch.constrain_radius(RadialCurve::Arc(fillet), radius)?;
+            sketch.settle_corner(point, &[first.clone(), second.clone()], false)
+        })
+    }
+
+    // Cuts off the corner at the point, where two lines or a line and an arc meet, with a line
+    // between the points at the given distance from the corner on both neighbors. Between two
+    // lines, the corner point stays as a virtual sharp that the distances are dimensioned from.
+    pub fn chamfer(
+        &mut self,
+        point: &Rc<RefCell<Point2>>,
+        distance: f64,
+    ) -> Result<CurveEdit, ISOTopeError> {
+        let [first, second] = self.corner(point)?;
+        let corner = point.borrow().data();
+        let (Some(end1), Some(end2)) = (
+            first.at_distance(corner, distance),
+            second.at_distance(corner, distance),
+        ) else {
+            return Err(ISOTopeError::CornerTooSmall(distance));
+        };
+
+        self.curve_edit(|sketch, _| {
+            let joint1 = sketch.cut_back(&first, end1)?;
+            let joint2 = sketch.cut_back(&second, end2)?;
+            sketch.add_line(joint1.clone(), joint2.clone())?;
+
+            let dimensioned = matches!((&first, &second), (Neighbor::Line(..), Neighbor::Line(..)));
+            sketch.settle_corner(point, &[first.clone(), second.clone()], dimensioned)?;
+            if dimensioned {
+                sketch.constrain_distance_euclidean(point.clone(), joint1, distance)?;
+                sketch.constrain_distance_euclidean(point.clone(), joint2, distance)?;
+            }
+            Ok(())
+        })
+    }
+
+    // The two curves that end at the point, with a line first
+    fn corner(&self, point: &Rc<RefCell<Point2>>) -> Result<[Neighbor; 2], ISOTopeError> {
+        let mut neighbors = Vec::new();
+        for primitive in self.primitives.values() {
+            if let PrimitiveCell::Line(line) = primitive {
+                if Rc::ptr_eq(&line.borrow().start(), point) {
+                    neighbors.push(Neighbor::Line(line.clone(), CurveEnd::Start));
+                }
+                if Rc::ptr_eq(&line.borrow().end(), point) {
+                    neighbors.push(Neighbor::Line(line.clone(), CurveEnd::End));
+                }
+            }
+        }
+        for constraint in self.constraints.iter() {
+            match constraint {
+                ConstraintCell::ArcStartPointCoincident(c)
+                    if Rc::ptr_eq(&c.borrow().point(), point) =>
+                {
+                    neighbors.push(Neighbor::Arc(c.borrow().arc(), CurveEnd::Start));
+                }
+                ConstraintCell::ArcEndPointCoincident(c)
+                    if Rc::ptr_eq(&c.borrow().point(), point) =>
+                {
+                    neighbors.push(Neighbor::Arc(c.borrow().arc(), CurveEnd::End));
+                }
+                _ => {}
+            }
+        }
+        match neighbors.as_slice() {
+            [line @ Neighbor::Line(..), other] | [other, line @ Neighbor::Line(..)] => {
+                Ok([line.clone(), other.clone()])
+            }
+            _ => Err(ISOTopeError::InvalidCorner),
+        }
+    }
+
+    // Trims the neighbor back to the position and returns the new point at its end there
+    fn cut_back(
+        &mut self,
+        neighbor: &Neighbor,
+        position: Vector2<f64>,
+    ) -> Result<Rc<RefCell<Point2>>, ISOTopeError> {
+        let point = self.add_point2(position.x, position.y)?;
+        match neighbor {
+            Neighbor::Line(line, end) => {
+                self.replace_line_end(line, *end, point.clone())?;
+            }
+            Neighbor::Arc(arc, end) => {
+                let center = arc.borrow().center().borrow().data();
+                self.move_arc_end(arc, *end, angle_of(position - center))?;
+                self.add_constraint(arc_end_coincident(arc, *end, point.clone()))?;
+            }
+        }
+        Ok(point)
+    }
+
+    // Deletes the corner point once the neighbors are cut back, unless it is still used or asked
+    // to stay. A point that stays is held on the lines it was the corner of.
+    fn settle_corner(
+        &mut self,
+        point: &Rc<RefCell<Point2>>,
+        neighbors: &[Neighbor],
+        keep: bool,
+    ) -> Result<(), ISOTopeError> {
+        let corner = PrimitiveCell::Point2(point.clone());
+        let used = self
+            .primitives
+            .values()
+            .any(|p| p.borrow().references().contains(&corner))
+            || self
+                .constraints
+                .iter()
+                .any(|c| c.borrow().references().contains(&corner));
+        if !keep && !used {
+            return self.delete_primitive_and_dependents(&corner);
+        }
+        for neighbor in neighbors.iter() {
+            if let Neighbor::Line(line, _) = neighbor {
+                self.constrain_distance_point_line(point.clone(), line.clone(), 0.0, true)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// One of the two curves at a corner, with its end at the corner
+#[derive(Debug, Clone)]
+enum Neighbor {
+    Line(Rc<RefCell<Line>>, CurveEnd),
+    Arc(Rc<RefCell<Arc>>, CurveEnd),
+}
+
+impl Neighbor {
+    fn continuity(&self) -> ContinuityCurve {
+        match self {
+            Neighbor::Line(line, end) => ContinuityCurve::Line(line.clone(), *end),
+            Neighbor::Arc(arc, end) => ContinuityCurve::Arc(arc.clone(), *end),
+        }
+    }
+
+    // The corner end and the far end of a line
+    fn line_ends(line: &Rc<RefCell<Line>>, end: CurveEnd) -> (Vector2<f64>, Vector2<f64>) {
+        let line = line.borrow();
+        let (start, stop) = (line.start().borrow().data(), line.end().borrow().data());
+        match end {
+            CurveEnd::Start => (start, stop),
+            CurveEnd::End => (stop, start),
+        }
+    }
+
+    // The curve moved by the distance to either side
+    fn offsets(&self, distance: f64) -> Vec<Offset> {
+        match self {
+            Neighbor::Line(line, end) => {
+                let (corner, far) = Neighbor::line_ends(line, *end);
+                let direction = (far - corner).normalize();
+                let normal = distance * Vector2::new(-direction.y, direction.x);
+                vec![
+                    Offset::Line(corner + normal, far + normal),
+                    Offset::Line(corner - normal, far - normal),
+                ]
+            }
+            Neighbor::Arc(arc, _) => {
+                let (center, radius) = {
+                    let arc = arc.borrow();
+                    (arc.center().borrow().data(), arc.radius())
+                };
+                let mut offsets = vec![Offset::Circle(center, radius + distance)];
+                if (radius - distance).abs() > TOLERANCE {
+                    offsets.push(Offset::Circle(center, (radius - distance).abs()));
+                }
+                offsets
+            }
+        }
+    }
+
+    // Where a circle around the center with the radius touches the curve, if it does
+    fn tangent_point(&self, center: Vector2<f64>, radius: f64) -> Option<Vector2<f64>> {
+        match self {
+            Neighbor::Line(line, end) => {
+                let (corner, far) = Neighbor::line_ends(line, *end);
+                let t = line_parameter(corner, far, center);
+                Some(corner + t * (far - corner))
+            }
+            Neighbor::Arc(arc, _) => {
+                let (arc_center, arc_radius) = {
+                    let arc = arc.borrow();
+                    (arc.center().borrow().data(), arc.radius())
+                };
+                let direction = (center - arc_center).try_normalize(TOLERANCE)?;
+                [1.0, -1.0]
+                    .into_iter()
+                    .map(|side| arc_center + side * arc_radius * direction)
+                    .find(|p| ((p - center).norm() - radius).abs() < TOLERANCE.sqrt() * radius)
+            }
+        }
+    }
+
+    // Whether a point on the extension of the curve is on the curve, away from the corner
+    fn contains(&self, point: Vector2<f64>) -> bool {
+        match self {
+            Neighbor::Line(line, end) => {
+                let (corner, far) = Neighbor::line_ends(line, *end);
+                let t = line_parameter(corner, far, point);
+                t > TOLERANCE && t < 1.0 - TOLERANCE
+            }
+            Neighbor::Arc(arc, end) => {
+                let arc = arc.borrow();
+                let center = arc.center().borrow().data();
+                // How far the point is along the arc from the corner
+                let from_corner = match end {
+                    CurveEnd::Start => {
+                        angle_from(center, arc.start_angle(), arc.clockwise(), point)
+                    }
+                    CurveEnd::End => angle_from(center, arc.end_angle(), !arc.clockwise(), point),
+                };
+                from_corner > TOLERANCE && from_corner < arc.sweep_angle() - TOLERANCE
+            }
+        }
+    }
+
+    // The point on the curve at the straight distance from the corner
+    fn at_distance(&self, corner: Vector2<f64>, distance: f64) -> Option<Vector2<f64>> {
+        let point = match self {
+            Neighbor::Line(line, end) => {
+                let (corner, far) = Neighbor::line_ends(line, *end);
+                corner + distance * (far - corner).try_normalize(TOLERANCE)?
+            }
+            Neighbor::Arc(arc, end) => {
+                let arc = arc.borrow();
+                let (center, radius) = (arc.center().borrow().data(), arc.radius());
+                if distance >= 2.0 * radius {
+                    return None;
+                }
+                // The chord of the distance spans this angle, turned from the corner into the arc
+                let angle = 2.0 * (distance / (2.0 * radius)).asin();
+                let forward = (*end == CurveEnd::Start) != arc.clockwise();
+                let turn = if forward { angle } else { -angle };
+                let corner_angle = angle_of(corner - center);
+                center
+                    + radius
+                        * Vector2::new((corner_angle + turn).cos(), (corner_angle + turn).sin())
+            }
+        };
+        (distance > 0.0 && self.contains(point)).then_some(point)
+    }
+}
+
+// Where a fillet goes, with the points where it touches the two neighbors
+struct Placement {
+    center: Vector2<f64>,
+    tangent1: Vector2<f64>,
+    tangent2: Vector2<f64>,
+}
+
+// A neighbor moved to the side, where the center of a fillet can be
+enum Offset {
+    Line(Vector2<f64>, Vector2<f64>),
+    Circle(Vector2<f64>, f64),
+}
+
+impl Offset {
+    fn intersections(&self, other: &Offset) -> Vec<Vector2<f64>> {
+        match (self, other) {
+            (Offset::Line(start1, end1), Offset::Line(start2, end2)) => {
+                line_line(*start1, *end1, *start2, *end2)
+            }
+            (Offset::Line(start, end), Offset::Circle(center, radius))
+            | (Offset::Circle(center, radius), Offset::Line(start, end)) => {
+                line_circle(*start, *end, *center, *radius)
+            }
+            (Offset::Circle(center1, radius1), Offset::Circle(center2, radius2)) => {
+                circle_circle(*center1, *radius1, *center2, *radius2)
+            }
+        }
+    }
+}
+
+pub(super) fn angle_of(offset: Vector2<f64>) -> f64 {
+    offset.y.atan2(offset.x)
+}
+
+pub(super) fn arc_end_coincident(
+    arc: &Rc<RefCell<Arc>>,
+    end: CurveEnd,
+    point: Rc<RefCell<Point2>>,
+) -> ConstraintCell {
+    match end {
+        CurveEnd::Start => ConstraintCell::ArcStartPointCoincident(Rc::new(RefCell::new(
+            ArcStartPointCoincident::new(arc.clone(), point),
+        ))),
+        CurveEnd::End => ConstraintCell::ArcEndPointCoincident(Rc::new(RefCell::new(
+            ArcEndPointCoincident::new(arc.clone(), point),
+        ))),
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{
+            coincident::arc_end_point_coincident::ArcEndPointCoincident,
+            lines::{horizontal_line::HorizontalLine, vertical_line::VerticalLine},
+            ConstraintCell,
+        },
+        error::ISOTopeError,
+        primitives::{arc::Arc, point2::Point2, PrimitiveCell},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    // A horizontal and a vertical line meeting in the origin
+    fn corner(sketch: &mut Sketch) -> Result<Rc<RefCell<Point2>>, ISOTopeError> {
+        let a = sketch.add_point2(2.0, 0.0)?;
+        let corner = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(0.0, 2.0)?;
+        let horizontal = sketch.add_line(a.clone(), corner.clone())?;
+        let vertical = sketch.add_line(corner.clone(), b.clone())?;
+        sketch.add_constraint(ConstraintCell::HorizontalLine(Rc::new(RefCell::new(
+            HorizontalLine::new(horizontal),
+        ))))?;
+        sketch.add_constraint(ConstraintCell::VerticalLine(Rc::new(RefCell::new(
+            VerticalLine::new(vertical),
+        ))))?;
+        sketch.constrain_fix_point(a, Vector2::new(2.0, 0.0))?;
+        sketch.constrain_fix_point(b, Vector2::new(0.0, 2.0))?;
+        Ok(corner)
+    }
+
+    fn arcs(sketch: &Sketch) -> Vec<Rc<RefCell<Arc>>> {
+        sketch
+            .primitives()
+            .into_values()
+            .filter_map(|p| match p {
+                PrimitiveCell::Arc(arc) => Some(arc),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fillet_lines() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let point = corner(&mut sketch)?;
+        let edit = sketch.fillet(&point, 0.5)?;
+
+        // The corner is gone, the fillet, its center and two tangent points are new
+        assert_eq!(edit.removed.len(), 1);
+        assert_eq!(edit.added.len(), 4);
+        assert!(edit.dropped.is_empty());
+        let fillet = arcs(&sketch).pop().ok_or("there should be a fillet")?;
+        let center = fillet.borrow().center().borrow().data();
+        assert!((center - Vector2::new(0.5, 0.5)).norm() < 1e-12);
+        assert!((fillet.borrow().sweep_angle() - PI / 2.0).abs() < 1e-12);
+        assert!(sketch.get_loss() < 1e-16);
+
+        // The fillet follows its radius
+        let radius = sketch
+            .constraints()
+            .into_iter()
+            .find(|c| matches!(c, ConstraintCell::RadiusDimension(_)))
+            .ok_or("the fillet should have a radius")?;
+        radius.set_dimension(0, 0.8)?;
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!((fillet.borrow().radius() - 0.8).abs() < 1e-6);
+        let center = fillet.borrow().center().borrow().data();
+        assert!((center - Vector2::new(0.8, 0.8)).norm() < 1e-4);
+
+        assert!(matches!(
+            sketch.fillet(&point, 0.5),
+            Err(ISOTopeError::InvalidCorner)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fillet_line_arc() -> Result<(), Box<dyn Error>> {
+        // A quarter circle coming down onto a line to the right
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let arc = sketch.add_arc(center, 1.0, true, PI / 2.0, 0.0)?;
+        let point = sketch.add_point2(1.0, 0.0)?;
+        let end = sketch.add_point2(3.0, 0.0)?;
+        sketch.add_line(point.clone(), end)?;
+        sketch.add_constraint(ConstraintCell::ArcEndPointCoincident(Rc::new(
+            RefCell::new(ArcEndPointCoincident::new(arc.clone(), point.clone())),
+        )))?;
+
+        assert!(matches!(
+            sketch.fillet(&point, 5.0),
+            Err(ISOTopeError::CornerTooSmall(_))
+        ));
+        sketch.fillet(&point, 0.25)?;
+
+        // The fillet sits outside the circle, touching it on the way to the center
+        let fillet = arcs(&sketch)
+            .into_iter()
+            .find(|a| !Rc::ptr_eq(a, &arc))
+            .ok_or("there should be a fillet")?;
+        let fillet_center = fillet.borrow().center().borrow().data();
+        assert!((fillet_center - Vector2::new(1.5f64.sqrt(), 0.25)).norm() < 1e-12);
+        let tangent = fillet_center / fillet_center.norm();
+        assert!((arc.borrow().end_point() - tangent).norm() < 1e-12);
+        assert!(sketch.get_loss() < 1e-16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chamfer() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let point = corner(&mut sketch)?;
+        let edit = sketch.chamfer(&point, 0.5)?;
+
+        // The corner stays to dimension the chamfer from
+        assert!(edit.removed.is_empty());
+        assert_eq!(edit.added.len(), 3);
+        assert_eq!(sketch.get_num_constraints(), 8);
+        assert!(sketch.get_loss() < 1e-16);
+
+        for constraint in sketch.constraints() {
+            if let ConstraintCell::EuclideanDistance(_) = constraint {
+                constraint.set_dimension(0, 0.7)?;
+            }
+        }
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!(point.borrow().data().norm() < 1e-4);
+        let ends: Vec<Vector2<f64>> = sketch
+            .primitives()
+            .into_values()
+            .filter_map(|p| match p {
+                PrimitiveCell::Point2(p) => Some(p.borrow().data()),
+                _ => None,
+            })
+            .filter(|p| (p.norm() - 0.7).abs() < 1e-4)
+            .collect();
+        assert_eq!(ends.len(), 2);
+
+        sketch.undo()?;
+        sketch.undo()?;
+        assert_eq!(sketch.get_num_primitives(), 5);
+        assert_eq!(sketch.get_num_constraints(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,462 @@
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use nalgebra::DVector;
+
+use crate::constraints::pattern::Pattern;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::parameters::{DimensionBinding, Parameters};
+use crate::primitives::{line::Line, point2::Point2, PrimitiveCell};
+use crate::units::LengthUnit;
+
+use super::Sketch;
+
+// A structural change to the sketch. Primitives and constraints are kept alive by the edit, so
+// undoing a deletion brings back the very same cells.
+#[derive(Debug, Clone)]
+pub(super) enum Edit {
+    AddPrimitive(u64, PrimitiveCell),
+    DeletePrimitive(u64, PrimitiveCell),
+    AddConstraint(usize, ConstraintCell),
+    DeleteConstraint(usize, ConstraintCell),
+    PushPatternCopy(Rc<RefCell<Pattern>>, Vec<PrimitiveCell>),
+    PopPatternCopy(Rc<RefCell<Pattern>>, Vec<PrimitiveCell>),
+    // Flips the direction of an arc or elliptical arc
+    ToggleClockwise(PrimitiveCell),
+    // Replaces the start and end point of a line, the first pair by the second
+    SetLineEnds(
+        Rc<RefCell<Line>>,
+        [Rc<RefCell<Point2>>; 2],
+        [Rc<RefCell<Point2>>; 2],
+    ),
+}
+
+impl Edit {
+    fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::AddPrimitive(id, primitive) => Edit::DeletePrimitive(id, primitive),
+            Edit::DeletePrimitive(id, primitive) => Edit::AddPrimitive(id, primitive),
+            Edit::AddConstraint(index, constraint) => Edit::DeleteConstraint(index, constraint),
+            Edit::DeleteConstraint(index, constraint) => Edit::AddConstraint(index, constraint),
+            Edit::PushPatternCopy(pattern, copy) => Edit::PopPatternCopy(pattern, copy),
+            Edit::PopPatternCopy(pattern, copy) => Edit::PushPatternCopy(pattern, copy),
+            Edit::ToggleClockwise(primitive) => Edit::ToggleClockwise(primitive),
+            Edit::SetLineEnds(line, from, to) => Edit::SetLineEnds(line, to, from),
+        }
+    }
+
+    pub(super) fn apply(&self, sketch: &mut Sketch) {
+        match self.clone() {
+            Edit::AddPrimitive(id, primitive) => {
+                sketch.primitives.insert(id, primitive);
+            }
+            Edit::DeletePrimitive(id, _) => {
+                sketch.primitives.remove(&id);
+            }
+            Edit::AddConstraint(index, constraint) => sketch.constraints.insert(index, constraint),
+            Edit::DeleteConstraint(index, _) => {
+                sketch.constraints.remove(index);
+            }
+            Edit::PushPatternCopy(pattern, copy) => pattern.borrow_mut().push_copy(copy),
+            Edit::PopPatternCopy(pattern, _) => {
+                pattern.borrow_mut().pop_copy();
+            }
+            Edit::ToggleClockwise(primitive) => match primitive {
+                PrimitiveCell::Arc(arc) => {
+                    let clockwise = arc.borrow().clockwise();
+                    arc.borrow_mut().set_clockwise(!clockwise);
+                }
+                PrimitiveCell::EllipticalArc(arc) => {
+                    let clockwise = arc.borrow().clockwise();
+                    arc.borrow_mut().set_clockwise(!clockwise);
+                }
+                _ => {}
+            },
+            Edit::SetLineEnds(line, _, [start, end]) => {
+                let mut line = line.borrow_mut();
+                line.set_start(start);
+                line.set_end(end);
+            }
+        }
+    }
+}
+
+// Everything that changes without changing the structure: the data of the primitives, the values
+// of the dimensions and the parameters, bindings, driven dimensions and unit of the sketch
+#[derive(Debug, Clone)]
+struct State {
+    data: Vec<(PrimitiveCell, DVector<f64>)>,
+    dimensions: Vec<(ConstraintCell, Vec<f64>)>,
+    parameters: Parameters,
+    dimension_bindings: Vec<DimensionBinding>,
+    driven_constraints: Vec<ConstraintCell>,
+    length_unit: LengthUnit,
+}
+
+impl State {
+    fn capture(sketch: &Sketch) -> Self {
+        Self {
+            data: sketch
+                .primitives
+                .values()
+                .map(|p| (p.clone(), p.borrow().get_data().clone_owned()))
+                .collect(),
+            dimensions: sketch
+                .constraints
+                .iter()
+                .map(|c| (c.clone(), c.dimensions()))
+                .collect(),
+            parameters: sketch.parameters.clone(),
+            dimension_bindings: sketch.dimension_bindings.clone(),
+            driven_constraints: sketch.driven_constraints.clone(),
+            length_unit: sketch.length_unit,
+        }
+    }
+
+    fn restore(&self, sketch: &mut Sketch) {
+        for (primitive, data) in self.data.iter() {
+            primitive.borrow_mut().set_data(data.as_view());
+        }
+        for (constraint, dimensions) in self.dimensions.iter() {
+            for (index, value) in dimensions.iter().enumerate() {
+                // The values were read from the same constraint, so they always fit
+                let _ = constraint.set_dimension(index, *value);
+            }
+        }
+        sketch.parameters = self.parameters.clone();
+        sketch.dimension_bindings = self.dimension_bindings.clone();
+        sketch.driven_constraints = self.driven_constraints.clone();
+        sketch.length_unit = self.length_unit;
+    }
+}
+
+// One step in the history. The state is only captured if the step changed it.
+#[derive(Debug, Clone)]
+struct Transaction {
+    edits: Vec<Edit>,
+    before: Option<State>,
+    after: Option<State>,
+}
+
+// An open, possibly nested, transaction
+#[derive(Debug, Clone)]
+struct Level {
+    start: usize,
+    before: Option<State>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct History {
+    undo: Vec<Transaction>,
+    redo: Vec<Transaction>,
+    edits: Vec<Edit>,
+    levels: Vec<Level>,
+    // Changes outside of a transaction, like the steps of a solver, are grouped until the next
+    // edit or undo
+    pending: Option<State>,
+}
+
+impl Sketch {
+    // Groups all following edits into one step of the history until the transaction is committed
+    // or rolled back. Transactions can be nested.
+    pub fn begin_transaction(&mut self) {
+        if self.history.levels.is_empty() {
+            self.flush_pending();
+        }
+        self.history.levels.push(Level {
+            start: self.history.edits.len(),
+            before: None,
+        });
+    }
+
+    pub fn commit_transaction(&mut self) -> Result<(), ISOTopeError> {
+        if self.history.levels.is_empty() {
+            return Err(ISOTopeError::NoTransaction);
+        }
+        self.end_transaction(true);
+        Ok(())
+    }
+
+    // Reverts everything since the matching `begin_transaction`
+    pub fn rollback_transaction(&mut self) -> Result<(), ISOTopeError> {
+        if self.history.levels.is_empty() {
+            return Err(ISOTopeError::NoTransaction);
+        }
+        self.end_transaction(false);
+        Ok(())
+    }
+
+    // Runs `f` in a transaction that is committed if it succeeds and rolled back if it fails,
+    // e.g. when a solve after some edits doesn't converge
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut Sketch) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.begin_transaction();
+        let result = f(self);
+        self.end_transaction(result.is_ok());
+        result
+    }
+
+    // Runs an edit that can not fail as a single step of the history
+    pub(super) fn recorded<T>(&mut self, f: impl FnOnce(&mut Sketch) -> T) -> T {
+        self.begin_transaction();
+        let result = f(self);
+        self.end_transaction(true);
+        result
+    }
+
+    fn end_transaction(&mut self, commit: bool) {
+        let Some(level) = self.history.levels.pop() else {
+            return;
+        };
+        if !commit {
+            let edits = self.history.edits.split_off(level.start);
+            for edit in edits.iter().rev() {
+                edit.inverse().apply(self);
+            }
+            if let Some(before) = level.before {
+                before.restore(self);
+            }
+        } else if self.history.levels.is_empty() {
+            let edits = mem::take(&mut self.history.edits);
+            let after = level.before.as_ref().map(|_| State::capture(self));
+            self.push_transaction(Transaction {
+                edits,
+                before: level.before,
+                after,
+            });
+        }
+    }
+
+    // Reverts the last step of the history. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> Result<bool, ISOTopeError> {
+        if !self.history.levels.is_empty() {
+            return Err(ISOTopeError::TransactionInProgress);
+        }
+        self.flush_pending();
+        let Some(transaction) = self.history.undo.pop() else {
+            return Ok(false);
+        };
+        for edit in transaction.edits.iter().rev() {
+            edit.inverse().apply(self);
+        }
+        if let Some(before) = &transaction.before {
+            before.restore(self);
+        }
+        self.history.redo.push(transaction);
+        Ok(true)
+    }
+
+    // Applies the last undone step again. Returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> Result<bool, ISOTopeError> {
+        if !self.history.levels.is_empty() {
+            return Err(ISOTopeError::TransactionInProgress);
+        }
+        self.flush_pending();
+        let Some(transaction) = self.history.redo.pop() else {
+            return Ok(false);
+        };
+        for edit in transaction.edits.iter() {
+            edit.apply(self);
+        }
+        if let Some(after) = &transaction.after {
+            after.restore(self);
+        }
+        self.history.undo.push(transaction);
+        Ok(true)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty() || self.history.pending.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty() && self.history.pending.is_none()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+        self.history.pending = None;
+    }
+
+    // Records a structural edit that has already been applied
+    pub(super) fn record(&mut self, edit: Edit) {
+        if self.history.levels.is_empty() {
+            self.flush_pending();
+            self.push_transaction(Transaction {
+                edits: vec![edit],
+                before: None,
+                after: None,
+            });
+        } else {
+            self.history.edits.push(edit);
+        }
+    }
+
+    // Has to be called before anything in `State` changes
+    pub(super) fn touch(&mut self) {
+        if self.history.levels.is_empty() {
+            if self.history.pending.is_none() {
+                self.history.pending = Some(State::capture(self));
+            }
+        } else if self.history.levels.iter().any(|l| l.before.is_none()) {
+            let state = State::capture(self);
+            for level in self.history.levels.iter_mut() {
+                if level.before.is_none() {
+                    level.before = Some(state.clone());
+                }
+            }
+        }
+    }
+
+    fn flush_pending(&mut self) {
+        if let Some(before) = self.history.pending.take() {
+            let after = State::capture(self);
+            self.push_transaction(Transaction {
+                edits: vec![],
+                before: Some(before),
+                after: Some(after),
+            });
+        }
+    }
+
+    fn push_transaction(&mut self, transaction: Transaction) {
+        if transaction.edits.is_empty() && transaction.before.is_none() {
+            return;
+        }
+        self.history.undo.push(transaction);
+        self.history.redo.clear();
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{pattern::PatternKind, ConstraintCell},
+        parameters::expression::Expression,
+        primitives::PrimitiveCell,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    #[test]
+    fn test_undo_redo_edits() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let line = sketch.add_line(a, b.clone())?;
+        let fix = sketch.constrain_fix_point(b.clone(), Vector2::new(1.0, 0.0))?;
+        let fix = ConstraintCell::FixPoint(fix);
+        assert_eq!(sketch.get_num_primitives(), 3);
+
+        sketch.delete_constraint(fix.clone())?;
+        assert_eq!(sketch.get_num_constraints(), 0);
+        assert!(sketch.undo()?);
+        assert_eq!(sketch.constraints()[0], fix);
+
+        // Undoing all the way back leaves an empty sketch, redoing brings back the same cells
+        while sketch.undo()? {}
+        assert_eq!(sketch.get_num_primitives(), 0);
+        assert_eq!(sketch.get_num_constraints(), 0);
+        assert!(!sketch.can_undo());
+        while sketch.redo()? {}
+        assert_eq!(sketch.get_num_primitives(), 3);
+        assert_eq!(sketch.get_num_constraints(), 0);
+        assert!(sketch
+            .get_primitive_id(&PrimitiveCell::Line(line))
+            .is_some());
+
+        // A new edit drops what could have been redone
+        sketch.undo()?;
+        sketch.add_point2(2.0, 2.0)?;
+        assert!(!sketch.can_redo());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_solve_and_parameters() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        sketch.constrain_fix_point(a.clone(), Vector2::new(0.0, 0.0))?;
+        let distance = sketch.constrain_distance_euclidean(a, b.clone(), 1.0)?;
+        let distance = ConstraintCell::EuclideanDistance(distance);
+        sketch.set_parameter("d", Expression::parse("3")?)?;
+        sketch.bind_dimension(distance.clone(), 0, Expression::parse("d")?)?;
+        assert_eq!(distance.dimensions(), vec![3.0]);
+
+        // All steps of the solver are a single step in the history
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!((b.borrow().data().norm() - 3.0).abs() < 1e-5);
+        sketch.undo()?;
+        assert_eq!(b.borrow().x(), 1.0);
+        sketch.redo()?;
+        assert!((b.borrow().data().norm() - 3.0).abs() < 1e-5);
+
+        sketch.set_parameter("d", Expression::parse("5")?)?;
+        assert_eq!(distance.dimensions(), vec![5.0]);
+        sketch.undo()?;
+        assert_eq!(distance.dimensions(), vec![3.0]);
+        assert_eq!(sketch.parameters().value("d"), Some(3.0));
+        sketch.undo()?;
+        sketch.undo()?;
+        assert!(sketch.dimension_bindings().is_empty());
+        assert_eq!(distance.dimensions(), vec![1.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transactions() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let pattern = sketch.add_pattern(
+            vec![PrimitiveCell::Point2(b.clone())],
+            PatternKind::Linear {
+                spacing: Vector2::new(1.0, 0.0),
+            },
+            3,
+        )?;
+        assert_eq!(sketch.get_num_primitives(), 5);
+
+        // Contradicting dimensions can't be solved, so the whole transaction is rolled back
+        let result = sketch.transaction(|sketch| -> Result<(), Box<dyn Error>> {
+            sketch.set_pattern_count(&pattern, 1)?;
+            sketch.constrain_distance_euclidean(a.clone(), b.clone(), 2.0)?;
+            sketch.constrain_distance_euclidean(a.clone(), b.clone(), 4.0)?;
+            BFGSSolver::new().solve(sketch)?;
+            if sketch.get_loss() > 1e-6 {
+                return Err("the sketch could not be solved".into());
+            }
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(sketch.get_num_primitives(), 5);
+        assert_eq!(sketch.get_num_constraints(), 1);
+        assert_eq!(pattern.borrow().count(), 3);
+        assert_eq!(b.borrow().x(), 1.0);
+
+        // A committed transaction is undone in one step
+        sketch.begin_transaction();
+        sketch.set_pattern_count(&pattern, 1)?;
+        sketch.constrain_distance_euclidean(a, b, 2.0)?;
+        assert!(sketch.undo().is_err());
+        sketch.commit_transaction()?;
+        assert_eq!(sketch.get_num_primitives(), 3);
+        sketch.undo()?;
+        assert_eq!(sketch.get_num_primitives(), 5);
+        assert_eq!(sketch.get_num_constraints(), 1);
+        assert_eq!(pattern.borrow().count(), 3);
+        assert!(sketch.commit_transaction().is_err());
+        Ok(())
+    }
+}
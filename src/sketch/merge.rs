@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::dsl::{self, primitive_name};
+use crate::error::ISOTopeError;
+use crate::parameters::expression::Expression;
+use crate::parameters::Parameters;
+use crate::primitives::PrimitiveCell;
+
+use super::Sketch;
+
+impl Sketch {
+    // A copy of the primitives with the given ids, together with the primitives they reference,
+    // the constraints between them and the parameters those constraints use. Returns the copy and
+    // the id in the copy for every copied id.
+    pub fn extract(&self, ids: &[u64]) -> Result<(Sketch, BTreeMap<u64, u64>), ISOTopeError> {
+        let mut closure: Vec<PrimitiveCell> = Vec::new();
+        let mut stack: Vec<PrimitiveCell> = Vec::new();
+        for id in ids.iter() {
+            let primitive = self
+                .get_primitive_by_id(*id)
+                .ok_or(ISOTopeError::PrimitiveNotFound(*id))?;
+            stack.push(primitive.clone());
+        }
+        while let Some(primitive) = stack.pop() {
+            if !closure.contains(&primitive) {
+                stack.extend(primitive.borrow().references());
+                closure.push(primitive);
+            }
+        }
+
+        // A sketch that shares the selected cells, keeping their ids
+        let mut selection = Sketch::new();
+        selection.length_unit = self.length_unit;
+        for (id, primitive) in self.primitives.iter() {
+            if closure.contains(primitive) {
+                selection.primitives.insert(*id, primitive.clone());
+            }
+        }
+        for constraint in self.constraints.iter() {
+            if constraint
+                .borrow()
+                .references()
+                .iter()
+                .all(|r| closure.contains(r))
+            {
+                selection.constraints.push_back(constraint.clone());
+            }
+        }
+        selection.dimension_bindings = self
+            .dimension_bindings
+            .iter()
+            .filter(|b| selection.constraints.contains(&b.constraint()))
+            .cloned()
+            .collect();
+        selection.driven_constraints = self
+            .driven_constraints
+            .iter()
+            .filter(|c| selection.constraints.contains(c))
+            .cloned()
+            .collect();
+        selection.parameters = self.used_parameters(&selection)?;
+
+        selection.deep_copy()
+    }
+
+    // Inserts a copy of the other sketch with fresh ids and returns the id in this sketch for
+    // every id in the other one. The copy is converted to the length unit of this sketch.
+    // Parameters this sketch already has are kept, so a pasted block follows them. Their values are
+    // in the unit of this sketch already, so dimensions that only use those are not converted.
+    pub fn merge(&mut self, other: &Sketch) -> Result<BTreeMap<u64, u64>, ISOTopeError> {
+        let (mut copy, copy_ids) = other.deep_copy()?;
+        let unconverted: Vec<Expression> = copy
+            .dimension_bindings
+            .iter()
+            .map(|b| b.expression())
+            .collect();
+        copy.convert_length_unit(self.length_unit)?;
+
+        self.transaction(|sketch| {
+            let kept: BTreeSet<String> = copy
+                .parameters
+                .names()
+                .into_iter()
+                .filter(|name| sketch.parameters.value(name).is_some())
+                .collect();
+            for name in copy.parameters.names() {
+                if sketch.parameters.value(&name).is_none() {
+                    if let Some(expression) = copy.parameters.expression(&name) {
+                        sketch.set_parameter(&name, expression)?;
+                    }
+                }
+            }
+
+            let mut ids = BTreeMap::new();
+            for (id, primitive) in copy.primitives.iter() {
+                ids.insert(*id, sketch.add_primitive(primitive.clone())?);
+            }
+            for constraint in copy.constraints.iter() {
+                sketch.add_constraint(constraint.clone())?;
+            }
+            for (binding, unconverted) in copy.dimension_bindings.iter().zip(unconverted) {
+                let parameters = unconverted.parameters();
+                let expression = if !parameters.is_empty() && parameters.is_subset(&kept) {
+                    unconverted
+                } else {
+                    binding.expression()
+                };
+                sketch.bind_dimension(binding.constraint(), binding.index(), expression)?;
+            }
+            for constraint in copy.driven_constraints.iter() {
+                sketch.set_driven(constraint, true)?;
+            }
+
+            Ok(copy_ids
+                .iter()
+                .filter_map(|(other_id, copy_id)| Some((*other_id, *ids.get(copy_id)?)))
+                .collect())
+        })
+    }
+
+    // Pastes a copy of the primitives with the given ids and the constraints between them into
+    // this sketch. Returns the id of the copy for every copied id.
+    pub fn duplicate(&mut self, ids: &[u64]) -> Result<BTreeMap<u64, u64>, ISOTopeError> {
+        let (selection, selection_ids) = self.extract(ids)?;
+        let merged_ids = self.merge(&selection)?;
+        Ok(selection_ids
+            .iter()
+            .filter_map(|(id, selection_id)| Some((*id, *merged_ids.get(selection_id)?)))
+            .collect())
+    }
+
+    // The parameters of this sketch that the bindings of the other sketch depend on
+    fn used_parameters(&self, other: &Sketch) -> Result<Parameters, ISOTopeError> {
+        let mut used: BTreeSet<String> = BTreeSet::new();
+        let mut stack: Vec<String> = other
+            .dimension_bindings
+            .iter()
+            .flat_map(|b| b.expression().parameters())
+            .collect();
+        while let Some(name) = stack.pop() {
+            if let Some(expression) = self.parameters.expression(&name) {
+                stack.extend(expression.parameters());
+                used.insert(name);
+            }
+        }
+
+        let mut parameters = Parameters::default();
+        for name in self.parameters.names() {
+            if let Some(expression) = self.parameters.expression(&name) {
+                if used.contains(&name) {
+                    parameters.set(&name, expression)?;
+                }
+            }
+        }
+        Ok(parameters)
+    }
+
+    // A copy with new cells for all primitives and constraints, and the id in the copy for every
+    // id in this sketch. The text format is lossless, so the copy is made by printing and parsing.
+    fn deep_copy(&self) -> Result<(Sketch, BTreeMap<u64, u64>), ISOTopeError> {
+        let (copy, names) = dsl::parse_with_names(&dsl::print(self)?)?;
+        let mut ids = BTreeMap::new();
+        for (id, primitive) in self.primitives.iter() {
+            let copy_id = names
+                .get(&primitive_name(*id, primitive))
+                .and_then(|p| copy.get_primitive_id(p))
+                .ok_or(ISOTopeError::PrimitiveNotFound(*id))?;
+            ids.insert(*id, copy_id);
+        }
+        Ok((copy, ids))
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintCell},
+        parameters::expression::Expression,
+        primitives::PrimitiveCell,
+        sketch::Sketch,
+        units::{Angle, LengthUnit},
+    };
+
+    // A slot of two lines and two arcs around two centers, with the width as parameter
+    fn slot(sketch: &mut Sketch) -> Result<Vec<u64>, Box<dyn Error>> {
+        let c1 = sketch.add_point2(0.0, 0.0)?;
+        let c2 = sketch.add_point2(3.0, 0.0)?;
+        let arc = sketch.add_arc(c1.clone(), 1.0, false, 0.5, 1.5)?;
+        let circle = sketch.add_circle(c2.clone(), 1.0)?;
+        let line = sketch.add_line(c1.clone(), c2.clone())?;
+        let length = sketch.constrain_distance_euclidean(c1, c2, 3.0)?;
+        sketch.constrain_equal_radius(
+            RadialCurve::Arc(arc.clone()),
+            RadialCurve::Circle(circle.clone()),
+        )?;
+        let radius = sketch.constrain_radius(RadialCurve::Circle(circle.clone()), 1.0)?;
+        sketch.set_parameter("width", Expression::parse("2")?)?;
+        sketch.bind_dimension(
+            ConstraintCell::RadiusDimension(radius),
+            0,
+            Expression::parse("width / 2")?,
+        )?;
+        sketch.set_driven(&ConstraintCell::EuclideanDistance(length), true)?;
+        Ok([
+            PrimitiveCell::Arc(arc),
+            PrimitiveCell::Circle(circle),
+            PrimitiveCell::Line(line),
+        ]
+        .iter()
+        .filter_map(|p| sketch.get_primitive_id(p))
+        .collect())
+    }
+
+    #[test]
+    fn test_extract_and_merge() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let other = sketch.add_point2(5.0, 5.0)?;
+        let ids = slot(&mut sketch)?;
+        sketch.set_parameter("unused", Expression::parse("1")?)?;
+        // Constraints to the rest of the sketch are not part of the selection
+        let PrimitiveCell::Arc(arc) = sketch.get_primitive_by_id(ids[0]).cloned().ok_or("")? else {
+            return Err("the first id should be the arc".into());
+        };
+        sketch.constrain_angle_between_points(
+            other,
+            arc.borrow().center(),
+            arc.borrow().center(),
+            Angle::degrees(10.0),
+        )?;
+
+        let (slot, slot_ids) = sketch.extract(&ids[..2])?;
+        assert_eq!(slot.get_num_primitives(), 4);
+        assert_eq!(slot.get_num_constraints(), 3);
+        assert_eq!(slot.parameters().names(), vec!["width".to_string()]);
+        assert_eq!(slot_ids.len(), 4);
+        // The copy shares nothing with the original
+        let copied_arc = slot.get_primitive_by_id(slot_ids[&ids[0]]).ok_or("")?;
+        assert!(copied_arc != &PrimitiveCell::Arc(arc));
+
+        // Merging into a sketch in inches follows its parameters and converts lengths
+        let mut target = Sketch::new();
+        target.set_length_unit(LengthUnit::Inch);
+        target.add_point2(0.0, 0.0)?;
+        target.set_parameter("width", Expression::parse("0.5")?)?;
+        let (all, all_ids) = sketch.extract(&ids)?;
+        let target_ids = target.merge(&all)?;
+        assert_eq!(target.get_num_primitives(), 6);
+        assert_eq!(target.get_num_constraints(), 3);
+        assert_eq!(target.driven_constraints().len(), 1);
+        assert_eq!(target.parameters().value("width"), Some(0.5));
+        // The radius follows the width of the target, which is in inches already
+        let radii: Vec<f64> = target
+            .dimension_bindings()
+            .iter()
+            .flat_map(|binding| binding.constraint().dimensions())
+            .collect();
+        assert_eq!(radii, vec![0.25]);
+        let PrimitiveCell::Line(line) = target
+            .get_primitive_by_id(target_ids[&all_ids[&ids[2]]])
+            .ok_or("")?
+        else {
+            return Err("the line should be copied as a line".into());
+        };
+        assert!((line.borrow().end().borrow().x() - 3.0 / 25.4).abs() < 1e-12);
+
+        // Merging is a single step in the history
+        target.undo()?;
+        assert_eq!(target.get_num_primitives(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let ids = slot(&mut sketch)?;
+        let copies = sketch.duplicate(&ids)?;
+
+        assert_eq!(sketch.get_num_primitives(), 10);
+        assert_eq!(sketch.get_num_constraints(), 6);
+        assert_eq!(sketch.dimension_bindings().len(), 2);
+        assert_eq!(copies.len(), 5);
+        for (id, copy) in copies.iter() {
+            assert_ne!(id, copy);
+        }
+
+        // Both slots follow the same parameter
+        sketch.set_parameter("width", Expression::parse("4")?)?;
+        for binding in sketch.dimension_bindings() {
+            assert_eq!(binding.constraint().dimensions(), vec![2.0]);
+        }
+        Ok(())
+    }
+}
@@ -6,26 +6,78 @@ use nalgebra::{DMatrix, DVector, Vector2};
 use serde::{Deserialize, Serialize};
 
 use crate::constraints::angle_between_points::AngleBetweenPoints;
+use crate::constraints::arcs::arc_length::ArcLength;
+use crate::constraints::arcs::arc_sweep_angle::ArcSweepAngle;
 use crate::constraints::distance::euclidian_distance_between_points::EuclidianDistanceBetweenPoints;
+use crate::constraints::distance::line_circle_distance::LineCircleDistance;
+use crate::constraints::distance::line_line_distance::LineLineDistance;
+use crate::constraints::distance::point_circle_distance::PointCircleDistance;
+use crate::constraints::distance::point_line_distance::PointLineDistance;
 use crate::constraints::fix_point::FixPoint;
+use crate::constraints::inequality::angle_bound::AngleBound;
+use crate::constraints::inequality::euclidean_distance_bound::EuclideanDistanceBound;
+use crate::constraints::inequality::horizontal_distance_bound::HorizontalDistanceBound;
+use crate::constraints::inequality::radius_bound::RadiusBound;
+use crate::constraints::inequality::vertical_distance_bound::VerticalDistanceBound;
+use crate::constraints::inequality::Bound;
+use crate::constraints::length::equal_lengths::EqualLengths;
+use crate::constraints::length::LengthCurve;
+use crate::constraints::lines::angle_between_lines::AngleBetweenLines;
+use crate::constraints::lines::collinear_lines::CollinearLines;
+use crate::constraints::lines::collinear_points::CollinearPoints;
 use crate::constraints::lines::parallel_lines::ParallelLines;
 use crate::constraints::lines::perpendicular_lines::PerpendicularLines;
+use crate::constraints::midpoint::{Midpoint, MidpointCurve};
+use crate::constraints::pattern::{Pattern, PatternKind};
+use crate::constraints::radius::diameter_dimension::DiameterDimension;
+use crate::constraints::radius::equal_radius::EqualRadius;
+use crate::constraints::radius::radius_difference::RadiusDifference;
+use crate::constraints::radius::radius_dimension::RadiusDimension;
+use crate::constraints::radius::RadialCurve;
+use crate::constraints::symmetric::symmetric_points::Symmetric;
 use crate::constraints::ConstraintCell;
 use crate::decompose::face::Face;
 use crate::decompose::{decompose_sketch, merge_faces};
 use crate::error::ISOTopeError;
+use crate::parameters::expression::Expression;
+use crate::parameters::{DimensionBinding, Parameters};
 use crate::primitives::arc::Arc;
+use crate::primitives::bezier::Bezier;
+use crate::primitives::bspline::BSpline;
+use crate::primitives::circle::Circle;
+use crate::primitives::ellipse::Ellipse;
+use crate::primitives::elliptical_arc::EllipticalArc;
 use crate::primitives::line::Line;
 use crate::primitives::point2::Point2;
 use crate::primitives::{point2, PrimitiveCell};
+use crate::units::{Angle, Length, LengthUnit, Quantity};
 
 use super::constraints::ConstraintLike;
 
+use self::history::{Edit, History};
+
+mod fillet;
+mod history;
+mod merge;
+pub mod offset;
+pub mod transform;
+pub mod trim;
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Sketch {
     primitives: BTreeMap<u64, PrimitiveCell>,
     primitives_next_id: u64,
     constraints: VecDeque<ConstraintCell>,
+    #[serde(default)]
+    parameters: Parameters,
+    #[serde(default)]
+    dimension_bindings: Vec<DimensionBinding>,
+    #[serde(default)]
+    driven_constraints: Vec<ConstraintCell>,
+    #[serde(default)]
+    length_unit: LengthUnit,
+    #[serde(skip)]
+    history: History,
 }
 
 impl Sketch {
@@ -45,10 +97,18 @@ impl Sketch {
             return Err(ISOTopeError::PrimitiveAlreadyInSketch);
         }
         // Add the primitive to the sketch
-        self.primitives.insert(self.primitives_next_id, primitive);
+        let id = self.primitives_next_id;
+        self.primitives.insert(id, primitive.clone());
         self.primitives_next_id += 1;
+        self.record(Edit::AddPrimitive(id, primitive));
 
-        Ok(self.primitives_next_id - 1)
+        Ok(id)
+    }
+
+    // Lets the next primitive get the given id, as long as no later id was handed out already.
+    // The skipped ids stay unused.
+    pub(crate) fn skip_primitive_ids(&mut self, next_id: u64) {
+        self.primitives_next_id = self.primitives_next_id.max(next_id);
     }
 
     pub fn add_point2(&mut self, x: f64, y: f64) -> Result<Rc<RefCell<Point2>>, ISOTopeError> {
@@ -86,6 +146,83 @@ impl Sketch {
         Ok(arc)
     }
 
+    pub fn add_circle(
+        &mut self,
+        center: Rc<RefCell<Point2>>,
+        radius: f64,
+    ) -> Result<Rc<RefCell<Circle>>, ISOTopeError> {
+        let circle = Rc::new(RefCell::new(Circle::new(center, radius)));
+        self.add_primitive(PrimitiveCell::Circle(circle.clone()))?;
+        Ok(circle)
+    }
+
+    pub fn add_ellipse(
+        &mut self,
+        center: Rc<RefCell<Point2>>,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+    ) -> Result<Rc<RefCell<Ellipse>>, ISOTopeError> {
+        let ellipse = Rc::new(RefCell::new(Ellipse::new(
+            center, radius_x, radius_y, rotation,
+        )));
+        self.add_primitive(PrimitiveCell::Ellipse(ellipse.clone()))?;
+        Ok(ellipse)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_elliptical_arc(
+        &mut self,
+        center: Rc<RefCell<Point2>>,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        clockwise: bool,
+        start_parameter: f64,
+        end_parameter: f64,
+    ) -> Result<Rc<RefCell<EllipticalArc>>, ISOTopeError> {
+        let arc = Rc::new(RefCell::new(EllipticalArc::new(
+            center,
+            radius_x,
+            radius_y,
+            rotation,
+            clockwise,
+            start_parameter,
+            end_parameter,
+        )));
+        self.add_primitive(PrimitiveCell::EllipticalArc(arc.clone()))?;
+        Ok(arc)
+    }
+
+    pub fn add_bspline(
+        &mut self,
+        control_points: Vec<Rc<RefCell<Point2>>>,
+        degree: usize,
+        knots: Vec<f64>,
+        weights: Option<Vec<f64>>,
+    ) -> Result<Rc<RefCell<BSpline>>, ISOTopeError> {
+        let spline = Rc::new(RefCell::new(BSpline::new(
+            control_points,
+            degree,
+            knots,
+            weights,
+        )?));
+        self.add_primitive(PrimitiveCell::BSpline(spline.clone()))?;
+        Ok(spline)
+    }
+
+    pub fn add_bezier(
+        &mut self,
+        start: Rc<RefCell<Point2>>,
+        control1: Rc<RefCell<Point2>>,
+        control2: Rc<RefCell<Point2>>,
+        end: Rc<RefCell<Point2>>,
+    ) -> Result<Rc<RefCell<Bezier>>, ISOTopeError> {
+        let bezier = Rc::new(RefCell::new(Bezier::new(start, control1, control2, end)));
+        self.add_primitive(PrimitiveCell::Bezier(bezier.clone()))?;
+        Ok(bezier)
+    }
+
     pub fn constrain_perpendicular_lines(
         &mut self,
         line1: Rc<RefCell<Line>>,
@@ -108,6 +245,140 @@ impl Sketch {
         Ok(parallel_lines)
     }
 
+    pub fn constrain_arc_sweep_angle(
+        &mut self,
+        arc: Rc<RefCell<Arc>>,
+        desired_angle: Angle,
+    ) -> Result<Rc<RefCell<ArcSweepAngle>>, ISOTopeError> {
+        let angle = Rc::new(RefCell::new(ArcSweepAngle::new(arc, desired_angle)));
+        self.add_constraint(ConstraintCell::ArcSweepAngle(angle.clone()))?;
+        Ok(angle)
+    }
+
+    pub fn constrain_arc_length(
+        &mut self,
+        arc: Rc<RefCell<Arc>>,
+        desired_length: f64,
+    ) -> Result<Rc<RefCell<ArcLength>>, ISOTopeError> {
+        let length = Rc::new(RefCell::new(ArcLength::new(arc, desired_length)));
+        self.add_constraint(ConstraintCell::ArcLength(length.clone()))?;
+        Ok(length)
+    }
+
+    pub fn constrain_radius(
+        &mut self,
+        curve: RadialCurve,
+        desired_radius: f64,
+    ) -> Result<Rc<RefCell<RadiusDimension>>, ISOTopeError> {
+        let radius = Rc::new(RefCell::new(RadiusDimension::new(curve, desired_radius)));
+        self.add_constraint(ConstraintCell::RadiusDimension(radius.clone()))?;
+        Ok(radius)
+    }
+
+    pub fn constrain_diameter(
+        &mut self,
+        curve: RadialCurve,
+        desired_diameter: f64,
+    ) -> Result<Rc<RefCell<DiameterDimension>>, ISOTopeError> {
+        let diameter = Rc::new(RefCell::new(DiameterDimension::new(
+            curve,
+            desired_diameter,
+        )));
+        self.add_constraint(ConstraintCell::DiameterDimension(diameter.clone()))?;
+        Ok(diameter)
+    }
+
+    pub fn constrain_equal_radius(
+        &mut self,
+        curve1: RadialCurve,
+        curve2: RadialCurve,
+    ) -> Result<Rc<RefCell<EqualRadius>>, ISOTopeError> {
+        let equal_radius = Rc::new(RefCell::new(EqualRadius::new(curve1, curve2)));
+        self.add_constraint(ConstraintCell::EqualRadius(equal_radius.clone()))?;
+        Ok(equal_radius)
+    }
+
+    pub fn constrain_radius_difference(
+        &mut self,
+        curve1: RadialCurve,
+        curve2: RadialCurve,
+        desired_difference: f64,
+    ) -> Result<Rc<RefCell<RadiusDifference>>, ISOTopeError> {
+        let difference = Rc::new(RefCell::new(RadiusDifference::new(
+            curve1,
+            curve2,
+            desired_difference,
+        )));
+        self.add_constraint(ConstraintCell::RadiusDifference(difference.clone()))?;
+        Ok(difference)
+    }
+
+    pub fn constrain_equal_lengths(
+        &mut self,
+        curves: Vec<LengthCurve>,
+    ) -> Result<Rc<RefCell<EqualLengths>>, ISOTopeError> {
+        let equal_lengths = Rc::new(RefCell::new(EqualLengths::new(curves)));
+        self.add_constraint(ConstraintCell::EqualLengths(equal_lengths.clone()))?;
+        Ok(equal_lengths)
+    }
+
+    pub fn constrain_collinear_lines(
+        &mut self,
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+    ) -> Result<Rc<RefCell<CollinearLines>>, ISOTopeError> {
+        let collinear = Rc::new(RefCell::new(CollinearLines::new(line1, line2)));
+        self.add_constraint(ConstraintCell::CollinearLines(collinear.clone()))?;
+        Ok(collinear)
+    }
+
+    pub fn constrain_collinear_points(
+        &mut self,
+        points: Vec<Rc<RefCell<Point2>>>,
+    ) -> Result<Rc<RefCell<CollinearPoints>>, ISOTopeError> {
+        let collinear = Rc::new(RefCell::new(CollinearPoints::new(points)));
+        self.add_constraint(ConstraintCell::CollinearPoints(collinear.clone()))?;
+        Ok(collinear)
+    }
+
+    pub fn constrain_symmetric(
+        &mut self,
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        line: Rc<RefCell<Line>>,
+    ) -> Result<Rc<RefCell<Symmetric>>, ISOTopeError> {
+        let symmetric = Rc::new(RefCell::new(Symmetric::new(point1, point2, line)));
+        self.add_constraint(ConstraintCell::Symmetric(symmetric.clone()))?;
+        Ok(symmetric)
+    }
+
+    pub fn constrain_angle_between_lines(
+        &mut self,
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+        desired_angle: Angle,
+        signed: bool,
+    ) -> Result<Rc<RefCell<AngleBetweenLines>>, ISOTopeError> {
+        let angle = Rc::new(RefCell::new(AngleBetweenLines::new(
+            line1,
+            line2,
+            desired_angle,
+            signed,
+        )));
+        self.add_constraint(ConstraintCell::AngleBetweenLines(angle.clone()))?;
+        Ok(angle)
+    }
+
+    pub fn constrain_midpoint(
+        &mut self,
+        curve: MidpointCurve,
+        point: Rc<RefCell<Point2>>,
+    ) -> Result<Rc<RefCell<Midpoint>>, ISOTopeError> {
+        let midpoint = Rc::new(RefCell::new(Midpoint::new(curve, point)));
+        self.add_constraint(ConstraintCell::Midpoint(midpoint.clone()))?;
+        Ok(midpoint)
+    }
+
     pub fn constrain_distance_euclidean(
         &mut self,
         point1: Rc<RefCell<Point2>>,
@@ -123,6 +394,74 @@ impl Sketch {
         Ok(distance)
     }
 
+    pub fn constrain_distance_point_line(
+        &mut self,
+        point: Rc<RefCell<Point2>>,
+        line: Rc<RefCell<Line>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Result<Rc<RefCell<PointLineDistance>>, ISOTopeError> {
+        let distance = Rc::new(RefCell::new(PointLineDistance::new(
+            point,
+            line,
+            desired_distance,
+            signed,
+        )));
+        self.add_constraint(ConstraintCell::PointLineDistance(distance.clone()))?;
+        Ok(distance)
+    }
+
+    pub fn constrain_distance_line_line(
+        &mut self,
+        line1: Rc<RefCell<Line>>,
+        line2: Rc<RefCell<Line>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Result<Rc<RefCell<LineLineDistance>>, ISOTopeError> {
+        let distance = Rc::new(RefCell::new(LineLineDistance::new(
+            line1,
+            line2,
+            desired_distance,
+            signed,
+        )));
+        self.add_constraint(ConstraintCell::LineLineDistance(distance.clone()))?;
+        Ok(distance)
+    }
+
+    pub fn constrain_distance_point_circle(
+        &mut self,
+        point: Rc<RefCell<Point2>>,
+        circle: Rc<RefCell<Circle>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Result<Rc<RefCell<PointCircleDistance>>, ISOTopeError> {
+        let distance = Rc::new(RefCell::new(PointCircleDistance::new(
+            point,
+            circle,
+            desired_distance,
+            signed,
+        )));
+        self.add_constraint(ConstraintCell::PointCircleDistance(distance.clone()))?;
+        Ok(distance)
+    }
+
+    pub fn constrain_distance_line_circle(
+        &mut self,
+        line: Rc<RefCell<Line>>,
+        circle: Rc<RefCell<Circle>>,
+        desired_distance: f64,
+        signed: bool,
+    ) -> Result<Rc<RefCell<LineCircleDistance>>, ISOTopeError> {
+        let distance = Rc::new(RefCell::new(LineCircleDistance::new(
+            line,
+            circle,
+            desired_distance,
+            signed,
+        )));
+        self.add_constraint(ConstraintCell::LineCircleDistance(distance.clone()))?;
+        Ok(distance)
+    }
+
     pub fn constrain_fix_point(
         &mut self,
         point: Rc<RefCell<Point2>>,
@@ -138,7 +477,7 @@ impl Sketch {
         point1: Rc<RefCell<Point2>>,
         point2: Rc<RefCell<Point2>>,
         middle_point: Rc<RefCell<Point2>>,
-        desired_angle: f64,
+        desired_angle: Angle,
     ) -> Result<Rc<RefCell<AngleBetweenPoints>>, ISOTopeError> {
         let angle = Rc::new(RefCell::new(AngleBetweenPoints::new(
             point1,
@@ -150,6 +489,389 @@ impl Sketch {
         Ok(angle)
     }
 
+    pub fn constrain_euclidean_distance_bound(
+        &mut self,
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        limit: f64,
+        bound: Bound,
+    ) -> Result<Rc<RefCell<EuclideanDistanceBound>>, ISOTopeError> {
+        let constraint = Rc::new(RefCell::new(EuclideanDistanceBound::new(
+            point1, point2, limit, bound,
+        )));
+        self.add_constraint(ConstraintCell::EuclideanDistanceBound(constraint.clone()))?;
+        Ok(constraint)
+    }
+
+    pub fn constrain_horizontal_distance_bound(
+        &mut self,
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        limit: f64,
+        bound: Bound,
+    ) -> Result<Rc<RefCell<HorizontalDistanceBound>>, ISOTopeError> {
+        let constraint = Rc::new(RefCell::new(HorizontalDistanceBound::new(
+            point1, point2, limit, bound,
+        )));
+        self.add_constraint(ConstraintCell::HorizontalDistanceBound(constraint.clone()))?;
+        Ok(constraint)
+    }
+
+    pub fn constrain_vertical_distance_bound(
+        &mut self,
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        limit: f64,
+        bound: Bound,
+    ) -> Result<Rc<RefCell<VerticalDistanceBound>>, ISOTopeError> {
+        let constraint = Rc::new(RefCell::new(VerticalDistanceBound::new(
+            point1, point2, limit, bound,
+        )));
+        self.add_constraint(ConstraintCell::VerticalDistanceBound(constraint.clone()))?;
+        Ok(constraint)
+    }
+
+    pub fn constrain_angle_bound(
+        &mut self,
+        point1: Rc<RefCell<Point2>>,
+        point2: Rc<RefCell<Point2>>,
+        middle_point: Rc<RefCell<Point2>>,
+        limit: Angle,
+        bound: Bound,
+    ) -> Result<Rc<RefCell<AngleBound>>, ISOTopeError> {
+        let constraint = Rc::new(RefCell::new(AngleBound::new(
+            point1,
+            point2,
+            middle_point,
+            limit,
+            bound,
+        )));
+        self.add_constraint(ConstraintCell::AngleBound(constraint.clone()))?;
+        Ok(constraint)
+    }
+
+    pub fn constrain_radius_bound(
+        &mut self,
+        curve: RadialCurve,
+        limit: f64,
+        bound: Bound,
+    ) -> Result<Rc<RefCell<RadiusBound>>, ISOTopeError> {
+        let constraint = Rc::new(RefCell::new(RadiusBound::new(curve, limit, bound)));
+        self.add_constraint(ConstraintCell::RadiusBound(constraint.clone()))?;
+        Ok(constraint)
+    }
+
+    // Adds copies of the seed primitives that are tied to the seed by a pattern constraint. The
+    // points referenced by the seed are copied along with it.
+    pub fn add_pattern(
+        &mut self,
+        seed: Vec<PrimitiveCell>,
+        kind: PatternKind,
+        count: usize,
+    ) -> Result<Rc<RefCell<Pattern>>, ISOTopeError> {
+        let mut closure: Vec<PrimitiveCell> = Vec::new();
+        for primitive in seed.iter() {
+            let references = primitive.borrow().references();
+            for p in references.into_iter().chain([primitive.clone()]) {
+                if !closure.contains(&p) {
+                    closure.push(p);
+                }
+            }
+        }
+
+        let pattern = Rc::new(RefCell::new(Pattern::new(closure, vec![], kind)));
+        for reference in pattern.borrow().references().iter() {
+            if self.get_primitive_id(reference).is_none() {
+                return Err(ISOTopeError::MissingSketchReferences);
+            }
+        }
+        self.transaction(|sketch| {
+            sketch.set_pattern_count(&pattern, count)?;
+            sketch.add_constraint(ConstraintCell::Pattern(pattern.clone()))
+        })?;
+        Ok(pattern)
+    }
+
+    // Adds or removes copies at the end of a pattern. Removed copies are deleted from the sketch
+    // together with everything that depends on them.
+    pub fn set_pattern_count(
+        &mut self,
+        pattern: &Rc<RefCell<Pattern>>,
+        count: usize,
+    ) -> Result<(), ISOTopeError> {
+        self.transaction(|sketch| {
+            while pattern.borrow().count() < count {
+                let copy = {
+                    let pattern = pattern.borrow();
+                    pattern.instantiate_copy(pattern.count() + 1)
+                };
+                for primitive in copy.iter() {
+                    sketch.add_primitive(primitive.clone())?;
+                }
+                pattern.borrow_mut().push_copy(copy.clone());
+                sketch.record(Edit::PushPatternCopy(pattern.clone(), copy));
+            }
+            while pattern.borrow().count() > count {
+                let copy = pattern.borrow_mut().pop_copy().unwrap_or_default();
+                sketch.record(Edit::PopPatternCopy(pattern.clone(), copy.clone()));
+                for primitive in copy.iter().rev() {
+                    sketch.delete_primitive_and_dependents(primitive)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Deletes a primitive along with the primitives and constraints that reference it
+    fn delete_primitive_and_dependents(
+        &mut self,
+        primitive: &PrimitiveCell,
+    ) -> Result<(), ISOTopeError> {
+        let dependents: Vec<PrimitiveCell> = self
+            .primitives
+            .values()
+            .filter(|p| p.borrow().references().contains(primitive))
+            .cloned()
+            .collect();
+        for dependent in dependents.iter() {
+            self.delete_primitive_and_dependents(dependent)?;
+        }
+
+        while let Some(index) = self
+            .constraints
+            .iter()
+            .position(|c| c.borrow().references().contains(primitive))
+        {
+            self.remove_constraint(index);
+        }
+        if let Some(id) = self.get_primitive_id(primitive) {
+            self.delete_primitive(id)?;
+        }
+        Ok(())
+    }
+
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+
+    // Defines or redefines a parameter and updates all dimensions that depend on it. Nothing
+    // changes if the parameters or dimensions can not be evaluated.
+    pub fn set_parameter(
+        &mut self,
+        name: &str,
+        expression: Expression,
+    ) -> Result<f64, ISOTopeError> {
+        let mut parameters = self.parameters.clone();
+        let value = parameters.set(name, expression)?;
+        let dimensions = self.evaluate_dimensions(&parameters)?;
+
+        self.transaction(|sketch| {
+            sketch.touch();
+            sketch.parameters = parameters;
+            sketch.apply_dimensions(dimensions)?;
+            Ok(value)
+        })
+    }
+
+    pub fn remove_parameter(&mut self, name: &str) -> Result<(), ISOTopeError> {
+        if self
+            .dimension_bindings
+            .iter()
+            .any(|b| b.expression().parameters().contains(name))
+        {
+            return Err(ISOTopeError::ParameterInUse(name.to_string()));
+        }
+        self.transaction(|sketch| {
+            sketch.touch();
+            sketch.parameters.remove(name)
+        })
+    }
+
+    // Drives a dimension of a constraint, see `ConstraintCell::dimensions`, by an expression. The
+    // dimension is updated right away and whenever a parameter changes.
+    pub fn bind_dimension(
+        &mut self,
+        constraint: ConstraintCell,
+        index: usize,
+        expression: Expression,
+    ) -> Result<(), ISOTopeError> {
+        if !self.constraints.contains(&constraint) {
+            return Err(ISOTopeError::ConstraintNotFound);
+        }
+        let value = self.parameters.evaluate(&expression)?;
+        self.transaction(|sketch| {
+            sketch.touch();
+            constraint.set_dimension(index, value)?;
+            // A dimension that follows a parameter is driving again
+            sketch.driven_constraints.retain(|c| c != &constraint);
+
+            sketch.unbind_dimension(&constraint, index);
+            sketch
+                .dimension_bindings
+                .push(DimensionBinding::new(constraint, index, expression));
+            Ok(())
+        })
+    }
+
+    // Turns a dimension back into a plain value, keeping its current value
+    pub fn unbind_dimension(&mut self, constraint: &ConstraintCell, index: usize) {
+        self.recorded(|sketch| {
+            sketch.touch();
+            sketch
+                .dimension_bindings
+                .retain(|b| !(&b.constraint() == constraint && b.index() == index));
+        })
+    }
+
+    pub fn dimension_expression(
+        &self,
+        constraint: &ConstraintCell,
+        index: usize,
+    ) -> Option<Expression> {
+        self.dimension_bindings
+            .iter()
+            .find(|b| &b.constraint() == constraint && b.index() == index)
+            .map(|b| b.expression())
+    }
+
+    pub fn dimension_bindings(&self) -> Vec<DimensionBinding> {
+        self.dimension_bindings.clone()
+    }
+
+    fn evaluate_dimensions(&self, parameters: &Parameters) -> Result<Vec<f64>, ISOTopeError> {
+        self.dimension_bindings
+            .iter()
+            .map(|b| parameters.evaluate(&b.expression()))
+            .collect()
+    }
+
+    fn apply_dimensions(&mut self, values: Vec<f64>) -> Result<(), ISOTopeError> {
+        for (binding, value) in self.dimension_bindings.iter().zip(values) {
+            binding.constraint().set_dimension(binding.index(), value)?;
+        }
+        Ok(())
+    }
+
+    // Driven dimensions measure the geometry instead of constraining it. They are left out of the
+    // loss and the Jacobian, and take the measured value after every solve. Making a dimension
+    // driven drops the parameter expression it followed.
+    pub fn set_driven(
+        &mut self,
+        constraint: &ConstraintCell,
+        driven: bool,
+    ) -> Result<(), ISOTopeError> {
+        if !self.constraints.contains(constraint) {
+            return Err(ISOTopeError::ConstraintNotFound);
+        }
+        if constraint.measure().is_empty() {
+            return Err(ISOTopeError::NotADimension(0));
+        }
+
+        self.transaction(|sketch| {
+            sketch.touch();
+            sketch.driven_constraints.retain(|c| c != constraint);
+            if driven {
+                sketch
+                    .dimension_bindings
+                    .retain(|b| &b.constraint() != constraint);
+                sketch.driven_constraints.push(constraint.clone());
+                sketch.update_driven_dimensions()?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn is_driven(&self, constraint: &ConstraintCell) -> bool {
+        self.driven_constraints.contains(constraint)
+    }
+
+    pub fn driven_constraints(&self) -> Vec<ConstraintCell> {
+        self.driven_constraints.clone()
+    }
+
+    // Sets all driven dimensions to their measured values, the solvers call this after solving
+    pub fn update_driven_dimensions(&mut self) -> Result<(), ISOTopeError> {
+        if !self.driven_constraints.is_empty() {
+            self.touch();
+        }
+        for constraint in self.driven_constraints.iter() {
+            for (index, value) in constraint.measure().into_iter().enumerate() {
+                // Degenerate geometry keeps the last measurement
+                if value.is_finite() {
+                    constraint.set_dimension(index, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The constraints that take part in solving
+    fn driving_constraints(&self) -> impl Iterator<Item = &ConstraintCell> {
+        self.constraints
+            .iter()
+            .filter(|c| !self.driven_constraints.contains(c))
+    }
+
+    // All coordinates, radii and length dimensions of the sketch are in this unit, angles are
+    // always in radians
+    pub fn length_unit(&self) -> LengthUnit {
+        self.length_unit
+    }
+
+    // Changes the unit the sketch is read in without touching any values, see
+    // `convert_length_unit` to keep the real size
+    pub fn set_length_unit(&mut self, unit: LengthUnit) {
+        self.recorded(|sketch| {
+            sketch.touch();
+            sketch.length_unit = unit;
+        })
+    }
+
+    // The value of a length in the unit of the sketch
+    pub fn length(&self, length: Length) -> f64 {
+        length.to(self.length_unit)
+    }
+
+    // Converts the sketch to another unit so the geometry keeps its real size. Coordinates, radii
+    // and length dimensions are scaled. Parameters have no unit, so length dimensions that follow
+    // a parameter get the conversion factor in their expression.
+    pub fn convert_length_unit(&mut self, unit: LengthUnit) -> Result<(), ISOTopeError> {
+        self.transaction(|sketch| {
+            sketch.touch();
+            let factor = sketch.length_unit.factor_to(unit);
+            sketch.length_unit = unit;
+            if factor == 1.0 {
+                return Ok(());
+            }
+
+            for primitive in sketch.primitives.values() {
+                primitive.scale_lengths(factor);
+            }
+            for constraint in sketch.constraints.iter() {
+                let dimensions = constraint.dimensions();
+                for (index, quantity) in constraint.dimension_quantities().into_iter().enumerate() {
+                    if quantity == Quantity::Length {
+                        constraint.set_dimension(index, dimensions[index] * factor)?;
+                    }
+                }
+            }
+            for binding in sketch.dimension_bindings.iter_mut() {
+                if binding.constraint().dimension_quantities()[binding.index()] == Quantity::Length
+                {
+                    *binding = DimensionBinding::new(
+                        binding.constraint(),
+                        binding.index(),
+                        Expression::Multiply(
+                            Box::new(binding.expression()),
+                            Box::new(Expression::Number(factor)),
+                        ),
+                    );
+                }
+            }
+            Ok(())
+        })
+    }
+
     pub fn get_num_primitives(&self) -> usize {
         self.primitives.len()
     }
@@ -166,7 +888,8 @@ impl Sketch {
             return Err(ISOTopeError::ConstraintAlreadyInSketch);
         }
 
-        self.constraints.push_back(constraint);
+        self.constraints.push_back(constraint.clone());
+        self.record(Edit::AddConstraint(self.constraints.len() - 1, constraint));
 
         Ok(())
     }
@@ -176,24 +899,45 @@ impl Sketch {
     }
 
     pub fn delete_primitive(&mut self, id: u64) -> Result<(), ISOTopeError> {
-        if self.primitives.remove(&id).is_none() {
+        let Some(primitive) = self.primitives.remove(&id) else {
             return Err(ISOTopeError::PrimitiveNotFound(id));
-        }
+        };
+        self.record(Edit::DeletePrimitive(id, primitive));
 
         Ok(())
     }
 
     pub fn delete_constraint(&mut self, constraint: ConstraintCell) -> Result<(), ISOTopeError> {
-        let init_len = self.constraints.len();
-        self.constraints.retain(|c| c == &constraint);
-
-        if init_len == self.constraints.len() {
-            return Err(ISOTopeError::ConstraintNotFound);
-        }
+        let index = self
+            .constraints
+            .iter()
+            .position(|c| c == &constraint)
+            .ok_or(ISOTopeError::ConstraintNotFound)?;
+        self.recorded(|sketch| sketch.remove_constraint(index));
 
         Ok(())
     }
 
+    // Removes a constraint together with its parameter bindings
+    fn remove_constraint(&mut self, index: usize) {
+        let Some(constraint) = self.constraints.get(index).cloned() else {
+            return;
+        };
+        if self.is_driven(&constraint)
+            || self
+                .dimension_bindings
+                .iter()
+                .any(|b| b.constraint() == constraint)
+        {
+            self.touch();
+            self.dimension_bindings
+                .retain(|b| b.constraint() != constraint);
+            self.driven_constraints.retain(|c| c != &constraint);
+        }
+        self.constraints.remove(index);
+        self.record(Edit::DeleteConstraint(index, constraint));
+    }
+
     pub fn primitives(&self) -> BTreeMap<u64, PrimitiveCell> {
         self.primitives.clone()
     }
@@ -225,7 +969,7 @@ impl Sketch {
 
     pub fn get_loss(&mut self) -> f64 {
         let mut loss = 0.0;
-        for constraint in self.constraints.iter_mut() {
+        for constraint in self.driving_constraints() {
             loss += constraint.borrow().loss_value();
         }
         loss
@@ -236,7 +980,7 @@ impl Sketch {
             primitive.1.borrow_mut().zero_gradient();
         }
 
-        for constraint in self.constraints.iter_mut() {
+        for constraint in self.driving_constraints() {
             constraint.borrow_mut().update_gradient();
         }
 
@@ -258,16 +1002,18 @@ impl Sketch {
     }
 
     pub fn get_loss_per_constraint(&self) -> DVector<f64> {
-        let mut loss_per_constraint = DVector::zeros(self.constraints.len());
-        for (i, constraint) in self.constraints.iter().enumerate() {
+        let driving: Vec<_> = self.driving_constraints().collect();
+        let mut loss_per_constraint = DVector::zeros(driving.len());
+        for (i, constraint) in driving.into_iter().enumerate() {
             loss_per_constraint[i] = constraint.borrow().loss_value();
         }
         loss_per_constraint
     }
 
     pub fn get_jacobian(&self) -> DMatrix<f64> {
-        let mut jacobian = DMatrix::zeros(self.constraints.len(), self.get_n_dofs());
-        for (i, constraint) in self.constraints.iter().enumerate() {
+        let driving: Vec<_> = self.driving_constraints().collect();
+        let mut jacobian = DMatrix::zeros(driving.len(), self.get_n_dofs());
+        for (i, constraint) in driving.into_iter().enumerate() {
             // Zero the gradients of all primitives
             for primitive in self.primitives.iter() {
                 primitive.1.borrow_mut().zero_gradient();
@@ -291,6 +1037,7 @@ impl Sketch {
 
     pub fn set_data(&mut self, data: DVector<f64>) {
         assert!(data.len() == self.get_n_dofs());
+        self.touch();
         let mut i = 0;
         for primitive in self.primitives.iter_mut() {
             let n = primitive.1.borrow().get_data().len();
@@ -381,6 +1128,7 @@ mod tests {
         constraints::coincident::arc_end_point_coincident::ArcEndPointCoincident,
         examples::test_rectangle_rotated::RotatedRectangleDemo,
         primitives::{arc::Arc, point2::Point2},
+        solvers::{bfgs_solver::BFGSSolver, Solver},
     };
 
     use super::*;
@@ -464,4 +1212,130 @@ mod tests {
         sketch.get_loss_per_constraint();
         sketch.get_jacobian();
     }
+
+    #[test]
+    fn test_parameter_driven_dimensions() -> Result<(), ISOTopeError> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let center = sketch.add_point2(0.0, 2.0)?;
+        let circle = sketch.add_circle(center, 1.0)?;
+        let distance = sketch.constrain_distance_euclidean(a, b, 1.0)?;
+        let diameter = sketch.constrain_diameter(RadialCurve::Circle(circle), 1.0)?;
+        let distance = ConstraintCell::EuclideanDistance(distance);
+        let diameter = ConstraintCell::DiameterDimension(diameter);
+
+        sketch.set_parameter("width", Expression::parse("40")?)?;
+        sketch.set_parameter("hole_d", Expression::parse("width / 8")?)?;
+        sketch.bind_dimension(distance.clone(), 0, Expression::parse("width")?)?;
+        sketch.bind_dimension(diameter.clone(), 0, Expression::parse("hole_d")?)?;
+        assert_eq!(distance.dimensions(), vec![40.0]);
+        assert_eq!(diameter.dimensions(), vec![5.0]);
+
+        // Both dimensions follow the parameter
+        sketch.set_parameter("width", Expression::parse("48")?)?;
+        assert_eq!(distance.dimensions(), vec![48.0]);
+        assert_eq!(diameter.dimensions(), vec![6.0]);
+
+        // Cycles and invalid values are rejected without changing anything
+        assert!(matches!(
+            sketch.set_parameter("width", Expression::parse("hole_d * 8")?),
+            Err(ISOTopeError::CyclicParameters(_))
+        ));
+        assert!(sketch
+            .set_parameter("hole_d", Expression::parse("width / 0")?)
+            .is_err());
+        assert_eq!(diameter.dimensions(), vec![6.0]);
+        assert!(matches!(
+            sketch.remove_parameter("hole_d"),
+            Err(ISOTopeError::ParameterInUse(_))
+        ));
+
+        // Deleting the constraint releases its binding
+        sketch.delete_constraint(diameter)?;
+        assert_eq!(sketch.get_num_constraints(), 1);
+        sketch.remove_parameter("hole_d")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_driven_dimensions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut sketch = Sketch::new();
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let c = sketch.add_point2(0.0, 1.0)?;
+        sketch.constrain_fix_point(a.clone(), Vector2::new(0.0, 0.0))?;
+        sketch.constrain_distance_euclidean(a.clone(), b.clone(), 3.0)?;
+        let measured = ConstraintCell::EuclideanDistance(sketch.constrain_distance_euclidean(
+            b.clone(),
+            c.clone(),
+            1.0,
+        )?);
+
+        // The driven dimension is not part of the loss or the Jacobian
+        sketch.set_driven(&measured, true)?;
+        assert_eq!(measured.dimensions(), vec![2.0f64.sqrt()]);
+        assert_eq!(sketch.get_loss_per_constraint().len(), 2);
+        assert_eq!(sketch.get_jacobian().nrows(), 2);
+
+        BFGSSolver::new().solve(&mut sketch)?;
+        let distance = (b.borrow().data() - c.borrow().data()).norm();
+        assert!(((a.borrow().data() - b.borrow().data()).norm() - 3.0).abs() < 1e-5);
+        assert_eq!(measured.dimensions(), vec![distance]);
+
+        // Flipped to driving, it constrains the sketch again
+        sketch.set_driven(&measured, false)?;
+        measured.set_dimension(0, 2.0)?;
+        assert_eq!(sketch.get_jacobian().nrows(), 3);
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!(((b.borrow().data() - c.borrow().data()).norm() - 2.0).abs() < 1e-5);
+
+        // Only dimensions can be driven
+        let line1 = sketch.add_line(a.clone(), b.clone())?;
+        let line2 = sketch.add_line(b, c)?;
+        let parallel =
+            ConstraintCell::ParallelLines(sketch.constrain_parallel_lines(line1, line2)?);
+        assert!(matches!(
+            sketch.set_driven(&parallel, true),
+            Err(ISOTopeError::NotADimension(_))
+        ));
+        Ok(())
+    }
+    #[test]
+    fn test_convert_length_unit() -> Result<(), ISOTopeError> {
+        let mut sketch = Sketch::new();
+        sketch.set_length_unit(LengthUnit::Inch);
+
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(2.0, 0.0)?;
+        let c = sketch.add_point2(2.0, 1.0)?;
+        let arc = sketch.add_arc(a.clone(), 0.5, false, 0.0, 1.0)?;
+        let distance = sketch.constrain_distance_euclidean(
+            a.clone(),
+            b.clone(),
+            sketch.length(Length::millimeters(50.8)),
+        )?;
+        let angle =
+            sketch.constrain_angle_between_points(b, c.clone(), a.clone(), Angle::degrees(30.0))?;
+        let height = sketch.constrain_distance_euclidean(a.clone(), c, 1.0)?;
+        let height = ConstraintCell::EuclideanDistance(height);
+        sketch.set_parameter("h", Expression::parse("1")?)?;
+        sketch.bind_dimension(height.clone(), 0, Expression::parse("h")?)?;
+        assert!((distance.borrow().desired_distance() - 2.0).abs() < 1e-12);
+
+        sketch.convert_length_unit(LengthUnit::Millimeter)?;
+        assert_eq!(sketch.length_unit(), LengthUnit::Millimeter);
+        assert!((distance.borrow().desired_distance() - 50.8).abs() < 1e-12);
+        assert!((height.dimensions()[0] - 25.4).abs() < 1e-12);
+        assert!((angle.borrow().desired_angle() - Angle::degrees(30.0).to_radians()).abs() < 1e-12);
+        assert!((arc.borrow().radius() - 12.7).abs() < 1e-12);
+        assert_eq!(arc.borrow().end_angle(), 1.0);
+
+        // The parameter keeps its value, the dimension still follows it
+        sketch.set_parameter("h", Expression::parse("2")?)?;
+        assert!((height.dimensions()[0] - 50.8).abs() < 1e-12);
+        Ok(())
+    }
 }
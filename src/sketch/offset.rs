@@ -0,0 +1,986 @@
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::Vector2;
+
+use crate::constraints::continuity::g1_continuity::G1Continuity;
+use crate::constraints::continuity::{ContinuityCurve, CurveEnd};
+use crate::constraints::radius::RadialCurve;
+use crate::constraints::ConstraintCell;
+use crate::decompose::segment::Segment;
+use crate::error::ISOTopeError;
+use crate::intersections::{angle_from, intersections, line_line, line_parameter};
+use crate::primitives::arc::Arc;
+use crate::primitives::line::Line;
+use crate::primitives::point2::Point2;
+use crate::primitives::{Primitive, PrimitiveCell};
+
+use super::fillet::{angle_of, arc_end_coincident};
+use super::trim::CurveEdit;
+use super::Sketch;
+
+// Points closer than this are the same point
+const TOLERANCE: f64 = 1e-9;
+// Ends of segments closer than this are connected, solved coincidences are not exact
+const CHAIN_TOLERANCE: f64 = 1e-6;
+// Relative slack when checking that a piece of the offset keeps its distance to the profile
+const DISTANCE_TOLERANCE: f64 = 1e-6;
+
+// How the offsets of two segments are joined where they move apart at a corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerJoin {
+    // An arc around the corner at the offset distance
+    Round,
+    // Both offsets are carried on along their tangents until they meet
+    Miter,
+}
+
+impl Sketch {
+    // Adds the offset of a chain of lines and arcs at the given distance. A closed profile grows
+    // for a positive distance and shrinks for a negative one, an open chain is offset to the left
+    // of its direction for a positive distance. Corners are joined as asked, and the parts of the
+    // offset that come closer to the profile than the distance, like the loops an inward offset
+    // forms at narrow places, are cut away. This may leave several chains. If parametric, offset
+    // lines are held at their distance from the segments they come from, offset arcs share the
+    // center of theirs and follow their radius, round joins are centered on the corner with the
+    // distance as radius and the offset is held tangent where it runs on smoothly.
+    pub fn offset(
+        &mut self,
+        segments: &[Segment],
+        distance: f64,
+        join: CornerJoin,
+        parametric: bool,
+    ) -> Result<CurveEdit, ISOTopeError> {
+        let (segments, closed) = chain(segments)?;
+        let profile: Vec<Piece> = segments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, segment)| Piece::from_segment(segment, Source::Segment(index)))
+            .collect();
+
+        // The distance to the left of the direction of the segments
+        let area: f64 = profile.iter().map(Piece::area).sum();
+        let left = if closed && area > 0.0 {
+            -distance
+        } else {
+            distance
+        };
+
+        let chains = offset_chains(&profile, closed, left, join);
+        if chains.is_empty() {
+            return Err(ISOTopeError::OffsetVanishes(distance));
+        }
+
+        // What the offset is held to if it stays parametric
+        let lines: Vec<Option<(Rc<RefCell<Line>>, bool)>> = segments
+            .iter()
+            .map(|segment| self.segment_line(segment))
+            .collect();
+        let arcs: Vec<Option<Rc<RefCell<Arc>>>> = segments
+            .iter()
+            .map(|segment| self.segment_arc(segment))
+            .collect();
+        let corners: Vec<Option<Rc<RefCell<Point2>>>> = (0..segments.len())
+            .map(|index| corner_point(&segments, index))
+            .collect();
+
+        self.curve_edit(|sketch, _| {
+            for chain in chains.iter() {
+                let (Some(first), Some(last)) = (chain.first(), chain.last()) else {
+                    continue;
+                };
+                let mut points = vec![sketch.add_point2(first.start.x, first.start.y)?];
+                for piece in chain.iter().take(chain.len() - 1) {
+                    points.push(sketch.add_point2(piece.end.x, piece.end.y)?);
+                }
+                let closed_chain = chain.len() > 1 && near(last.end, first.start);
+                if closed_chain {
+                    points.push(points[0].clone());
+                } else {
+                    points.push(sketch.add_point2(last.end.x, last.end.y)?);
+                }
+
+                let mut curves = Vec::new();
+                for (piece, ends) in chain.iter().zip(points.windows(2)) {
+                    let (start, end) = (ends[0].clone(), ends[1].clone());
+                    match piece.shape {
+                        Shape::Line => {
+                            let line = sketch.add_line(start, end)?;
+                            curves.push(PrimitiveCell::Line(line.clone()));
+                            let original = match piece.source {
+                                Source::Segment(index) if parametric => lines[index].clone(),
+                                _ => None,
+                            };
+                            if let Some((original, reversed)) = original {
+                                sketch.constrain_parallel_lines(original.clone(), line.clone())?;
+                                let signed = if reversed { -left } else { left };
+                                sketch
+                                    .constrain_distance_line_line(original, line, signed, true)?;
+                            }
+                        }
+                        Shape::Arc {
+                            center,
+                            radius,
+                            clockwise,
+                        } => {
+                            let shared = match (piece.source, parametric) {
+                                (Source::Segment(index), true) => match &segments[index] {
+                                    Segment::Arc(original) => Some(original.center()),
+                                    _ => None,
+                                },
+                                (Source::Corner(index), true) => corners[index].clone(),
+                                _ => None,
+                            };
+                            let center_point = match shared {
+                                Some(point) => point,
+                                None => sketch.add_point2(center.x, center.y)?,
+                            };
+                            let arc = sketch.add_arc(
+                                center_point,
+                                radius,
+                                clockwise,
+                                angle_of(piece.start - center),
+                                angle_of(piece.end - center),
+                            )?;
+                            sketch.add_constraint(arc_end_coincident(
+                                &arc,
+                                CurveEnd::Start,
+                                start,
+                            ))?;
+                            sketch.add_constraint(arc_end_coincident(&arc, CurveEnd::End, end))?;
+                            curves.push(PrimitiveCell::Arc(arc.clone()));
+                            // Offset arcs keep their distance to the arc they come from
+                            let original = match piece.source {
+                                Source::Segment(index) if parametric => arcs[index].clone(),
+                                _ => None,
+                            };
+                            if let Some(original) = original {
+                                let difference = radius - original.borrow().radius();
+                                sketch.constrain_radius_difference(
+                                    RadialCurve::Arc(original),
+                                    RadialCurve::Arc(arc),
+                                    difference,
+                                )?;
+                            } else if parametric && !matches!(piece.source, Source::Bridge) {
+                                sketch.constrain_radius(RadialCurve::Arc(arc), radius)?;
+                            }
+                        }
+                    }
+                }
+
+                // Tangency where the offset runs on smoothly, which the distances alone barely hold
+                let joints = if closed_chain {
+                    chain.len()
+                } else {
+                    chain.len() - 1
+                };
+                for k in (0..joints).filter(|_| parametric) {
+                    let next = (k + 1) % chain.len();
+                    let smooth = chain[k]
+                        .tangent(chain[k].end)
+                        .dot(&chain[next].tangent(chain[next].start))
+                        > 1.0 - CHAIN_TOLERANCE;
+                    if let (true, Some(first), Some(second)) = (
+                        smooth,
+                        continuity(&curves[k], CurveEnd::End),
+                        continuity(&curves[next], CurveEnd::Start),
+                    ) {
+                        sketch.add_constraint(ConstraintCell::G1Continuity(Rc::new(
+                            RefCell::new(G1Continuity::new(first, second)),
+                        )))?;
+                    }
+                }
+
+                // The free ends of an open offset stay across from the ends of the chain
+                if parametric && !closed {
+                    let ends = [
+                        (segments.first(), first.start, points.first(), true),
+                        (segments.last(), last.end, points.last(), false),
+                    ];
+                    for (segment, position, point, start) in ends {
+                        let (Some(Segment::Line(line)), Some(point)) = (segment, point) else {
+                            continue;
+                        };
+                        let original = if start { line.start() } else { line.end() };
+                        let across = original.borrow().data();
+                        if ((position - across).norm() - left.abs()).abs() <= CHAIN_TOLERANCE {
+                            sketch.constrain_distance_euclidean(
+                                original,
+                                point.clone(),
+                                left.abs(),
+                            )?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // The line of the sketch that is the segment, and whether it runs the other way
+    fn segment_line(&self, segment: &Segment) -> Option<(Rc<RefCell<Line>>, bool)> {
+        let Segment::Line(segment) = segment else {
+            return None;
+        };
+        self.primitives.values().find_map(|primitive| {
+            let PrimitiveCell::Line(line) = primitive else {
+                return None;
+            };
+            let (start, end) = (line.borrow().start(), line.borrow().end());
+            if Rc::ptr_eq(&start, &segment.start()) && Rc::ptr_eq(&end, &segment.end()) {
+                Some((line.clone(), false))
+            } else if Rc::ptr_eq(&start, &segment.end()) && Rc::ptr_eq(&end, &segment.start()) {
+                Some((line.clone(), true))
+            } else {
+                None
+            }
+        })
+    }
+
+    // The arc of the sketch that is the segment, in either direction
+    fn segment_arc(&self, segment: &Segment) -> Option<Rc<RefCell<Arc>>> {
+        let Segment::Arc(segment) = segment else {
+            return None;
+        };
+        let reversed = segment.reverse();
+        self.primitives.values().find_map(|primitive| {
+            let PrimitiveCell::Arc(arc) = primitive else {
+                return None;
+            };
+            let same = |other: &Arc| {
+                let arc = arc.borrow();
+                Rc::ptr_eq(&arc.center(), &other.center())
+                    && arc.clockwise() == other.clockwise()
+                    && arc.data == other.data
+            };
+            (same(segment) || same(&reversed)).then(|| arc.clone())
+        })
+    }
+}
+
+// Where a piece of the offset comes from
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    // The segment with this index
+    Segment(usize),
+    // The round join at the corner after the segment with this index
+    Corner(usize),
+    // A line across a gap, like the tangent of a miter at an arc
+    Bridge,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    Line,
+    Arc {
+        center: Vector2<f64>,
+        radius: f64,
+        clockwise: bool,
+    },
+}
+
+// A line or arc from start to end, which is what the offset is built from
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    start: Vector2<f64>,
+    end: Vector2<f64>,
+    shape: Shape,
+    source: Source,
+}
+
+impl Piece {
+    fn from_segment(segment: &Segment, source: Source) -> Option<Self> {
+        let shape = match segment {
+            Segment::Line(_) => Shape::Line,
+            Segment::Arc(arc) => Shape::Arc {
+                center: arc.center().borrow().data(),
+                radius: arc.radius(),
+                clockwise: arc.clockwise(),
+            },
+            _ => return None,
+        };
+        Some(Piece {
+            start: segment.get_start(),
+            end: segment.get_end(),
+            shape,
+            source,
+        })
+    }
+
+    fn line(start: Vector2<f64>, end: Vector2<f64>, source: Source) -> Self {
+        Piece {
+            start,
+            end,
+            shape: Shape::Line,
+            source,
+        }
+    }
+
+    // The piece moved to its left by the distance. Arcs that shrink to nothing are gone.
+    fn offset(&self, distance: f64) -> Option<Self> {
+        match self.shape {
+            Shape::Line => {
+                let direction = (self.end - self.start).normalize();
+                let normal = Vector2::new(-direction.y, direction.x);
+                Some(Piece::line(
+                    self.start + distance * normal,
+                    self.end + distance * normal,
+                    self.source,
+                ))
+            }
+            Shape::Arc {
+                center,
+                radius,
+                clockwise,
+            } => {
+                // The center is on the left of a counterclockwise arc
+                let offset_radius = if clockwise {
+                    radius + distance
+                } else {
+                    radius - distance
+                };
+                if offset_radius <= TOLERANCE {
+                    return None;
+                }
+                let scale = offset_radius / radius;
+                Some(Piece {
+                    start: center + scale * (self.start - center),
+                    end: center + scale * (self.end - center),
+                    shape: Shape::Arc {
+                        center,
+                        radius: offset_radius,
+                        clockwise,
+                    },
+                    source: self.source,
+                })
+            }
+        }
+    }
+
+    // The direction of travel at the point on the piece
+    fn tangent(&self, point: Vector2<f64>) -> Vector2<f64> {
+        match self.shape {
+            Shape::Line => (self.end - self.start).normalize(),
+            Shape::Arc {
+                center, clockwise, ..
+            } => {
+                let radial = (point - center).normalize();
+                let tangent = Vector2::new(-radial.y, radial.x);
+                if clockwise {
+                    -tangent
+                } else {
+                    tangent
+                }
+            }
+        }
+    }
+
+    // How far along the piece the point is, 0 at the start and `extent` at the end
+    fn parameter(&self, point: Vector2<f64>) -> f64 {
+        match self.shape {
+            Shape::Line => line_parameter(self.start, self.end, point),
+            Shape::Arc {
+                center, clockwise, ..
+            } => angle_from(center, angle_of(self.start - center), clockwise, point),
+        }
+    }
+
+    fn extent(&self) -> f64 {
+        match self.shape {
+            Shape::Line => 1.0,
+            Shape::Arc { .. } => self.parameter(self.end),
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        match self.shape {
+            Shape::Line => (self.end - self.start).norm(),
+            Shape::Arc { radius, .. } => radius,
+        }
+    }
+
+    fn midpoint(&self) -> Vector2<f64> {
+        match self.shape {
+            Shape::Line => 0.5 * (self.start + self.end),
+            Shape::Arc {
+                center,
+                radius,
+                clockwise,
+            } => {
+                let half = 0.5 * self.extent();
+                let angle = angle_of(self.start - center) + if clockwise { -half } else { half };
+                center + radius * Vector2::new(angle.cos(), angle.sin())
+            }
+        }
+    }
+
+    fn distance_to(&self, point: Vector2<f64>) -> f64 {
+        let to_ends = (point - self.start).norm().min((point - self.end).norm());
+        match self.shape {
+            Shape::Line => {
+                let t = line_parameter(self.start, self.end, point).clamp(0.0, 1.0);
+                (point - (self.start + t * (self.end - self.start))).norm()
+            }
+            Shape::Arc { center, radius, .. } => {
+                if self.parameter(point) <= self.extent() {
+                    ((point - center).norm() - radius).abs()
+                } else {
+                    to_ends
+                }
+            }
+        }
+    }
+
+    // Twice the signed area this piece contributes to a closed profile
+    fn area(&self) -> f64 {
+        match self.shape {
+            Shape::Line => self.start.perp(&self.end),
+            Shape::Arc {
+                center,
+                radius,
+                clockwise,
+            } => {
+                let sweep = if clockwise {
+                    -self.extent()
+                } else {
+                    self.extent()
+                };
+                radius * radius * sweep + center.perp(&(self.end - self.start))
+            }
+        }
+    }
+
+    fn to_primitive(self) -> Primitive {
+        let point = |p: Vector2<f64>| Rc::new(RefCell::new(Point2::new(p.x, p.y)));
+        match self.shape {
+            Shape::Line => Primitive::Line(Line::new(point(self.start), point(self.end))),
+            Shape::Arc {
+                center,
+                radius,
+                clockwise,
+            } => Primitive::Arc(Arc::new(
+                point(center),
+                radius,
+                clockwise,
+                angle_of(self.start - center),
+                angle_of(self.end - center),
+            )),
+        }
+    }
+
+    // The piece cut into parts at the points that lie inside it
+    fn split(&self, cuts: &[Vector2<f64>]) -> Vec<Piece> {
+        let tolerance = TOLERANCE / self.scale();
+        let extent = self.extent();
+        let mut cuts: Vec<(f64, Vector2<f64>)> = cuts
+            .iter()
+            .map(|cut| (self.parameter(*cut), *cut))
+            .filter(|(t, _)| *t > tolerance && *t < extent - tolerance)
+            .collect();
+        cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        cuts.dedup_by(|a, b| a.0 - b.0 <= tolerance);
+
+        let mut parts = Vec::new();
+        let mut start = self.start;
+        for (_, cut) in cuts {
+            parts.push(Piece {
+                start,
+                end: cut,
+                ..*self
+            });
+            start = cut;
+        }
+        parts.push(Piece { start, ..*self });
+        parts
+    }
+}
+
+// The segments turned so that each one starts where the one before ends, and whether the chain
+// comes back to its start
+fn chain(segments: &[Segment]) -> Result<(Vec<Segment>, bool), ISOTopeError> {
+    let mut chain: Vec<Segment> = Vec::new();
+    for segment in segments {
+        if !matches!(segment, Segment::Line(_) | Segment::Arc(_)) {
+            return Err(ISOTopeError::InvalidOffsetChain);
+        }
+        // The first segment may need to be turned around to reach the second
+        if let [first] = chain.as_mut_slice() {
+            let (start, end) = (first.get_start(), first.get_end());
+            let touches =
+                |point| near(point, segment.get_start()) || near(point, segment.get_end());
+            if !touches(end) && touches(start) {
+                *first = first.reverse();
+            }
+        }
+        match chain.last() {
+            None => chain.push(segment.clone()),
+            Some(last) if near(last.get_end(), segment.get_start()) => chain.push(segment.clone()),
+            Some(last) if near(last.get_end(), segment.get_end()) => chain.push(segment.reverse()),
+            Some(_) => return Err(ISOTopeError::InvalidOffsetChain),
+        }
+    }
+    let closed = match (chain.first(), chain.last()) {
+        (Some(first), Some(last)) => chain.len() > 1 && near(last.get_end(), first.get_start()),
+        _ => return Err(ISOTopeError::InvalidOffsetChain),
+    };
+    Ok((chain, closed))
+}
+
+// The point of the sketch at the corner after the segment with this index, if a line ends there
+fn corner_point(segments: &[Segment], index: usize) -> Option<Rc<RefCell<Point2>>> {
+    match (&segments[index], segments.get((index + 1) % segments.len())) {
+        (Segment::Line(line), _) => Some(line.end()),
+        (_, Some(Segment::Line(line))) => Some(line.start()),
+        _ => None,
+    }
+}
+
+// The offset of the profile by the distance to its left, as chains of pieces
+fn offset_chains(profile: &[Piece], closed: bool, left: f64, join: CornerJoin) -> Vec<Vec<Piece>> {
+    let count = profile.len();
+    let mut offsets: Vec<(usize, Piece)> = profile
+        .iter()
+        .enumerate()
+        .filter_map(|(index, piece)| Some((index, piece.offset(left)?)))
+        .collect();
+
+    // Join each offset to the next one, which closes the gaps at the outside of corners
+    let mut joins = vec![Vec::new(); offsets.len()];
+    for k in 0..offsets.len() {
+        let next = if k + 1 < offsets.len() {
+            k + 1
+        } else if closed && offsets.len() > 1 {
+            0
+        } else {
+            break;
+        };
+        let (index, next_index) = (offsets[k].0, offsets[next].0);
+        let adjacent = (index + 1) % count == next_index;
+        let (mut first, mut second) = (offsets[k].1, offsets[next].1);
+        joins[k] = if adjacent {
+            join_corner(
+                &mut first,
+                &mut second,
+                profile[index].end,
+                left,
+                join,
+                index,
+            )
+        } else {
+            // Arcs in between shrank to nothing
+            vec![Piece::line(first.end, second.start, Source::Bridge)]
+        };
+        offsets[k].1.end = first.end;
+        offsets[next].1.start = second.start;
+    }
+    let raw: Vec<Piece> = offsets
+        .into_iter()
+        .zip(joins)
+        .flat_map(|((_, piece), joins)| std::iter::once(piece).chain(joins))
+        .filter(|piece| piece.scale() > TOLERANCE && !near(piece.start, piece.end))
+        .collect();
+
+    // Where the raw offset crosses itself, the parts in between came too close to the profile
+    let primitives: Vec<Primitive> = raw.iter().map(|piece| piece.to_primitive()).collect();
+    let limit = left.abs() - DISTANCE_TOLERANCE * left.abs().max(1.0);
+    let mut pieces = Vec::new();
+    for (i, piece) in raw.iter().enumerate() {
+        let cuts: Vec<Vector2<f64>> = primitives
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .flat_map(|(_, other)| intersections(primitives[i].clone(), other.clone()))
+            .collect();
+        pieces.extend(piece.split(&cuts).into_iter().filter(|part| {
+            let midpoint = part.midpoint();
+            profile
+                .iter()
+                .all(|original| original.distance_to(midpoint) >= limit)
+        }));
+    }
+
+    // What is left is put back together where the ends meet
+    let mut chains: Vec<Vec<Piece>> = Vec::new();
+    for piece in pieces {
+        match chains.last_mut() {
+            Some(chain) if chain.last().is_some_and(|last| near(last.end, piece.start)) => {
+                chain.push(piece)
+            }
+            _ => chains.push(vec![piece]),
+        }
+    }
+    while let Some((a, b)) = (0..chains.len())
+        .flat_map(|a| (0..chains.len()).map(move |b| (a, b)))
+        .find(|(a, b)| {
+            a != b
+                && match (chains[*a].last(), chains[*b].first()) {
+                    (Some(last), Some(first)) => near(last.end, first.start),
+                    _ => false,
+                }
+        })
+    {
+        let following = chains.remove(b);
+        let a = if b < a { a - 1 } else { a };
+        chains[a].extend(following);
+    }
+    chains
+}
+
+// The pieces that join the offsets of two segments meeting at the corner. Where the offsets
+// overlap instead, the round join forms a small loop that is cut away later.
+fn join_corner(
+    first: &mut Piece,
+    second: &mut Piece,
+    corner: Vector2<f64>,
+    left: f64,
+    join: CornerJoin,
+    index: usize,
+) -> Vec<Piece> {
+    let (end, start) = (first.end, second.start);
+    if (end - start).norm() <= CHAIN_TOLERANCE {
+        second.start = end;
+        return Vec::new();
+    }
+    let (tangent1, tangent2) = (first.tangent(end), second.tangent(start));
+    let opens = tangent1.perp(&tangent2) * left < 0.0;
+    if opens && join == CornerJoin::Miter {
+        let miter = line_line(end, end + tangent1, start, start + tangent2)
+            .into_iter()
+            .find(|miter| {
+                (miter - end).dot(&tangent1) > 0.0 && (start - miter).dot(&tangent2) > 0.0
+            });
+        if let Some(miter) = miter {
+            let mut joins = Vec::new();
+            match first.shape {
+                Shape::Line => first.end = miter,
+                Shape::Arc { .. } => joins.push(Piece::line(end, miter, Source::Bridge)),
+            }
+            match second.shape {
+                Shape::Line => second.start = miter,
+                Shape::Arc { .. } => joins.push(Piece::line(miter, start, Source::Bridge)),
+            }
+            return joins;
+        }
+    }
+    // The round join takes the short way around the corner
+    let clockwise = (angle_of(start - corner) - angle_of(end - corner)).rem_euclid(2.0 * PI) > PI;
+    vec![Piece {
+        start: end,
+        end: start,
+        shape: Shape::Arc {
+            center: corner,
+            radius: (end - corner).norm(),
+            clockwise,
+        },
+        source: Source::Corner(index),
+    }]
+}
+
+fn continuity(curve: &PrimitiveCell, end: CurveEnd) -> Option<ContinuityCurve> {
+    match curve {
+        PrimitiveCell::Line(line) => Some(ContinuityCurve::Line(line.clone(), end)),
+        PrimitiveCell::Arc(arc) => Some(ContinuityCurve::Arc(arc.clone(), end)),
+        _ => None,
+    }
+}
+
+fn near(a: Vector2<f64>, b: Vector2<f64>) -> bool {
+    (a - b).norm() <= CHAIN_TOLERANCE
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintCell},
+        decompose::segment::Segment,
+        error::ISOTopeError,
+        primitives::{arc::Arc, PrimitiveCell},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    use super::CornerJoin;
+
+    // A closed polygon through the corners, counterclockwise
+    fn polygon(sketch: &mut Sketch, corners: &[(f64, f64)]) -> Result<Vec<Segment>, ISOTopeError> {
+        let points = corners
+            .iter()
+            .map(|(x, y)| sketch.add_point2(*x, *y))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut segments = Vec::new();
+        for (i, start) in points.iter().enumerate() {
+            let end = points[(i + 1) % points.len()].clone();
+            let line = sketch.add_line(start.clone(), end)?;
+            segments.push(Segment::Line(line.borrow().clone()));
+        }
+        Ok(segments)
+    }
+
+    fn count(sketch: &Sketch, ids: &[u64]) -> (usize, usize) {
+        let (mut lines, mut arcs) = (0, 0);
+        for id in ids {
+            match sketch.get_primitive_by_id(*id) {
+                Some(PrimitiveCell::Line(_)) => lines += 1,
+                Some(PrimitiveCell::Arc(_)) => arcs += 1,
+                _ => {}
+            }
+        }
+        (lines, arcs)
+    }
+
+    // The corners of the lines among the primitives
+    fn line_ends(sketch: &Sketch, ids: &[u64]) -> Vec<Vector2<f64>> {
+        let mut ends = Vec::new();
+        for id in ids {
+            if let Some(PrimitiveCell::Line(line)) = sketch.get_primitive_by_id(*id) {
+                ends.push(line.borrow().start().borrow().data());
+                ends.push(line.borrow().end().borrow().data());
+            }
+        }
+        ends
+    }
+
+    fn contains(points: &[Vector2<f64>], x: f64, y: f64) -> bool {
+        points
+            .iter()
+            .any(|point| (point - Vector2::new(x, y)).norm() < 1e-9)
+    }
+
+    #[test]
+    fn test_offset_square() -> Result<(), Box<dyn Error>> {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+
+        // Growing with rounded corners
+        let mut sketch = Sketch::new();
+        let segments = polygon(&mut sketch, &square)?;
+        let edit = sketch.offset(&segments, 0.5, CornerJoin::Round, false)?;
+        assert_eq!(count(&sketch, &edit.added), (4, 4));
+        let ends = line_ends(&sketch, &edit.added);
+        assert!(contains(&ends, 0.0, -0.5));
+        assert!(contains(&ends, 2.5, 2.0));
+        assert!(sketch.get_loss() < 1e-16);
+
+        // Growing with sharp corners, the segments may come in any direction
+        let mut sketch = Sketch::new();
+        let mut segments = polygon(&mut sketch, &square)?;
+        segments.reverse();
+        segments[1] = segments[1].reverse();
+        let edit = sketch.offset(&segments, 0.5, CornerJoin::Miter, false)?;
+        assert_eq!(count(&sketch, &edit.added), (4, 0));
+        let ends = line_ends(&sketch, &edit.added);
+        for (x, y) in [(-0.5, -0.5), (2.5, -0.5), (2.5, 2.5), (-0.5, 2.5)] {
+            assert!(contains(&ends, x, y));
+        }
+
+        // Shrinking leaves sharp corners either way
+        let mut sketch = Sketch::new();
+        let segments = polygon(&mut sketch, &square)?;
+        let edit = sketch.offset(&segments, -0.5, CornerJoin::Round, false)?;
+        assert_eq!(count(&sketch, &edit.added), (4, 0));
+        let ends = line_ends(&sketch, &edit.added);
+        for (x, y) in [(0.5, 0.5), (1.5, 0.5), (1.5, 1.5), (0.5, 1.5)] {
+            assert!(contains(&ends, x, y));
+        }
+
+        assert!(matches!(
+            sketch.offset(&segments, -1.5, CornerJoin::Round, false),
+            Err(ISOTopeError::OffsetVanishes(_))
+        ));
+        assert!(matches!(
+            sketch.offset(
+                &[segments[0].clone(), segments[2].clone()],
+                0.5,
+                CornerJoin::Round,
+                false
+            ),
+            Err(ISOTopeError::InvalidOffsetChain)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_self_intersection() -> Result<(), Box<dyn Error>> {
+        // Two squares joined by a narrow neck, which is cut away when shrinking
+        let mut sketch = Sketch::new();
+        let segments = polygon(
+            &mut sketch,
+            &[
+                (0.0, 0.0),
+                (2.0, 0.0),
+                (2.0, 0.8),
+                (3.0, 0.8),
+                (3.0, 0.0),
+                (5.0, 0.0),
+                (5.0, 2.0),
+                (3.0, 2.0),
+                (3.0, 1.2),
+                (2.0, 1.2),
+                (2.0, 2.0),
+                (0.0, 2.0),
+            ],
+        )?;
+        let edit = sketch.offset(&segments, -0.3, CornerJoin::Round, false)?;
+        // Each square bulges a little towards the neck, where the corners of the neck are rounded
+        assert_eq!(count(&sketch, &edit.added), (10, 4));
+        let ends = line_ends(&sketch, &edit.added);
+        assert!(ends
+            .iter()
+            .all(|end| end.x <= 1.7 + 1e-9 || end.x >= 3.3 - 1e-9));
+        for (x, y) in [
+            (0.3, 0.3),
+            (1.7, 0.3),
+            (1.7, 1.7),
+            (0.3, 1.7),
+            (3.3, 0.3),
+            (4.7, 0.3),
+            (4.7, 1.7),
+            (3.3, 1.7),
+        ] {
+            assert!(contains(&ends, x, y));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_parametric() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        let segments = polygon(&mut sketch, &square)?;
+        for segment in segments.iter() {
+            if let Segment::Line(line) = segment {
+                sketch.constrain_fix_point(line.start(), line.start().borrow().data())?;
+            }
+        }
+        let edit = sketch.offset(&segments, 0.5, CornerJoin::Round, true)?;
+        // The rounded corners are centered on the corners of the square
+        assert_eq!(count(&sketch, &edit.added), (4, 4));
+        assert!(sketch.get_loss() < 1e-16);
+
+        // All distances and radii are the offset, changing them moves the offset
+        let mut dimensions = 0;
+        for constraint in sketch.constraints() {
+            if matches!(
+                constraint,
+                ConstraintCell::LineLineDistance(_) | ConstraintCell::RadiusDimension(_)
+            ) {
+                assert!((constraint.dimensions()[0].abs() - 0.5).abs() < 1e-12);
+                let value = constraint.dimensions()[0] * 2.0;
+                constraint.set_dimension(0, value)?;
+                dimensions += 1;
+            }
+        }
+        assert_eq!(dimensions, 8);
+        BFGSSolver::new().solve(&mut sketch)?;
+        let ends = line_ends(&sketch, &edit.added);
+        assert!(contains_near(&ends, 0.0, -1.0));
+        assert!(contains_near(&ends, -1.0, 2.0));
+        Ok(())
+    }
+
+    // A half disk of radius 1 around the origin, the arc on top, in either direction
+    fn half_disk(
+        sketch: &mut Sketch,
+        clockwise: bool,
+    ) -> Result<(Vec<Segment>, Rc<RefCell<Arc>>), ISOTopeError> {
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let left = sketch.add_point2(-1.0, 0.0)?;
+        let right = sketch.add_point2(1.0, 0.0)?;
+        let (line, arc) = if clockwise {
+            (
+                sketch.add_line(right, left)?,
+                sketch.add_arc(center, 1.0, true, PI, 0.0)?,
+            )
+        } else {
+            (
+                sketch.add_line(left, right)?,
+                sketch.add_arc(center, 1.0, false, 0.0, PI)?,
+            )
+        };
+        let segments = vec![
+            Segment::Line(line.borrow().clone()),
+            Segment::Arc(arc.borrow().clone()),
+        ];
+        Ok((segments, arc))
+    }
+
+    fn arc_radii(sketch: &Sketch, ids: &[u64]) -> Vec<f64> {
+        ids.iter()
+            .filter_map(|id| match sketch.get_primitive_by_id(*id) {
+                Some(PrimitiveCell::Arc(arc)) => Some(arc.borrow().radius()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offset_line_arc() -> Result<(), Box<dyn Error>> {
+        for clockwise in [false, true] {
+            // Rounded corners where the arc meets the line
+            let mut sketch = Sketch::new();
+            let (segments, _) = half_disk(&mut sketch, clockwise)?;
+            let edit = sketch.offset(&segments, 0.5, CornerJoin::Round, false)?;
+            assert_eq!(count(&sketch, &edit.added), (1, 3));
+            assert!(arc_radii(&sketch, &edit.added)
+                .iter()
+                .all(|radius| (radius - 1.5).abs() < 1e-9 || (radius - 0.5).abs() < 1e-9));
+
+            // Sharp corners carry the arc on along its tangent
+            let mut sketch = Sketch::new();
+            let (segments, _) = half_disk(&mut sketch, clockwise)?;
+            let edit = sketch.offset(&segments, 0.5, CornerJoin::Miter, false)?;
+            assert_eq!(count(&sketch, &edit.added), (3, 1));
+            assert!((arc_radii(&sketch, &edit.added)[0] - 1.5).abs() < 1e-9);
+            let ends = line_ends(&sketch, &edit.added);
+            for (x, y) in [(-1.5, -0.5), (1.5, -0.5), (-1.5, 0.0), (1.5, 0.0)] {
+                assert!(contains(&ends, x, y));
+            }
+
+            // Shrinking brings the arc closer to its center
+            let mut sketch = Sketch::new();
+            let (segments, _) = half_disk(&mut sketch, clockwise)?;
+            let edit = sketch.offset(&segments, -0.25, CornerJoin::Round, false)?;
+            assert_eq!(count(&sketch, &edit.added), (1, 1));
+            assert!((arc_radii(&sketch, &edit.added)[0] - 0.75).abs() < 1e-9);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_parametric_arc() -> Result<(), Box<dyn Error>> {
+        for clockwise in [false, true] {
+            let mut sketch = Sketch::new();
+            let (segments, arc) = half_disk(&mut sketch, clockwise)?;
+            let radius = sketch.constrain_radius(RadialCurve::Arc(arc.clone()), 1.0)?;
+            let edit = sketch.offset(&segments, 0.5, CornerJoin::Miter, true)?;
+            assert!(sketch.get_loss() < 1e-16);
+            let differences = sketch
+                .constraints()
+                .iter()
+                .filter(|constraint| matches!(constraint, ConstraintCell::RadiusDifference(_)))
+                .count();
+            assert_eq!(differences, 1);
+
+            // The offset arc follows its source
+            radius.borrow_mut().set_desired_radius(1.2);
+            BFGSSolver::new().solve(&mut sketch)?;
+            assert!((arc.borrow().radius() - 1.2).abs() < 1e-4);
+            assert!((arc_radii(&sketch, &edit.added)[0] - 1.7).abs() < 1e-4);
+        }
+        Ok(())
+    }
+
+    fn contains_near(points: &[Vector2<f64>], x: f64, y: f64) -> bool {
+        points
+            .iter()
+            .any(|point| (point - Vector2::new(x, y)).norm() < 1e-4)
+    }
+}
@@ -0,0 +1,491 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use nalgebra::{Matrix2, Rotation2, Vector2};
+
+use crate::constraints::fix_point::FixPoint;
+use crate::constraints::pattern::PatternKind;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::parameters::expression::Expression;
+use crate::primitives::PrimitiveCell;
+use crate::solvers::Solver;
+use crate::units::{Angle, Quantity};
+
+use super::history::Edit;
+use super::Sketch;
+
+// A similarity transform of the plane, x -> linear * x + translation, where the linear part is a
+// rotation, possibly mirrored, times a uniform scale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    linear: Matrix2<f64>,
+    translation: Vector2<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            linear: Matrix2::identity(),
+            translation: Vector2::zeros(),
+        }
+    }
+
+    pub fn translation(offset: Vector2<f64>) -> Self {
+        Self {
+            linear: Matrix2::identity(),
+            translation: offset,
+        }
+    }
+
+    pub fn rotation(center: Vector2<f64>, angle: Angle) -> Self {
+        Self::around(center, *Rotation2::new(angle.to_radians()).matrix())
+    }
+
+    pub fn scaling(center: Vector2<f64>, factor: f64) -> Result<Self, ISOTopeError> {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(ISOTopeError::InvalidTransform(format!(
+                "the scale factor must be positive, not {}",
+                factor
+            )));
+        }
+        Ok(Self::around(center, Matrix2::identity() * factor))
+    }
+
+    // Mirrors at the line through the point in the given direction
+    pub fn mirror(point: Vector2<f64>, direction: Vector2<f64>) -> Result<Self, ISOTopeError> {
+        let direction = direction.normalize();
+        if !direction.iter().all(|x| x.is_finite()) {
+            return Err(ISOTopeError::InvalidTransform(
+                "the mirror axis needs a direction".to_string(),
+            ));
+        }
+        let (c, s) = (direction.x, direction.y);
+        let linear = Matrix2::new(c * c - s * s, 2.0 * c * s, 2.0 * c * s, s * s - c * c);
+        Ok(Self::around(point, linear))
+    }
+
+    fn around(center: Vector2<f64>, linear: Matrix2<f64>) -> Self {
+        Self {
+            linear,
+            translation: center - linear * center,
+        }
+    }
+
+    // This transform followed by the other one
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            linear: other.linear * self.linear,
+            translation: other.linear * self.translation + other.translation,
+        }
+    }
+
+    pub fn apply(&self, point: Vector2<f64>) -> Vector2<f64> {
+        self.linear * point + self.translation
+    }
+
+    // Directions and offsets are not translated
+    pub fn apply_vector(&self, vector: Vector2<f64>) -> Vector2<f64> {
+        self.linear * vector
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.linear.determinant().abs().sqrt()
+    }
+
+    pub fn is_mirror(&self) -> bool {
+        self.linear.determinant() < 0.0
+    }
+
+    // Maps the angle of a direction. Mirroring reverses the direction angles are measured in.
+    pub fn map_angle(&self, angle: f64) -> f64 {
+        let rotation = self.linear[(1, 0)].atan2(self.linear[(0, 0)]);
+        if self.is_mirror() {
+            rotation - angle
+        } else {
+            rotation + angle
+        }
+    }
+}
+
+impl Sketch {
+    // Transforms the selected primitives together with the points they reference. Dimensions and
+    // targets of constraints that only involve transformed primitives move along, constraints to
+    // the rest of the sketch are left for the next solve.
+    pub fn transform(
+        &mut self,
+        selection: &[PrimitiveCell],
+        transform: &Transform,
+    ) -> Result<(), ISOTopeError> {
+        let mut closure: Vec<PrimitiveCell> = Vec::new();
+        for primitive in selection.iter() {
+            let references = primitive.borrow().references();
+            for p in references.into_iter().chain([primitive.clone()]) {
+                if !closure.contains(&p) {
+                    closure.push(p);
+                }
+            }
+        }
+        if closure.iter().any(|p| self.get_primitive_id(p).is_none()) {
+            return Err(ISOTopeError::MissingSketchReferences);
+        }
+
+        self.transaction(|sketch| {
+            sketch.touch();
+            for primitive in closure.iter() {
+                sketch.transform_primitive(primitive, transform);
+            }
+            for constraint in sketch.constraints() {
+                if constraint
+                    .borrow()
+                    .references()
+                    .iter()
+                    .all(|r| closure.contains(r))
+                {
+                    sketch.transform_dimensions(&constraint, transform)?;
+                }
+            }
+            sketch.update_driven_dimensions()
+        })
+    }
+
+    pub fn transform_all(&mut self, transform: &Transform) -> Result<(), ISOTopeError> {
+        let primitives: Vec<PrimitiveCell> = self.primitives.values().cloned().collect();
+        self.transform(&primitives, transform)
+    }
+
+    // Transforms the selection and solves the sketch again. The selection is held in place while
+    // the rest of the sketch follows it. Where the rest can't follow, a second solve pulls the
+    // selection back until all constraints hold. Nothing changes if solving fails.
+    pub fn transform_and_solve(
+        &mut self,
+        selection: &[PrimitiveCell],
+        transform: &Transform,
+        solver: &impl Solver,
+    ) -> Result<(), Box<dyn Error>> {
+        self.transaction(|sketch| -> Result<(), Box<dyn Error>> {
+            sketch.transform(selection, transform)?;
+
+            let mut points = Vec::new();
+            for primitive in selection.iter() {
+                let references = primitive.borrow().references();
+                for p in references.into_iter().chain([primitive.clone()]) {
+                    if let PrimitiveCell::Point2(point) = p {
+                        if !points.iter().any(|q| Rc::ptr_eq(q, &point)) {
+                            points.push(point);
+                        }
+                    }
+                }
+            }
+            let holds: Vec<ConstraintCell> = points
+                .into_iter()
+                .map(|point| {
+                    let position = point.borrow().data();
+                    ConstraintCell::FixPoint(Rc::new(RefCell::new(FixPoint::new(point, position))))
+                })
+                .collect();
+            for hold in holds.iter() {
+                sketch.add_constraint(hold.clone())?;
+            }
+            // The held solve fails if the rest of the sketch can't follow, the second one decides
+            let _ = solver.solve(sketch);
+            for hold in holds {
+                sketch.delete_constraint(hold)?;
+            }
+            solver.solve(sketch)
+        })
+    }
+
+    fn transform_primitive(&mut self, primitive: &PrimitiveCell, transform: &Transform) {
+        let scale = transform.scale();
+        match primitive {
+            PrimitiveCell::Point2(point) => {
+                let position = transform.apply(point.borrow().data());
+                let mut point = point.borrow_mut();
+                point.set_x(position.x);
+                point.set_y(position.y);
+            }
+            PrimitiveCell::Circle(circle) => {
+                let radius = circle.borrow().radius();
+                circle.borrow_mut().set_radius(radius * scale);
+            }
+            PrimitiveCell::Arc(arc) => {
+                let (radius, start_angle, end_angle) = {
+                    let arc = arc.borrow();
+                    (arc.radius(), arc.start_angle(), arc.end_angle())
+                };
+                {
+                    let mut arc = arc.borrow_mut();
+                    arc.set_radius(radius * scale);
+                    arc.set_start_angle(transform.map_angle(start_angle));
+                    arc.set_end_angle(transform.map_angle(end_angle));
+                }
+                // Mirroring reverses the direction, so the arc keeps its start and end points
+                if transform.is_mirror() {
+                    self.toggle_clockwise(primitive);
+                }
+            }
+            PrimitiveCell::Ellipse(ellipse) => {
+                let (radius_x, radius_y, rotation) = {
+                    let ellipse = ellipse.borrow();
+                    (ellipse.radius_x(), ellipse.radius_y(), ellipse.rotation())
+                };
+                let mut ellipse = ellipse.borrow_mut();
+                ellipse.set_radius_x(radius_x * scale);
+                ellipse.set_radius_y(radius_y * scale);
+                ellipse.set_rotation(transform.map_angle(rotation));
+            }
+            PrimitiveCell::EllipticalArc(arc) => {
+                let (radius_x, radius_y, rotation, start, end) = {
+                    let arc = arc.borrow();
+                    (
+                        arc.radius_x(),
+                        arc.radius_y(),
+                        arc.rotation(),
+                        arc.start_parameter(),
+                        arc.end_parameter(),
+                    )
+                };
+                {
+                    let mut arc = arc.borrow_mut();
+                    arc.set_radius_x(radius_x * scale);
+                    arc.set_radius_y(radius_y * scale);
+                    arc.set_rotation(transform.map_angle(rotation));
+                    // Mirroring also mirrors the parameters of the points on the ellipse
+                    if transform.is_mirror() {
+                        arc.set_start_parameter(-start);
+                        arc.set_end_parameter(-end);
+                    }
+                }
+                if transform.is_mirror() {
+                    self.toggle_clockwise(primitive);
+                }
+            }
+            // Lines and splines are defined by their points alone
+            PrimitiveCell::Line(_) | PrimitiveCell::BSpline(_) | PrimitiveCell::Bezier(_) => {}
+        }
+    }
+
+    fn toggle_clockwise(&mut self, primitive: &PrimitiveCell) {
+        let edit = Edit::ToggleClockwise(primitive.clone());
+        edit.apply(self);
+        self.record(edit);
+    }
+
+    fn transform_dimensions(
+        &mut self,
+        constraint: &ConstraintCell,
+        transform: &Transform,
+    ) -> Result<(), ISOTopeError> {
+        let dimensions = constraint.dimensions();
+        let orientation = if transform.is_mirror() { -1.0 } else { 1.0 };
+        // The new values, and the factor to the old value where there is one
+        let values: Vec<(f64, Option<f64>)> = match constraint {
+            ConstraintCell::FixPoint(_) => {
+                let position = transform.apply(Vector2::new(dimensions[0], dimensions[1]));
+                vec![(position.x, None), (position.y, None)]
+            }
+            ConstraintCell::Pattern(pattern) => match pattern.borrow().kind() {
+                PatternKind::Linear { spacing } => {
+                    let spacing = transform.apply_vector(spacing);
+                    vec![(spacing.x, None), (spacing.y, None)]
+                }
+                PatternKind::Circular { step_angle, .. } => {
+                    vec![(orientation * step_angle, Some(orientation))]
+                }
+            },
+            ConstraintCell::AngleBetweenLines(angle) if angle.borrow().signed() => {
+                vec![(orientation * dimensions[0], Some(orientation))]
+            }
+            // Signed distances are positive on the left of the line, which a mirror swaps
+            ConstraintCell::PointLineDistance(distance) if distance.borrow().signed() => {
+                let factor = orientation * transform.scale();
+                vec![(factor * dimensions[0], Some(factor))]
+            }
+            ConstraintCell::LineLineDistance(distance) if distance.borrow().signed() => {
+                let factor = orientation * transform.scale();
+                vec![(factor * dimensions[0], Some(factor))]
+            }
+            _ => dimensions
+                .iter()
+                .zip(constraint.dimension_quantities())
+                .map(|(value, quantity)| match quantity {
+                    Quantity::Length => (value * transform.scale(), Some(transform.scale())),
+                    Quantity::Angle => (*value, Some(1.0)),
+                })
+                .collect(),
+        };
+
+        for (index, (value, factor)) in values.into_iter().enumerate() {
+            constraint.set_dimension(index, value)?;
+            // Expressions keep driving the dimension if the new value still follows from them
+            if let Some(expression) = self.dimension_expression(constraint, index) {
+                let expression = match factor {
+                    Some(1.0) => continue,
+                    Some(-1.0) => Expression::Negate(Box::new(expression)),
+                    Some(factor) => Expression::Multiply(
+                        Box::new(expression),
+                        Box::new(Expression::Number(factor)),
+                    ),
+                    None => {
+                        self.unbind_dimension(constraint, index);
+                        continue;
+                    }
+                };
+                self.bind_dimension(constraint.clone(), index, expression)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{radius::RadialCurve, ConstraintCell},
+        error::ISOTopeError,
+        examples::test_rectangle_rotated::RotatedRectangleDemo,
+        primitives::PrimitiveCell,
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+        units::Angle,
+    };
+
+    use super::Transform;
+
+    #[test]
+    fn test_transform_sketch() -> Result<(), Box<dyn Error>> {
+        let rectangle = RotatedRectangleDemo::new()?;
+        let mut sketch = rectangle.sketch;
+        let arc = sketch.add_arc(rectangle.point_a.clone(), 0.5, false, 0.2, 1.2)?;
+        let radius = sketch.constrain_radius(RadialCurve::Arc(arc.clone()), 0.5)?;
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!(sketch.get_loss() < 1e-8);
+
+        let b = rectangle.point_b.borrow().data();
+        let start = arc.borrow().start_point();
+        let transform = Transform::rotation(Vector2::new(1.0, 1.0), Angle::degrees(90.0))
+            .then(&Transform::scaling(Vector2::zeros(), 2.0)?);
+        sketch.transform_all(&transform)?;
+
+        // Everything moves along, so the constraints still hold
+        assert!(sketch.get_loss() < 1e-8);
+        assert!((rectangle.point_b.borrow().data() - transform.apply(b)).norm() < 1e-12);
+        assert!((arc.borrow().start_point() - transform.apply(start)).norm() < 1e-12);
+        assert!((radius.borrow().desired_radius() - 1.0).abs() < 1e-12);
+
+        sketch.undo()?;
+        assert!((rectangle.point_b.borrow().data() - b).norm() < 1e-12);
+        assert!((radius.borrow().desired_radius() - 0.5).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirror() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(1.0, 0.5)?;
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(2.0, 0.0)?;
+        let c = sketch.add_point2(1.0, 1.0)?;
+        let arc = sketch.add_arc(center.clone(), 1.0, false, 0.3, 2.0)?;
+        let elliptical_arc =
+            sketch.add_elliptical_arc(center.clone(), 2.0, 1.0, 0.4, true, -0.5, 1.0)?;
+        let line1 = sketch.add_line(a.clone(), b)?;
+        let line2 = sketch.add_line(a, c.clone())?;
+        let angle = sketch.constrain_angle_between_lines(
+            line1.clone(),
+            line2.clone(),
+            Angle::radians(0.25 * std::f64::consts::PI),
+            true,
+        )?;
+        // The top of the triangle is on the left of the base
+        let distance = sketch.constrain_distance_point_line(c.clone(), line1.clone(), 1.0, true)?;
+        let d = sketch.add_point2(2.0, 1.0)?;
+        let line3 = sketch.add_line(c.clone(), d)?;
+        let line_distance = sketch.constrain_distance_line_line(line1.clone(), line3, 1.0, true)?;
+        assert!(sketch.get_loss() < 1e-12);
+
+        let arc_points = (arc.borrow().start_point(), arc.borrow().end_point());
+        let elliptical_start = elliptical_arc.borrow().start_point();
+        let transform = Transform::mirror(Vector2::new(3.0, 0.0), Vector2::new(1.0, 2.0))?;
+        assert!(transform.is_mirror());
+        sketch.transform_all(&transform)?;
+
+        assert!((arc.borrow().start_point() - transform.apply(arc_points.0)).norm() < 1e-12);
+        assert!((arc.borrow().end_point() - transform.apply(arc_points.1)).norm() < 1e-12);
+        assert!(arc.borrow().clockwise());
+        assert!(!elliptical_arc.borrow().clockwise());
+        assert!(
+            (elliptical_arc.borrow().start_point() - transform.apply(elliptical_start)).norm()
+                < 1e-12
+        );
+        // Signed angles and distances change their sign, so the constraints still hold
+        assert!((angle.borrow().desired_angle() + 0.25 * std::f64::consts::PI).abs() < 1e-12);
+        assert!((distance.borrow().desired_distance() + 1.0).abs() < 1e-12);
+        assert!((line_distance.borrow().desired_distance() + 1.0).abs() < 1e-12);
+        assert!(sketch.get_loss() < 1e-12);
+
+        sketch.undo()?;
+        assert!(!arc.borrow().clockwise());
+        assert!(elliptical_arc.borrow().clockwise());
+
+        assert!(matches!(
+            Transform::mirror(Vector2::zeros(), Vector2::zeros()),
+            Err(ISOTopeError::InvalidTransform(_))
+        ));
+        assert!(matches!(
+            Transform::scaling(Vector2::zeros(), 0.0),
+            Err(ISOTopeError::InvalidTransform(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_and_solve() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let a = sketch.add_point2(0.0, 0.0)?;
+        let b = sketch.add_point2(1.0, 0.0)?;
+        let c = sketch.add_point2(1.0, 1.0)?;
+        let line = sketch.add_line(b.clone(), c)?;
+        sketch.constrain_distance_euclidean(a.clone(), b.clone(), 1.0)?;
+        let selection = [PrimitiveCell::Line(line)];
+        let solver = BFGSSolver::new();
+
+        // The free point follows the moved line
+        sketch.transform_and_solve(
+            &selection,
+            &Transform::translation(Vector2::new(2.0, 0.0)),
+            &solver,
+        )?;
+        assert!((b.borrow().x() - 3.0).abs() < 1e-4);
+        assert!(((a.borrow().data() - b.borrow().data()).norm() - 1.0).abs() < 1e-4);
+
+        // A fixed point can't follow, so the line is pulled back
+        let fix = sketch.constrain_fix_point(a.clone(), a.borrow().data())?;
+        sketch.transform_and_solve(
+            &selection,
+            &Transform::translation(Vector2::new(2.0, 0.0)),
+            &solver,
+        )?;
+        assert!(((a.borrow().data() - b.borrow().data()).norm() - 1.0).abs() < 1e-4);
+        assert!((a.borrow().data() - fix.borrow().desired_pos()).norm() < 1e-4);
+        assert!(sketch
+            .constraints()
+            .iter()
+            .all(|c| !matches!(c, ConstraintCell::FixPoint(f) if !Rc::ptr_eq(f, &fix))));
+        Ok(())
+    }
+}
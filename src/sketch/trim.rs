@@ -0,0 +1,921 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use nalgebra::Vector2;
+
+use crate::constraints::coincident::arc_end_point_coincident::ArcEndPointCoincident;
+use crate::constraints::coincident::arc_start_point_coincident::ArcStartPointCoincident;
+use crate::constraints::continuity::g1_continuity::G1Continuity;
+use crate::constraints::continuity::g2_continuity::G2Continuity;
+use crate::constraints::continuity::{ContinuityCurve, CurveEnd};
+use crate::constraints::inequality::radius_bound::RadiusBound;
+use crate::constraints::radius::diameter_dimension::DiameterDimension;
+use crate::constraints::radius::equal_radius::EqualRadius;
+use crate::constraints::radius::radius_difference::RadiusDifference;
+use crate::constraints::radius::radius_dimension::RadiusDimension;
+use crate::constraints::radius::RadialCurve;
+use crate::constraints::ConstraintCell;
+use crate::error::ISOTopeError;
+use crate::intersections::{angle_from, extension_intersections, intersections, line_parameter};
+use crate::primitives::arc::Arc;
+use crate::primitives::circle::Circle;
+use crate::primitives::line::Line;
+use crate::primitives::point2::Point2;
+use crate::primitives::PrimitiveCell;
+
+use super::history::Edit;
+use super::Sketch;
+
+// Cuts closer than this to each other or to the end of a curve, relative to its length or sweep,
+// are ignored
+const TOLERANCE: f64 = 1e-9;
+
+// What a trim, extend or split did to the sketch
+#[derive(Debug, Clone, Default)]
+pub struct CurveEdit {
+    // Ids of the new pieces and of the points between them
+    pub added: Vec<u64>,
+    // Ids of the primitives that were deleted
+    pub removed: Vec<u64>,
+    // Constraints that now apply to another piece, as the old and the new constraint
+    pub remapped: Vec<(ConstraintCell, ConstraintCell)>,
+    // Constraints that no longer made sense, e.g. the length of a line that was split
+    pub dropped: Vec<ConstraintCell>,
+}
+
+impl Sketch {
+    // Splits a line or arc at the points closest to the given positions. The original primitive
+    // keeps the first piece and constraints on its end move to the last one. The pieces share new
+    // points and are held collinear or concentric with the same radius. A circle is split into
+    // arcs, which needs at least two positions.
+    pub fn split(&mut self, id: u64, at: &[Vector2<f64>]) -> Result<CurveEdit, ISOTopeError> {
+        let primitive = self.curve(id)?;
+        self.curve_edit(|sketch, remapped| match &primitive {
+            PrimitiveCell::Line(line) => {
+                let (start, end) = line_ends(line);
+                let cuts = sorted_cuts(at.iter().map(|p| line_parameter(start, end, *p)), 1.0);
+                if cuts.is_empty() {
+                    return Err(ISOTopeError::InvalidSplit(id));
+                }
+                for t in cuts.iter().rev() {
+                    let position = start + *t * (end - start);
+                    sketch.cut_line(line, position, position, remapped)?;
+                }
+                Ok(())
+            }
+            PrimitiveCell::Arc(arc) => {
+                let sweep = arc.borrow().sweep_angle();
+                let cuts = sorted_cuts(at.iter().map(|p| arc_parameter(arc, *p)), sweep);
+                if cuts.is_empty() {
+                    return Err(ISOTopeError::InvalidSplit(id));
+                }
+                let angles: Vec<f64> = cuts.iter().map(|s| arc_angle(arc, *s)).collect();
+                for angle in angles.iter().rev() {
+                    sketch.cut_arc(arc, *angle, *angle, remapped)?;
+                }
+                Ok(())
+            }
+            PrimitiveCell::Circle(circle) => {
+                let angles = circle_cuts(circle, at);
+                let (Some(first), Some(last)) = (angles.first(), angles.last()) else {
+                    return Err(ISOTopeError::InvalidSplit(id));
+                };
+                if angles.len() < 2 {
+                    return Err(ISOTopeError::InvalidSplit(id));
+                }
+                let arc = sketch.open_circle(circle, *first, *last, remapped)?;
+                let rest = sketch.add_arc(
+                    arc.borrow().center(),
+                    arc.borrow().radius(),
+                    false,
+                    *last,
+                    *first,
+                )?;
+                rest.borrow_mut()
+                    .set_construction(arc.borrow().construction());
+                sketch.constrain_equal_radius(
+                    RadialCurve::Arc(arc.clone()),
+                    RadialCurve::Arc(rest.clone()),
+                )?;
+                sketch.join_arcs(&arc, &rest)?;
+                sketch.join_arcs(&rest, &arc)?;
+                for angle in angles[1..angles.len() - 1].iter().rev() {
+                    sketch.cut_arc(&arc, *angle, *angle, remapped)?;
+                }
+                Ok(())
+            }
+            _ => Err(ISOTopeError::UnsupportedCurve(id)),
+        })
+    }
+
+    // Removes the part of a line, arc or circle around the picked position up to the nearest
+    // intersections with other primitives on both sides. Without intersections the whole
+    // primitive is deleted.
+    pub fn trim(&mut self, id: u64, pick: Vector2<f64>) -> Result<CurveEdit, ISOTopeError> {
+        let primitive = self.curve(id)?;
+        let cuts = self.cuts(&primitive, false);
+        self.curve_edit(|sketch, remapped| match &primitive {
+            PrimitiveCell::Line(line) => {
+                let (start, end) = line_ends(line);
+                let cuts = sorted_cuts(cuts.iter().map(|p| line_parameter(start, end, *p)), 1.0);
+                let t = line_parameter(start, end, pick);
+                let position = |t: f64| start + t * (end - start);
+                match surrounding(&cuts, t) {
+                    (None, None) => sketch.remove_curve(&primitive),
+                    (Some(lo), None) => sketch.move_line_end(line, CurveEnd::End, position(lo)),
+                    (None, Some(hi)) => sketch.move_line_end(line, CurveEnd::Start, position(hi)),
+                    (Some(lo), Some(hi)) => sketch
+                        .cut_line(line, position(lo), position(hi), remapped)
+                        .map(|_| ()),
+                }
+            }
+            PrimitiveCell::Arc(arc) => {
+                let sweep = arc.borrow().sweep_angle();
+                let cuts = sorted_cuts(cuts.iter().map(|p| arc_parameter(arc, *p)), sweep);
+                let mut s = arc_parameter(arc, pick);
+                if s > sweep {
+                    // Off the arc, take the closer end
+                    s = if s - sweep < 2.0 * PI - s { sweep } else { 0.0 };
+                }
+                let angle = |s: f64| arc_angle(arc, s);
+                match surrounding(&cuts, s) {
+                    (None, None) => sketch.remove_curve(&primitive),
+                    (Some(lo), None) => sketch.move_arc_end(arc, CurveEnd::End, angle(lo)),
+                    (None, Some(hi)) => sketch.move_arc_end(arc, CurveEnd::Start, angle(hi)),
+                    (Some(lo), Some(hi)) => sketch
+                        .cut_arc(arc, angle(lo), angle(hi), remapped)
+                        .map(|_| ()),
+                }
+            }
+            PrimitiveCell::Circle(circle) => {
+                let angles = circle_cuts(circle, &cuts);
+                if angles.len() < 2 {
+                    return sketch.remove_curve(&primitive);
+                }
+                let center = circle.borrow().center().borrow().data();
+                let angle = angle_from(center, 0.0, false, pick);
+                let (lo, hi) = surrounding(&angles, angle);
+                // Around the circle, the cuts before the first and after the last are the others
+                let lo = lo.or(angles.last().copied()).unwrap_or_default();
+                let hi = hi.or(angles.first().copied()).unwrap_or_default();
+                sketch.open_circle(circle, hi, lo, remapped).map(|_| ())
+            }
+            _ => Err(ISOTopeError::UnsupportedCurve(id)),
+        })
+    }
+
+    // Extends the end of a line or arc closest to the picked position up to the nearest other
+    // primitive it reaches
+    pub fn extend(&mut self, id: u64, pick: Vector2<f64>) -> Result<CurveEdit, ISOTopeError> {
+        let primitive = self.curve(id)?;
+        let targets = self.cuts(&primitive, true);
+        self.curve_edit(|sketch, _| match &primitive {
+            PrimitiveCell::Line(line) => {
+                let (start, end) = line_ends(line);
+                let parameters = targets.iter().map(|p| line_parameter(start, end, *p));
+                let (end_to_move, t) = if (pick - start).norm() < (pick - end).norm() {
+                    let t = parameters.filter(|t| *t < -TOLERANCE).reduce(f64::max);
+                    (CurveEnd::Start, t)
+                } else {
+                    let t = parameters.filter(|t| *t > 1.0 + TOLERANCE).reduce(f64::min);
+                    (CurveEnd::End, t)
+                };
+                let t = t.ok_or(ISOTopeError::NothingToExtendTo(id))?;
+                sketch.move_line_end(line, end_to_move, start + t * (end - start))
+            }
+            PrimitiveCell::Arc(arc) => {
+                let (sweep, start, end) = {
+                    let arc = arc.borrow();
+                    (arc.sweep_angle(), arc.start_point(), arc.end_point())
+                };
+                // Beyond the end going forward, or beyond the start going backward
+                let parameters = targets
+                    .iter()
+                    .map(|p| arc_parameter(arc, *p))
+                    .filter(|s| *s > sweep + TOLERANCE && *s < 2.0 * PI - TOLERANCE);
+                let (end_to_move, s) = if (pick - start).norm() < (pick - end).norm() {
+                    (CurveEnd::Start, parameters.reduce(f64::max))
+                } else {
+                    (CurveEnd::End, parameters.reduce(f64::min))
+                };
+                let s = s.ok_or(ISOTopeError::NothingToExtendTo(id))?;
+                sketch.move_arc_end(arc, end_to_move, arc_angle(arc, s))
+            }
+            _ => Err(ISOTopeError::UnsupportedCurve(id)),
+        })
+    }
+
+    fn curve(&self, id: u64) -> Result<PrimitiveCell, ISOTopeError> {
+        match self.get_primitive_by_id(id) {
+            Some(
+                primitive @ (PrimitiveCell::Line(_)
+                | PrimitiveCell::Arc(_)
+                | PrimitiveCell::Circle(_)),
+            ) => Ok(primitive.clone()),
+            Some(_) => Err(ISOTopeError::UnsupportedCurve(id)),
+            None => Err(ISOTopeError::PrimitiveNotFound(id)),
+        }
+    }
+
+    // Where the primitive, or its extension, meets the other primitives
+    fn cuts(&self, primitive: &PrimitiveCell, extended: bool) -> Vec<Vector2<f64>> {
+        let curve = primitive.borrow().to_primitive();
+        self.primitives
+            .values()
+            .filter(|other| *other != primitive)
+            .flat_map(|other| {
+                let other = other.borrow().to_primitive();
+                if extended {
+                    extension_intersections(curve.clone(), other)
+                } else {
+                    intersections(curve.clone(), other)
+                }
+            })
+            .collect()
+    }
+
+    // Runs the edit as one step of the history and reports what it did
+    pub(super) fn curve_edit(
+        &mut self,
+        f: impl FnOnce(
+            &mut Sketch,
+            &mut Vec<(ConstraintCell, ConstraintCell)>,
+        ) -> Result<(), ISOTopeError>,
+    ) -> Result<CurveEdit, ISOTopeError> {
+        let ids: BTreeSet<u64> = self.primitives.keys().copied().collect();
+        let constraints = self.constraints.clone();
+        let mut remapped = Vec::new();
+        self.transaction(|sketch| {
+            sketch.touch();
+            f(sketch, &mut remapped)?;
+            sketch.update_driven_dimensions()
+        })?;
+
+        // A constraint may have been moved more than once, e.g. to each new piece of a split
+        let mut moves = Vec::new();
+        for (old, new) in remapped.iter() {
+            if !constraints.contains(old) {
+                continue;
+            }
+            let mut new = new.clone();
+            while let Some((_, next)) = remapped.iter().find(|(o, _)| o == &new) {
+                new = next.clone();
+            }
+            if self.constraints.contains(&new) {
+                moves.push((old.clone(), new));
+            }
+        }
+        Ok(CurveEdit {
+            added: self
+                .primitives
+                .keys()
+                .filter(|id| !ids.contains(id))
+                .copied()
+                .collect(),
+            removed: ids
+                .iter()
+                .filter(|id| !self.primitives.contains_key(id))
+                .copied()
+                .collect(),
+            dropped: constraints
+                .iter()
+                .filter(|c| !self.constraints.contains(c) && !moves.iter().any(|(o, _)| o == *c))
+                .cloned()
+                .collect(),
+            remapped: moves,
+        })
+    }
+
+    // Shortens the line to end at the first position and adds a piece from the second position to
+    // its old end. A single position splits the line.
+    fn cut_line(
+        &mut self,
+        line: &Rc<RefCell<Line>>,
+        from: Vector2<f64>,
+        to: Vector2<f64>,
+        remapped: &mut Vec<(ConstraintCell, ConstraintCell)>,
+    ) -> Result<Rc<RefCell<Line>>, ISOTopeError> {
+        let (start, end) = (line.borrow().start(), line.borrow().end());
+        let new_end = self.add_point2(from.x, from.y)?;
+        let new_start = if from == to {
+            new_end.clone()
+        } else {
+            self.add_point2(to.x, to.y)?
+        };
+        let piece = self.add_line(new_start, end.clone())?;
+        piece
+            .borrow_mut()
+            .set_construction(line.borrow().construction());
+        self.set_line_ends(line, [start, new_end]);
+
+        let (line_cell, piece_cell) = (
+            PrimitiveCell::Line(line.clone()),
+            PrimitiveCell::Line(piece.clone()),
+        );
+        self.carry_constraints(&line_cell, &piece_cell, remapped)?;
+        self.constrain_collinear_lines(line.clone(), piece.clone())?;
+        Ok(piece)
+    }
+
+    // Shortens the arc to end at the first angle and adds a piece from the second angle to its old
+    // end. A single angle splits the arc.
+    fn cut_arc(
+        &mut self,
+        arc: &Rc<RefCell<Arc>>,
+        from: f64,
+        to: f64,
+        remapped: &mut Vec<(ConstraintCell, ConstraintCell)>,
+    ) -> Result<Rc<RefCell<Arc>>, ISOTopeError> {
+        let (center, radius, clockwise, end) = {
+            let arc = arc.borrow();
+            (arc.center(), arc.radius(), arc.clockwise(), arc.end_angle())
+        };
+        let piece = self.add_arc(center, radius, clockwise, to, end)?;
+        piece
+            .borrow_mut()
+            .set_construction(arc.borrow().construction());
+        arc.borrow_mut().set_end_angle(from);
+
+        let (arc_cell, piece_cell) = (
+            PrimitiveCell::Arc(arc.clone()),
+            PrimitiveCell::Arc(piece.clone()),
+        );
+        self.carry_constraints(&arc_cell, &piece_cell, remapped)?;
+        self.constrain_equal_radius(
+            RadialCurve::Arc(arc.clone()),
+            RadialCurve::Arc(piece.clone()),
+        )?;
+        if from == to {
+            self.join_arcs(arc, &piece)?;
+        }
+        Ok(piece)
+    }
+
+    // Replaces a circle by a counterclockwise arc between the angles
+    fn open_circle(
+        &mut self,
+        circle: &Rc<RefCell<Circle>>,
+        from: f64,
+        to: f64,
+        remapped: &mut Vec<(ConstraintCell, ConstraintCell)>,
+    ) -> Result<Rc<RefCell<Arc>>, ISOTopeError> {
+        let (center, radius, construction) = {
+            let circle = circle.borrow();
+            (circle.center(), circle.radius(), circle.construction())
+        };
+        let arc = self.add_arc(center, radius, false, from, to)?;
+        arc.borrow_mut().set_construction(construction);
+
+        for constraint in self.constraints() {
+            if let Some(radial) = radial_to_arc(&constraint, circle, &arc) {
+                self.replace_constraint(&constraint, radial.clone())?;
+                remapped.push((constraint, radial));
+            }
+        }
+        self.delete_primitive_and_dependents(&PrimitiveCell::Circle(circle.clone()))?;
+        Ok(arc)
+    }
+
+    // Moves an end of the line to a new point at the position. The old point is deleted if
+    // nothing else uses it.
+    fn move_line_end(
+        &mut self,
+        line: &Rc<RefCell<Line>>,
+        end: CurveEnd,
+        position: Vector2<f64>,
+    ) -> Result<(), ISOTopeError> {
+        let point = self.add_point2(position.x, position.y)?;
+        let old_point = self.replace_line_end(line, end, point)?;
+        self.remove_if_unused(&old_point)
+    }
+
+    // Puts the point at an end of the line and drops the constraints on that end. Returns the
+    // point that was there.
+    pub(super) fn replace_line_end(
+        &mut self,
+        line: &Rc<RefCell<Line>>,
+        end: CurveEnd,
+        point: Rc<RefCell<Point2>>,
+    ) -> Result<Rc<RefCell<Point2>>, ISOTopeError> {
+        let (start, old_end) = (line.borrow().start(), line.borrow().end());
+        let old_point = match end {
+            CurveEnd::Start => {
+                self.set_line_ends(line, [point, old_end]);
+                start
+            }
+            CurveEnd::End => {
+                self.set_line_ends(line, [start, point]);
+                old_end
+            }
+        };
+        self.drop_end_constraints(&PrimitiveCell::Line(line.clone()), end)?;
+        Ok(old_point)
+    }
+
+    pub(super) fn move_arc_end(
+        &mut self,
+        arc: &Rc<RefCell<Arc>>,
+        end: CurveEnd,
+        angle: f64,
+    ) -> Result<(), ISOTopeError> {
+        match end {
+            CurveEnd::Start => arc.borrow_mut().set_start_angle(angle),
+            CurveEnd::End => arc.borrow_mut().set_end_angle(angle),
+        }
+        self.drop_end_constraints(&PrimitiveCell::Arc(arc.clone()), end)
+    }
+
+    fn set_line_ends(&mut self, line: &Rc<RefCell<Line>>, ends: [Rc<RefCell<Point2>>; 2]) {
+        let old = [line.borrow().start(), line.borrow().end()];
+        let edit = Edit::SetLineEnds(line.clone(), old, ends);
+        edit.apply(self);
+        self.record(edit);
+    }
+
+    // Holds the end of the first arc and the start of the second together at a new point
+    fn join_arcs(
+        &mut self,
+        first: &Rc<RefCell<Arc>>,
+        second: &Rc<RefCell<Arc>>,
+    ) -> Result<(), ISOTopeError> {
+        let position = first.borrow().end_point();
+        let point = self.add_point2(position.x, position.y)?;
+        self.add_constraint(ConstraintCell::ArcEndPointCoincident(Rc::new(
+            RefCell::new(ArcEndPointCoincident::new(first.clone(), point.clone())),
+        )))?;
+        self.add_constraint(ConstraintCell::ArcStartPointCoincident(Rc::new(
+            RefCell::new(ArcStartPointCoincident::new(second.clone(), point)),
+        )))
+    }
+
+    // Deletes a primitive with its constraints and the points only it used
+    fn remove_curve(&mut self, primitive: &PrimitiveCell) -> Result<(), ISOTopeError> {
+        let references = primitive.borrow().references();
+        self.delete_primitive_and_dependents(primitive)?;
+        for reference in references.iter() {
+            if let PrimitiveCell::Point2(point) = reference {
+                self.remove_if_unused(point)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_if_unused(&mut self, point: &Rc<RefCell<Point2>>) -> Result<(), ISOTopeError> {
+        let point = PrimitiveCell::Point2(point.clone());
+        if self
+            .primitives
+            .values()
+            .any(|p| p.borrow().references().contains(&point))
+        {
+            return Ok(());
+        }
+        self.delete_primitive_and_dependents(&point)
+    }
+
+    // Moves the constraints on the end of a curve that was cut to the piece after the cut, and
+    // drops those that depended on its full extent
+    fn carry_constraints(
+        &mut self,
+        from: &PrimitiveCell,
+        to: &PrimitiveCell,
+        remapped: &mut Vec<(ConstraintCell, ConstraintCell)>,
+    ) -> Result<(), ISOTopeError> {
+        for constraint in self.constraints() {
+            if !constraint.borrow().references().contains(from) {
+                continue;
+            }
+            if depends_on_extent(&constraint, from) {
+                self.delete_constraint(constraint)?;
+            } else if let Some(moved) = moved_end(&constraint, from, CurveEnd::End, to) {
+                self.replace_constraint(&constraint, moved.clone())?;
+                remapped.push((constraint, moved));
+            }
+        }
+        Ok(())
+    }
+
+    // Drops the constraints on an end of a curve that moved, and those that depended on its full
+    // extent
+    fn drop_end_constraints(
+        &mut self,
+        primitive: &PrimitiveCell,
+        end: CurveEnd,
+    ) -> Result<(), ISOTopeError> {
+        for constraint in self.constraints() {
+            if !constraint.borrow().references().contains(primitive) {
+                continue;
+            }
+            // Moving the end to the primitive itself tells if the constraint is on that end
+            if depends_on_extent(&constraint, primitive)
+                || moved_end(&constraint, primitive, end, primitive).is_some()
+            {
+                self.delete_constraint(constraint)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Swaps a constraint for another, which takes over its bindings and driven state
+    fn replace_constraint(
+        &mut self,
+        old: &ConstraintCell,
+        new: ConstraintCell,
+    ) -> Result<(), ISOTopeError> {
+        self.add_constraint(new.clone())?;
+        for binding in self.dimension_bindings() {
+            if &binding.constraint() == old {
+                self.bind_dimension(new.clone(), binding.index(), binding.expression())?;
+            }
+        }
+        if self.is_driven(old) {
+            self.set_driven(&new, true)?;
+        }
+        self.delete_constraint(old.clone())
+    }
+}
+
+fn line_ends(line: &Rc<RefCell<Line>>) -> (Vector2<f64>, Vector2<f64>) {
+    let line = line.borrow();
+    let start = line.start().borrow().data();
+    let end = line.end().borrow().data();
+    (start, end)
+}
+
+// How far along the arc the point is, as the angle from its start
+fn arc_parameter(arc: &Rc<RefCell<Arc>>, point: Vector2<f64>) -> f64 {
+    let arc = arc.borrow();
+    let center = arc.center().borrow().data();
+    angle_from(center, arc.start_angle(), arc.clockwise(), point)
+}
+
+fn arc_angle(arc: &Rc<RefCell<Arc>>, parameter: f64) -> f64 {
+    let arc = arc.borrow();
+    if arc.clockwise() {
+        arc.start_angle() - parameter
+    } else {
+        arc.start_angle() + parameter
+    }
+}
+
+// The distinct parameters strictly between 0 and the length, in increasing order
+fn sorted_cuts(parameters: impl Iterator<Item = f64>, length: f64) -> Vec<f64> {
+    let tolerance = TOLERANCE * length.max(1.0);
+    let mut cuts: Vec<f64> = parameters
+        .filter(|p| *p > tolerance && *p < length - tolerance)
+        .collect();
+    cuts.sort_by(f64::total_cmp);
+    cuts.dedup_by(|a, b| *a - *b <= tolerance);
+    cuts
+}
+
+// The distinct angles of the points around the circle, in increasing order
+fn circle_cuts(circle: &Rc<RefCell<Circle>>, points: &[Vector2<f64>]) -> Vec<f64> {
+    let center = circle.borrow().center().borrow().data();
+    let mut angles: Vec<f64> = points
+        .iter()
+        .map(|p| angle_from(center, 0.0, false, *p))
+        .collect();
+    angles.sort_by(f64::total_cmp);
+    angles.dedup_by(|a, b| *a - *b <= TOLERANCE);
+    if let (Some(first), Some(last)) = (angles.first(), angles.last()) {
+        if angles.len() > 1 && first + 2.0 * PI - last <= TOLERANCE {
+            angles.pop();
+        }
+    }
+    angles
+}
+
+// The closest cuts before and after the parameter
+fn surrounding(cuts: &[f64], parameter: f64) -> (Option<f64>, Option<f64>) {
+    (
+        cuts.iter().rev().find(|c| **c < parameter).copied(),
+        cuts.iter().find(|c| **c > parameter).copied(),
+    )
+}
+
+// Constraints that hold for the curve as a whole, like its length or symmetry to another curve
+fn depends_on_extent(constraint: &ConstraintCell, primitive: &PrimitiveCell) -> bool {
+    match constraint {
+        ConstraintCell::ArcLength(_)
+        | ConstraintCell::ArcSweepAngle(_)
+        | ConstraintCell::EqualLength(_)
+        | ConstraintCell::EqualLengths(_)
+        | ConstraintCell::Midpoint(_)
+        | ConstraintCell::Pattern(_) => true,
+        // Symmetry about the curve still holds for a piece of it
+        ConstraintCell::SymmetricLines(c) => PrimitiveCell::Line(c.borrow().axis()) != *primitive,
+        ConstraintCell::SymmetricArcs(c) => PrimitiveCell::Line(c.borrow().axis()) != *primitive,
+        _ => false,
+    }
+}
+
+// The constraint on the given end of a curve, moved to another curve. None if the constraint is
+// not on that end.
+fn moved_end(
+    constraint: &ConstraintCell,
+    from: &PrimitiveCell,
+    end: CurveEnd,
+    to: &PrimitiveCell,
+) -> Option<ConstraintCell> {
+    match (constraint, to) {
+        (ConstraintCell::ArcStartPointCoincident(c), PrimitiveCell::Arc(arc))
+            if end == CurveEnd::Start && PrimitiveCell::Arc(c.borrow().arc()) == *from =>
+        {
+            Some(ConstraintCell::ArcStartPointCoincident(Rc::new(
+                RefCell::new(ArcStartPointCoincident::new(
+                    arc.clone(),
+                    c.borrow().point(),
+                )),
+            )))
+        }
+        (ConstraintCell::ArcEndPointCoincident(c), PrimitiveCell::Arc(arc))
+            if end == CurveEnd::End && PrimitiveCell::Arc(c.borrow().arc()) == *from =>
+        {
+            Some(ConstraintCell::ArcEndPointCoincident(Rc::new(
+                RefCell::new(ArcEndPointCoincident::new(arc.clone(), c.borrow().point())),
+            )))
+        }
+        (ConstraintCell::G1Continuity(c), _) => {
+            let c = c.borrow();
+            let (curve1, curve2) = moved_curves(c.curve1(), c.curve2(), from, end, to)?;
+            Some(ConstraintCell::G1Continuity(Rc::new(RefCell::new(
+                G1Continuity::new(curve1, curve2),
+            ))))
+        }
+        (ConstraintCell::G2Continuity(c), _) => {
+            let c = c.borrow();
+            let (curve1, curve2) = moved_curves(c.curve1(), c.curve2(), from, end, to)?;
+            Some(ConstraintCell::G2Continuity(Rc::new(RefCell::new(
+                G2Continuity::new(curve1, curve2),
+            ))))
+        }
+        _ => None,
+    }
+}
+
+// The curves of a continuity constraint with the given end moved. None if neither is on it.
+fn moved_curves(
+    curve1: ContinuityCurve,
+    curve2: ContinuityCurve,
+    from: &PrimitiveCell,
+    end: CurveEnd,
+    to: &PrimitiveCell,
+) -> Option<(ContinuityCurve, ContinuityCurve)> {
+    let moved = |curve: &ContinuityCurve| match (curve, to) {
+        (ContinuityCurve::Line(line, e), PrimitiveCell::Line(to))
+            if *e == end && PrimitiveCell::Line(line.clone()) == *from =>
+        {
+            Some(ContinuityCurve::Line(to.clone(), end))
+        }
+        (ContinuityCurve::Arc(arc, e), PrimitiveCell::Arc(to))
+            if *e == end && PrimitiveCell::Arc(arc.clone()) == *from =>
+        {
+            Some(ContinuityCurve::Arc(to.clone(), end))
+        }
+        _ => None,
+    };
+    match (moved(&curve1), moved(&curve2)) {
+        (None, None) => None,
+        (new1, new2) => Some((new1.unwrap_or(curve1), new2.unwrap_or(curve2))),
+    }
+}
+
+// The radius constraint on the circle, put on the arc instead
+fn radial_to_arc(
+    constraint: &ConstraintCell,
+    circle: &Rc<RefCell<Circle>>,
+    arc: &Rc<RefCell<Arc>>,
+) -> Option<ConstraintCell> {
+    let circle = PrimitiveCell::Circle(circle.clone());
+    let on_circle = |curve: &RadialCurve| curve.primitive() == circle;
+    let arc = RadialCurve::Arc(arc.clone());
+    match constraint {
+        ConstraintCell::RadiusDimension(c) if on_circle(&c.borrow().curve()) => {
+            Some(ConstraintCell::RadiusDimension(Rc::new(RefCell::new(
+                RadiusDimension::new(arc, c.borrow().desired_radius()),
+            ))))
+        }
+        ConstraintCell::DiameterDimension(c) if on_circle(&c.borrow().curve()) => {
+            Some(ConstraintCell::DiameterDimension(Rc::new(RefCell::new(
+                DiameterDimension::new(arc, c.borrow().desired_diameter()),
+            ))))
+        }
+        ConstraintCell::RadiusBound(c) if on_circle(&c.borrow().curve()) => {
+            let c = c.borrow();
+            Some(ConstraintCell::RadiusBound(Rc::new(RefCell::new(
+                RadiusBound::new(arc, c.limit(), c.bound()),
+            ))))
+        }
+        ConstraintCell::EqualRadius(c) => {
+            let c = c.borrow();
+            let (curve1, curve2) = (c.curve1(), c.curve2());
+            if on_circle(&curve1) {
+                Some(EqualRadius::new(arc, curve2))
+            } else if on_circle(&curve2) {
+                Some(EqualRadius::new(curve1, arc))
+            } else {
+                None
+            }
+            .map(|c| ConstraintCell::EqualRadius(Rc::new(RefCell::new(c))))
+        }
+        ConstraintCell::RadiusDifference(c) => {
+            let c = c.borrow();
+            let (curve1, curve2) = (c.curve1(), c.curve2());
+            if on_circle(&curve1) {
+                Some(RadiusDifference::new(arc, curve2, c.desired_difference()))
+            } else if on_circle(&curve2) {
+                Some(RadiusDifference::new(curve1, arc, c.desired_difference()))
+            } else {
+                None
+            }
+            .map(|c| ConstraintCell::RadiusDifference(Rc::new(RefCell::new(c))))
+        }
+        _ => None,
+    }
+}
+
+// Run some tests
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::f64::consts::PI;
+    use std::rc::Rc;
+
+    use nalgebra::Vector2;
+
+    use crate::{
+        constraints::{
+            length::LengthCurve, lines::horizontal_line::HorizontalLine, radius::RadialCurve,
+            ConstraintCell,
+        },
+        error::ISOTopeError,
+        primitives::{line::Line, PrimitiveCell},
+        sketch::Sketch,
+        solvers::{bfgs_solver::BFGSSolver, Solver},
+    };
+
+    fn add_line(
+        sketch: &mut Sketch,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Result<Rc<RefCell<Line>>, ISOTopeError> {
+        let start = sketch.add_point2(x1, y1)?;
+        let end = sketch.add_point2(x2, y2)?;
+        sketch.add_line(start, end)
+    }
+
+    fn lines(sketch: &Sketch) -> Vec<(Vector2<f64>, Vector2<f64>)> {
+        sketch
+            .primitives()
+            .values()
+            .filter_map(|p| match p {
+                PrimitiveCell::Line(line) => {
+                    let line = line.borrow();
+                    Some((line.start().borrow().data(), line.end().borrow().data()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let start = sketch.add_point2(0.0, 0.0)?;
+        let end = sketch.add_point2(4.0, 0.0)?;
+        let line = sketch.add_line(start.clone(), end.clone())?;
+        let other = add_line(&mut sketch, 0.0, 0.0, 0.0, 4.0)?;
+        sketch.add_constraint(ConstraintCell::HorizontalLine(Rc::new(RefCell::new(
+            HorizontalLine::new(line.clone()),
+        ))))?;
+        sketch.constrain_fix_point(start, Vector2::new(0.0, 0.0))?;
+        let equal = sketch.constrain_equal_lengths(vec![
+            LengthCurve::Line(line.clone()),
+            LengthCurve::Line(other),
+        ])?;
+        let id = sketch
+            .get_primitive_id(&PrimitiveCell::Line(line.clone()))
+            .ok_or("the line should be in the sketch")?;
+
+        let edit = sketch.split(id, &[Vector2::new(1.0, 0.0), Vector2::new(3.0, 0.1)])?;
+        // Two pieces and two points between them
+        assert_eq!(edit.added.len(), 4);
+        assert!(edit.removed.is_empty());
+        assert_eq!(edit.dropped.len(), 1);
+        assert!(edit.dropped[0] == ConstraintCell::EqualLengths(equal));
+        assert_eq!(line.borrow().end().borrow().data(), Vector2::new(1.0, 0.0));
+        assert_eq!(lines(&sketch).len(), 4);
+        sketch.undo()?;
+        assert!(Rc::ptr_eq(&line.borrow().end(), &end));
+        assert_eq!(lines(&sketch).len(), 2);
+        sketch.redo()?;
+
+        // The pieces stay on one line
+        end.borrow_mut().set_y(0.5);
+        BFGSSolver::new().solve(&mut sketch)?;
+        assert!(end.borrow().y().abs() < 1e-6);
+
+        assert!(matches!(
+            sketch.split(id, &[Vector2::new(1.0, 0.0)]),
+            Err(ISOTopeError::InvalidSplit(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let line = add_line(&mut sketch, -2.0, 0.0, 2.0, 0.0)?;
+        add_line(&mut sketch, -1.0, -1.0, -1.0, 1.0)?;
+        add_line(&mut sketch, 1.0, -1.0, 1.0, 1.0)?;
+        add_line(&mut sketch, -1.5, -1.0, -1.5, 1.0)?;
+        let id = sketch
+            .get_primitive_id(&PrimitiveCell::Line(line.clone()))
+            .ok_or("the line should be in the sketch")?;
+
+        // Cutting out the middle leaves two pieces
+        sketch.trim(id, Vector2::new(0.0, 0.0))?;
+        assert_eq!(line.borrow().end().borrow().data(), Vector2::new(-1.0, 0.0));
+        let right = lines(&sketch)
+            .into_iter()
+            .filter(|(start, _)| *start == Vector2::new(1.0, 0.0))
+            .count();
+        assert_eq!(right, 1);
+
+        // An end up to the first cut, the old end point goes with it
+        let edit = sketch.trim(id, Vector2::new(-1.8, 0.0))?;
+        assert_eq!(
+            line.borrow().start().borrow().data(),
+            Vector2::new(-1.5, 0.0)
+        );
+        assert_eq!(edit.added.len(), 1);
+        assert_eq!(edit.removed.len(), 1);
+
+        // Without cuts the whole line goes
+        let edit = sketch.trim(id, Vector2::new(-1.2, 0.0))?;
+        assert!(edit.removed.contains(&id));
+        assert!(sketch.get_primitive_by_id(id).is_none());
+
+        // Trimming a circle opens it into an arc that keeps the radius dimension
+        let mut sketch = Sketch::new();
+        let center = sketch.add_point2(0.0, 0.0)?;
+        let circle = sketch.add_circle(center, 1.0)?;
+        let radius = sketch.constrain_radius(RadialCurve::Circle(circle.clone()), 1.0)?;
+        add_line(&mut sketch, -2.0, 0.0, 2.0, 0.0)?;
+        let id = sketch
+            .get_primitive_id(&PrimitiveCell::Circle(circle))
+            .ok_or("the circle should be in the sketch")?;
+        let edit = sketch.trim(id, Vector2::new(0.0, -1.0))?;
+        assert!(edit.dropped.is_empty());
+        assert_eq!(edit.remapped.len(), 1);
+        assert!(edit.remapped[0].0 == ConstraintCell::RadiusDimension(radius));
+        let arc = sketch
+            .primitives()
+            .into_values()
+            .find_map(|p| match p {
+                PrimitiveCell::Arc(arc) => Some(arc),
+                _ => None,
+            })
+            .ok_or("the circle should be replaced by an arc")?;
+        assert!((arc.borrow().start_angle() - 0.0).abs() < 1e-9);
+        assert!((arc.borrow().end_angle() - PI).abs() < 1e-9);
+
+        // One step in the history
+        sketch.undo()?;
+        assert!(sketch.get_primitive_by_id(id).is_some());
+        assert_eq!(sketch.get_num_constraints(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend() -> Result<(), Box<dyn Error>> {
+        let mut sketch = Sketch::new();
+        let line = add_line(&mut sketch, 0.0, 0.0, 1.0, 0.0)?;
+        add_line(&mut sketch, 3.0, -1.0, 3.0, 1.0)?;
+        let center = sketch.add_point2(0.0, 5.0)?;
+        let arc = sketch.add_arc(center, 1.0, false, 0.0, PI / 2.0)?;
+        add_line(&mut sketch, -0.5, 3.0, -0.5, 7.0)?;
+        let line_id = sketch
+            .get_primitive_id(&PrimitiveCell::Line(line.clone()))
+            .ok_or("the line should be in the sketch")?;
+        let arc_id = sketch
+            .get_primitive_id(&PrimitiveCell::Arc(arc.clone()))
+            .ok_or("the arc should be in the sketch")?;
+
+        sketch.extend(line_id, Vector2::new(0.9, 0.0))?;
+        assert!((line.borrow().end().borrow().data() - Vector2::new(3.0, 0.0)).norm() < 1e-9);
+        assert!(matches!(
+            sketch.extend(line_id, Vector2::new(0.1, 0.0)),
+            Err(ISOTopeError::NothingToExtendTo(_))
+        ));
+
+        sketch.extend(arc_id, Vector2::new(0.0, 6.0))?;
+        assert!((arc.borrow().end_angle() - 2.0 * PI / 3.0).abs() < 1e-9);
+        Ok(())
+    }
+}
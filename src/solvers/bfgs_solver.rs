@@ -115,6 +115,7 @@ impl Solver for BFGSSolver {
             iterations += 1;
         }
 
+        sketch.update_driven_dimensions()?;
         Ok(())
     }
 }
@@ -59,6 +59,7 @@ impl Solver for GaussNewtonSolver {
 
             iterations += 1;
         }
+        sketch.update_driven_dimensions()?;
         Ok(())
     }
 }
@@ -65,6 +65,7 @@ impl Solver for GradientBasedSolver {
             iterations += 1;
         }
 
+        sketch.update_driven_dimensions()?;
         Ok(())
     }
 }
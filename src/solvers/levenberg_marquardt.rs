@@ -70,6 +70,7 @@ impl Solver for LevenbergMarquardtSolver {
 
             iterations += 1;
         }
+        sketch.update_driven_dimensions()?;
         Ok(())
     }
 }
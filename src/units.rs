@@ -0,0 +1,221 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+// The unit of all lengths in a sketch: coordinates, radii and length dimensions
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum LengthUnit {
+    #[default]
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "mm" => Some(LengthUnit::Millimeter),
+            "cm" => Some(LengthUnit::Centimeter),
+            "m" => Some(LengthUnit::Meter),
+            "in" => Some(LengthUnit::Inch),
+            "ft" => Some(LengthUnit::Foot),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Inch => "in",
+            LengthUnit::Foot => "ft",
+        }
+    }
+
+    pub fn millimeters(&self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Centimeter => 10.0,
+            LengthUnit::Meter => 1000.0,
+            LengthUnit::Inch => 25.4,
+            LengthUnit::Foot => 304.8,
+        }
+    }
+
+    // The factor that converts a value in this unit to the other unit
+    pub fn factor_to(&self, other: LengthUnit) -> f64 {
+        if *self == other {
+            1.0
+        } else {
+            self.millimeters() / other.millimeters()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum AngleUnit {
+    Radian,
+    Degree,
+}
+
+impl AngleUnit {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "rad" => Some(AngleUnit::Radian),
+            "deg" => Some(AngleUnit::Degree),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AngleUnit::Radian => "rad",
+            AngleUnit::Degree => "deg",
+        }
+    }
+}
+
+// Either kind of unit, as written after a value
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Unit {
+    Length(LengthUnit),
+    Angle(AngleUnit),
+}
+
+impl Unit {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        LengthUnit::from_symbol(symbol)
+            .map(Unit::Length)
+            .or_else(|| AngleUnit::from_symbol(symbol).map(Unit::Angle))
+    }
+
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Unit::Length(_) => Quantity::Length,
+            Unit::Angle(_) => Quantity::Angle,
+        }
+    }
+}
+
+// What a dimension measures
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Quantity {
+    Length,
+    Angle,
+}
+
+// A length together with its unit
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Length {
+    value: f64,
+    unit: LengthUnit,
+}
+
+impl Length {
+    pub fn new(value: f64, unit: LengthUnit) -> Self {
+        assert!(value.is_finite());
+        Self { value, unit }
+    }
+
+    pub fn millimeters(value: f64) -> Self {
+        Self::new(value, LengthUnit::Millimeter)
+    }
+
+    pub fn inches(value: f64) -> Self {
+        Self::new(value, LengthUnit::Inch)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> LengthUnit {
+        self.unit
+    }
+
+    // The value of the length in the given unit
+    pub fn to(&self, unit: LengthUnit) -> f64 {
+        self.value * self.unit.factor_to(unit)
+    }
+}
+
+// An angle. It can only be created with an explicit unit, so degrees and radians can not be mixed
+// up by accident.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Angle {
+    radians: f64,
+}
+
+impl Angle {
+    pub fn new(value: f64, unit: AngleUnit) -> Self {
+        match unit {
+            AngleUnit::Radian => Self::radians(value),
+            AngleUnit::Degree => Self::degrees(value),
+        }
+    }
+
+    pub fn radians(value: f64) -> Self {
+        assert!(value.is_finite());
+        Self { radians: value }
+    }
+
+    pub fn degrees(value: f64) -> Self {
+        Self::radians(value * PI / 180.0)
+    }
+
+    pub fn to_radians(&self) -> f64 {
+        self.radians
+    }
+
+    pub fn to_degrees(&self) -> f64 {
+        self.radians * 180.0 / PI
+    }
+
+    pub fn to(&self, unit: AngleUnit) -> f64 {
+        match unit {
+            AngleUnit::Radian => self.to_radians(),
+            AngleUnit::Degree => self.to_degrees(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{Angle, Length, LengthUnit, Unit};
+
+    #[test]
+    fn test_conversions() {
+        assert!((Length::inches(2.0).to(LengthUnit::Millimeter) - 50.8).abs() < 1e-12);
+        assert!((Length::new(1.0, LengthUnit::Foot).to(LengthUnit::Inch) - 12.0).abs() < 1e-12);
+        assert_eq!(Length::millimeters(3.5).to(LengthUnit::Millimeter), 3.5);
+
+        assert!((Angle::degrees(90.0).to_radians() - 0.5 * PI).abs() < 1e-15);
+        assert!((Angle::radians(PI).to_degrees() - 180.0).abs() < 1e-12);
+
+        assert_eq!(
+            Unit::from_symbol("in"),
+            Some(Unit::Length(LengthUnit::Inch))
+        );
+        assert_eq!(Unit::from_symbol("inch"), None);
+    }
+}